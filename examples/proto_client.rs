@@ -86,6 +86,7 @@ async fn main() -> Result<(), tonic::Status> {
         .commit_index(proto::CommitIndexRequest {
             index_name: "test_index".to_string(),
             with_hotcache: false,
+            transaction_id: None,
         })
         .await?;
     let search_response = search_api_client
@@ -115,6 +116,7 @@ async fn main() -> Result<(), tonic::Status> {
             is_fieldnorms_scoring_enabled: None,
             load_cache: None,
             store_cache: None,
+            timeout_ms: None,
         })
         .await?;
     println!("{:?}", search_response);
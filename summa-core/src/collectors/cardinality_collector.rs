@@ -0,0 +1,227 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::BuildHasherDefault;
+
+use hyperloglogplus::{HyperLogLog, HyperLogLogPlus};
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+use super::value_column::ValueColumn;
+
+/// Sketches have to share the hasher for being mergeable
+type Sketch = HyperLogLogPlus<[u8], BuildHasherDefault<DefaultHasher>>;
+
+/// Distinct values of the field that are kept exactly until their number exceeds the threshold
+pub enum DistinctValues {
+    Exact(HashSet<Vec<u8>>),
+    Approximate(Sketch),
+}
+
+impl DistinctValues {
+    fn sketch(values: &HashSet<Vec<u8>>, precision: u8) -> Sketch {
+        let mut sketch = Sketch::new(precision, BuildHasherDefault::default()).expect("precision should be validated");
+        for value in values {
+            sketch.insert(value.as_slice());
+        }
+        sketch
+    }
+
+    fn insert(&mut self, value: Vec<u8>, precision: u8, exact_threshold: usize) {
+        match self {
+            DistinctValues::Exact(values) => {
+                values.insert(value);
+                if values.len() > exact_threshold {
+                    *self = DistinctValues::Approximate(DistinctValues::sketch(values, precision));
+                }
+            }
+            DistinctValues::Approximate(sketch) => sketch.insert(value.as_slice()),
+        }
+    }
+
+    fn merge(self, other: DistinctValues, precision: u8, exact_threshold: usize) -> tantivy::Result<DistinctValues> {
+        Ok(match (self, other) {
+            (DistinctValues::Exact(mut values), DistinctValues::Exact(other_values)) => {
+                values.extend(other_values);
+                if values.len() > exact_threshold {
+                    DistinctValues::Approximate(DistinctValues::sketch(&values, precision))
+                } else {
+                    DistinctValues::Exact(values)
+                }
+            }
+            (DistinctValues::Exact(values), DistinctValues::Approximate(mut sketch))
+            | (DistinctValues::Approximate(mut sketch), DistinctValues::Exact(values)) => {
+                for value in &values {
+                    sketch.insert(value.as_slice());
+                }
+                DistinctValues::Approximate(sketch)
+            }
+            (DistinctValues::Approximate(mut sketch), DistinctValues::Approximate(other_sketch)) => {
+                sketch
+                    .merge(&other_sketch)
+                    .map_err(|error| tantivy::TantivyError::InternalError(format!("cannot merge sketches: {error:?}")))?;
+                DistinctValues::Approximate(sketch)
+            }
+        })
+    }
+
+    /// Returns the number of distinct values and whether it is exact
+    pub fn cardinality(self) -> (u64, bool) {
+        match self {
+            DistinctValues::Exact(values) => (values.len() as u64, true),
+            DistinctValues::Approximate(mut sketch) => (sketch.count().round() as u64, false),
+        }
+    }
+}
+
+/// `Cardinality` collector counts distinct values of the fast field among matched documents
+///
+/// Values are counted exactly while their number does not exceed `exact_threshold`. Above it, the collector switches
+/// to HyperLogLog++ sketches that are built per segment and merged afterwards. `precision` sets the number of sketch registers
+/// to `2^precision` and trades memory for accuracy.
+pub struct Cardinality {
+    field_name: String,
+    precision: u8,
+    exact_threshold: usize,
+}
+
+impl Cardinality {
+    pub const MIN_PRECISION: u8 = 4;
+    pub const MAX_PRECISION: u8 = 18;
+
+    /// Creates collector, `precision` must lie in `[MIN_PRECISION, MAX_PRECISION]`
+    pub fn new(field_name: &str, precision: u8, exact_threshold: usize) -> Self {
+        Cardinality {
+            field_name: field_name.to_string(),
+            precision,
+            exact_threshold,
+        }
+    }
+
+    fn segment_collector(&self, cardinality_column: Option<ValueColumn>) -> SegmentCardinality {
+        SegmentCardinality {
+            cardinality_column,
+            values: HashSet::new(),
+            approximate: None,
+            precision: self.precision,
+            exact_threshold: self.exact_threshold,
+        }
+    }
+}
+
+#[async_trait]
+impl Collector for Cardinality {
+    type Fruit = DistinctValues;
+
+    type Child = SegmentCardinality;
+
+    fn for_segment(&self, _: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(self.segment_collector(ValueColumn::open(segment_reader, &self.field_name)?))
+    }
+
+    async fn for_segment_async(&self, _: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(self.segment_collector(ValueColumn::open_async(segment_reader, &self.field_name).await?))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<tantivy::Result<DistinctValues>>) -> tantivy::Result<DistinctValues> {
+        let mut distinct_values = DistinctValues::Exact(HashSet::new());
+        for segment_fruit in segment_fruits {
+            distinct_values = distinct_values.merge(segment_fruit?, self.precision, self.exact_threshold)?;
+        }
+        Ok(distinct_values)
+    }
+}
+
+pub struct SegmentCardinality {
+    cardinality_column: Option<ValueColumn>,
+    // Term ordinals for strings are bounded by the size of the segment dictionary and resolved once on harvesting,
+    // numeric values are moved to `approximate` after exceeding the threshold
+    values: HashSet<u64>,
+    approximate: Option<Sketch>,
+    precision: u8,
+    exact_threshold: usize,
+}
+
+impl SegmentCollector for SegmentCardinality {
+    type Fruit = tantivy::Result<DistinctValues>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        match &self.cardinality_column {
+            Some(ValueColumn::Str(str_column)) => self.values.extend(str_column.ords().values_for_doc(doc)),
+            Some(ValueColumn::Numeric(column)) => {
+                for value in column.values_for_doc(doc) {
+                    match &mut self.approximate {
+                        Some(sketch) => sketch.insert(value.to_le_bytes().as_slice()),
+                        None => {
+                            self.values.insert(value);
+                            if self.values.len() > self.exact_threshold {
+                                let mut sketch = Sketch::new(self.precision, BuildHasherDefault::default()).expect("precision should be validated");
+                                for value in self.values.drain() {
+                                    sketch.insert(value.to_le_bytes().as_slice());
+                                }
+                                self.approximate = Some(sketch);
+                            }
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        if let Some(sketch) = self.approximate {
+            return Ok(DistinctValues::Approximate(sketch));
+        }
+        let mut distinct_values = DistinctValues::Exact(HashSet::new());
+        match &self.cardinality_column {
+            Some(ValueColumn::Str(str_column)) => {
+                let mut value = String::new();
+                for ord in self.values {
+                    value.clear();
+                    str_column.ord_to_str(ord, &mut value)?;
+                    distinct_values.insert(value.as_bytes().to_vec(), self.precision, self.exact_threshold);
+                }
+            }
+            Some(ValueColumn::Numeric(_)) => {
+                for value in self.values {
+                    distinct_values.insert(value.to_le_bytes().to_vec(), self.precision, self.exact_threshold);
+                }
+            }
+            None => {}
+        }
+        Ok(distinct_values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::doc;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{Schema, FAST, STRING};
+
+    use super::Cardinality;
+    use crate::components::test_utils::create_test_index;
+
+    #[test]
+    fn test_cardinality() {
+        let mut schema_builder = Schema::builder();
+        let author = schema_builder.add_text_field("author", STRING | FAST);
+        let year = schema_builder.add_u64_field("year", FAST);
+        let documents = (0..1000u64)
+            .map(|i| doc!(author => format!("author_{}", i % 100), year => i))
+            .collect::<Vec<_>>();
+        let index = create_test_index(schema_builder.build(), documents.chunks(300).map(<[_]>::to_vec));
+
+        let searcher = index.reader().unwrap().searcher();
+        let cardinality = searcher.search(&AllQuery, &Cardinality::new("author", 14, 200)).unwrap().cardinality();
+        assert_eq!(cardinality, (100, true));
+
+        let (count, is_exact) = searcher.search(&AllQuery, &Cardinality::new("year", 14, 200)).unwrap().cardinality();
+        assert!(!is_exact);
+        assert!((950..=1050).contains(&count));
+    }
+}
@@ -0,0 +1,380 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use tantivy::collector::{Collector, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector};
+use tantivy::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+use super::value_column::ValueColumn;
+
+/// Value that documents are grouped by
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum CollapseKey {
+    /// Value of numeric, date or boolean fast field mapped to `u64`
+    Numeric(u64),
+    Str(String),
+    /// Documents without a value of the collapsing field are never grouped with other documents
+    Missing(DocAddress),
+}
+
+/// Documents sharing the same `CollapseKey`
+#[derive(Clone, Debug)]
+pub struct CollapsedGroup<TScore> {
+    pub key: CollapseKey,
+    /// Best documents of the group in descending order of score. The first one represents the whole group
+    pub hits: Vec<(TScore, DocAddress)>,
+    /// Number of matched documents in the group. Segments where the group does not get into
+    /// the top `offset + limit` groups are not counted, so the size is exact only for single-segment indices
+    pub size: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CollapsedTopDocs<TScore> {
+    pub groups: Vec<CollapsedGroup<TScore>>,
+    /// Lower bound of the number of groups, exact for single-segment indices
+    pub groups_count: u64,
+}
+
+/// `CollapsingTopDocs` collector returns top groups of documents sharing the same value of the fast field
+///
+/// Groups are ordered by the best score of their documents. Scores are produced by `ScoreTweaker` so the collector
+/// works with relevance scores as well as with custom scorers. Every segment returns only its top `offset + limit` groups
+/// that is enough for finding the top groups of the whole index because every group gets into the top of the segment
+/// containing its best document.
+pub struct CollapsingTopDocs<TScoreTweaker, TScore> {
+    score_tweaker: TScoreTweaker,
+    field_name: String,
+    limit: usize,
+    offset: usize,
+    inner_hits_limit: usize,
+    min_score: Option<TScore>,
+    _score: PhantomData<fn() -> TScore>,
+}
+
+impl<TScoreTweaker, TScore: PartialOrd> CollapsingTopDocs<TScoreTweaker, TScore> {
+    /// Creates collector returning `limit` groups after skipping `offset` ones.
+    /// Every group keeps `inner_hits_limit` documents besides the best one
+    pub fn new(score_tweaker: TScoreTweaker, field_name: &str, limit: usize, offset: usize, inner_hits_limit: usize) -> Self {
+        CollapsingTopDocs {
+            score_tweaker,
+            field_name: field_name.to_string(),
+            limit,
+            offset,
+            inner_hits_limit,
+            min_score: None,
+            _score: PhantomData,
+        }
+    }
+
+    /// Drops documents scored lower than `min_score` so they neither form groups nor count in their sizes
+    pub fn with_min_score(mut self, min_score: TScore) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    fn hits_per_group(&self) -> usize {
+        self.inner_hits_limit + 1
+    }
+
+    fn segment_collector<TSegmentScoreTweaker>(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment_score_tweaker: TSegmentScoreTweaker,
+        group_column: Option<ValueColumn>,
+    ) -> SegmentCollapsingTopDocs<TSegmentScoreTweaker, TScore>
+    where
+        TScore: Clone,
+    {
+        SegmentCollapsingTopDocs {
+            segment_ord,
+            segment_score_tweaker,
+            min_score: self.min_score.clone(),
+            group_column,
+            groups: HashMap::new(),
+            groups_limit: self.offset + self.limit,
+            hits_per_group: self.hits_per_group(),
+            missing: TopHits::new(self.offset + self.limit),
+            missing_count: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl<TScoreTweaker, TScore> Collector for CollapsingTopDocs<TScoreTweaker, TScore>
+where
+    TScoreTweaker: ScoreTweaker<TScore> + Send + Sync,
+    TScoreTweaker::Child: Send,
+    TScore: 'static + PartialOrd + Clone + Send + Sync,
+{
+    type Fruit = CollapsedTopDocs<TScore>;
+
+    type Child = SegmentCollapsingTopDocs<TScoreTweaker::Child, TScore>;
+
+    fn for_segment(&self, segment_ord: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(self.segment_collector(
+            segment_ord,
+            self.score_tweaker.segment_tweaker(segment_reader)?,
+            ValueColumn::open(segment_reader, &self.field_name)?,
+        ))
+    }
+
+    async fn for_segment_async(&self, segment_ord: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(self.segment_collector(
+            segment_ord,
+            self.score_tweaker.segment_tweaker_async(segment_reader).await?,
+            ValueColumn::open_async(segment_reader, &self.field_name).await?,
+        ))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<tantivy::Result<SegmentCollapsedTopDocs<TScore>>>) -> tantivy::Result<CollapsedTopDocs<TScore>> {
+        let mut groups: HashMap<CollapseKey, CollapsedGroup<TScore>> = HashMap::new();
+        let mut missing = vec![];
+        let mut missing_count = 0;
+        let mut max_segment_groups_count = 0;
+        for segment_fruit in segment_fruits {
+            let segment_fruit = segment_fruit?;
+            max_segment_groups_count = max_segment_groups_count.max(segment_fruit.groups_count);
+            for segment_group in segment_fruit.groups {
+                match groups.entry(segment_group.key.clone()) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(segment_group);
+                    }
+                    Entry::Occupied(mut entry) => {
+                        let group = entry.get_mut();
+                        group.size += segment_group.size;
+                        group.hits.extend(segment_group.hits);
+                        group.hits.sort_by(compare_hits);
+                        group.hits.truncate(self.hits_per_group());
+                    }
+                }
+            }
+            missing.extend(segment_fruit.missing);
+            missing_count += segment_fruit.missing_count;
+        }
+        let groups_count = (groups.len() as u64).max(max_segment_groups_count) + missing_count;
+        let mut groups: Vec<_> = groups
+            .into_values()
+            .chain(missing.into_iter().map(|hit| CollapsedGroup {
+                key: CollapseKey::Missing(hit.1),
+                hits: vec![hit],
+                size: 1,
+            }))
+            .collect();
+        groups.sort_by(|left, right| compare_hits(&left.hits[0], &right.hits[0]));
+        Ok(CollapsedTopDocs {
+            groups: groups.into_iter().skip(self.offset).take(self.limit).collect(),
+            groups_count,
+        })
+    }
+}
+
+/// Orders hits by descending score and then by ascending address to make results stable
+pub(super) fn compare_hits<TScore: PartialOrd>(left: &(TScore, DocAddress), right: &(TScore, DocAddress)) -> Ordering {
+    right.0.partial_cmp(&left.0).unwrap_or(Ordering::Equal).then_with(|| left.1.cmp(&right.1))
+}
+
+impl ValueColumn {
+    fn group_ord(&self, doc: DocId) -> Option<u64> {
+        match self {
+            ValueColumn::Numeric(column) => column.first(doc),
+            ValueColumn::Str(str_column) => str_column.ords().first(doc),
+        }
+    }
+
+    fn key(&self, group_ord: u64) -> tantivy::Result<CollapseKey> {
+        match self {
+            ValueColumn::Numeric(_) => Ok(CollapseKey::Numeric(group_ord)),
+            ValueColumn::Str(str_column) => {
+                let mut value = String::new();
+                str_column.ord_to_str(group_ord, &mut value)?;
+                Ok(CollapseKey::Str(value))
+            }
+        }
+    }
+}
+
+/// Keeps best `limit` hits, sorting is deferred until the buffer grows twice
+pub(super) struct TopHits<TScore> {
+    hits: Vec<(TScore, DocId)>,
+    limit: usize,
+}
+
+impl<TScore: PartialOrd> TopHits<TScore> {
+    pub(super) fn new(limit: usize) -> Self {
+        TopHits { hits: vec![], limit }
+    }
+
+    pub(super) fn push(&mut self, score: TScore, doc: DocId) {
+        self.hits.push((score, doc));
+        if self.hits.len() >= 2 * self.limit.max(1) {
+            self.truncate();
+        }
+    }
+
+    fn truncate(&mut self) {
+        self.hits
+            .sort_by(|left, right| right.0.partial_cmp(&left.0).unwrap_or(Ordering::Equal).then_with(|| left.1.cmp(&right.1)));
+        self.hits.truncate(self.limit);
+    }
+
+    pub(super) fn into_doc_addresses(mut self, segment_ord: SegmentOrdinal) -> Vec<(TScore, DocAddress)> {
+        self.truncate();
+        self.hits.into_iter().map(|(score, doc)| (score, DocAddress::new(segment_ord, doc))).collect()
+    }
+}
+
+struct SegmentGroup<TScore> {
+    hits: TopHits<TScore>,
+    size: u64,
+}
+
+pub struct SegmentCollapsedTopDocs<TScore> {
+    groups: Vec<CollapsedGroup<TScore>>,
+    groups_count: u64,
+    missing: Vec<(TScore, DocAddress)>,
+    missing_count: u64,
+}
+
+pub struct SegmentCollapsingTopDocs<TSegmentScoreTweaker, TScore> {
+    segment_ord: SegmentOrdinal,
+    segment_score_tweaker: TSegmentScoreTweaker,
+    min_score: Option<TScore>,
+    group_column: Option<ValueColumn>,
+    // Every group is kept until the end of the segment for computing exact sizes of groups
+    groups: HashMap<u64, SegmentGroup<TScore>>,
+    groups_limit: usize,
+    hits_per_group: usize,
+    missing: TopHits<TScore>,
+    missing_count: u64,
+}
+
+impl<TSegmentScoreTweaker, TScore> SegmentCollector for SegmentCollapsingTopDocs<TSegmentScoreTweaker, TScore>
+where
+    TSegmentScoreTweaker: 'static + ScoreSegmentTweaker<TScore> + Send,
+    TScore: 'static + PartialOrd + Clone + Send + Sync,
+{
+    type Fruit = tantivy::Result<SegmentCollapsedTopDocs<TScore>>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let score = self.segment_score_tweaker.score(doc, score);
+        if matches!(&self.min_score, Some(min_score) if score < *min_score) {
+            return;
+        }
+        match self.group_column.as_ref().and_then(|group_column| group_column.group_ord(doc)) {
+            Some(group_ord) => {
+                let group = self.groups.entry(group_ord).or_insert_with(|| SegmentGroup {
+                    hits: TopHits::new(self.hits_per_group),
+                    size: 0,
+                });
+                group.size += 1;
+                group.hits.push(score, doc);
+            }
+            None => {
+                self.missing_count += 1;
+                self.missing.push(score, doc);
+            }
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let segment_ord = self.segment_ord;
+        let groups_count = self.groups.len() as u64;
+        let mut top_groups: Vec<_> = self
+            .groups
+            .into_iter()
+            .map(|(group_ord, group)| (group_ord, group.hits.into_doc_addresses(segment_ord), group.size))
+            .collect();
+        if top_groups.len() > self.groups_limit {
+            top_groups.select_nth_unstable_by(self.groups_limit, |left, right| compare_hits(&left.1[0], &right.1[0]));
+            top_groups.truncate(self.groups_limit);
+        }
+        // Keys are resolved only for the groups leaving the segment
+        let groups = match &self.group_column {
+            Some(group_column) => top_groups
+                .into_iter()
+                .map(|(group_ord, hits, size)| {
+                    Ok(CollapsedGroup {
+                        key: group_column.key(group_ord)?,
+                        hits,
+                        size,
+                    })
+                })
+                .collect::<tantivy::Result<Vec<_>>>()?,
+            None => vec![],
+        };
+        Ok(SegmentCollapsedTopDocs {
+            groups,
+            groups_count,
+            missing: self.missing.into_doc_addresses(segment_ord),
+            missing_count: self.missing_count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::doc;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{Schema, FAST, STRING};
+
+    use super::{CollapseKey, CollapsingTopDocs};
+    use crate::components::test_utils::create_test_index;
+    use crate::scorers::score_tweakers::FastFieldScoreTweaker;
+
+    #[test]
+    fn test_collapsing() {
+        let mut schema_builder = Schema::builder();
+        let group_id = schema_builder.add_text_field("group_id", STRING | FAST);
+        let rank = schema_builder.add_i64_field("rank", FAST);
+        let schema = schema_builder.build();
+        let index = create_test_index(
+            schema.clone(),
+            [
+                vec![doc!(group_id => "a", rank => -1i64), doc!(group_id => "a", rank => 5i64)],
+                vec![doc!(group_id => "b", rank => -3i64), doc!(group_id => "a", rank => 4i64), doc!(rank => 2i64)],
+            ],
+        );
+
+        let searcher = index.reader().unwrap().searcher();
+        let score_tweaker = || FastFieldScoreTweaker::new("rank", &schema).unwrap();
+        let collapsed = searcher
+            .search(&AllQuery, &CollapsingTopDocs::new(score_tweaker(), "group_id", 10, 0, 1))
+            .unwrap();
+        assert_eq!(collapsed.groups_count, 3);
+        assert_eq!(
+            collapsed
+                .groups
+                .iter()
+                .map(|group| (group.key.clone(), group.hits.iter().map(|hit| hit.0).collect::<Vec<_>>(), group.size))
+                .collect::<Vec<_>>(),
+            vec![
+                (CollapseKey::Str("a".to_string()), vec![5.0, 4.0], 3),
+                (collapsed.groups[1].key.clone(), vec![2.0], 1),
+                (CollapseKey::Str("b".to_string()), vec![-3.0], 1),
+            ]
+        );
+        assert!(matches!(collapsed.groups[1].key, CollapseKey::Missing(_)));
+
+        let collapsed = searcher
+            .search(&AllQuery, &CollapsingTopDocs::new(score_tweaker(), "group_id", 1, 2, 0))
+            .unwrap();
+        assert_eq!(collapsed.groups_count, 3);
+        assert_eq!(collapsed.groups.len(), 1);
+        assert_eq!(collapsed.groups[0].key, CollapseKey::Str("b".to_string()));
+        assert_eq!(collapsed.groups[0].hits.len(), 1);
+
+        // The second segment returns only group `a` but still counts `b`
+        let collapsed = searcher
+            .search(&AllQuery, &CollapsingTopDocs::new(score_tweaker(), "group_id", 1, 0, 0))
+            .unwrap();
+        assert_eq!(collapsed.groups_count, 3);
+        assert_eq!(collapsed.groups.len(), 1);
+        assert_eq!(collapsed.groups[0].key, CollapseKey::Str("a".to_string()));
+        assert_eq!(collapsed.groups[0].size, 3);
+    }
+}
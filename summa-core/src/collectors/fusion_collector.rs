@@ -0,0 +1,266 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use tantivy::collector::{Collector, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector};
+use tantivy::fastfield::AliveBitSet;
+use tantivy::query::{EnableScoring, Query, Scorer};
+use tantivy::{DocAddress, DocId, DocSet, Score, Searcher, SegmentOrdinal, SegmentReader, TERMINATED};
+
+use super::collapsing_top_docs_collector::{compare_hits, TopHits};
+
+/// The way rankings of sources are merged
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FusionMethod {
+    /// Sum of `weight / (rank_constant + rank)` over sources
+    ReciprocalRank { rank_constant: f64 },
+    /// Sum of `weight * score` over sources, scores are min-max normalized within windows of sources
+    WeightedScore,
+}
+
+/// Ranked list of documents matching `query` and scored by `score_tweaker`
+pub struct FusionSource<TScoreTweaker> {
+    query: Box<dyn Query>,
+    score_tweaker: TScoreTweaker,
+    weight: f64,
+}
+
+impl<TScoreTweaker> FusionSource<TScoreTweaker> {
+    pub fn new(query: Box<dyn Query>, score_tweaker: TScoreTweaker, weight: f64) -> Self {
+        FusionSource { query, score_tweaker, weight }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FusedHit {
+    pub score: f64,
+    pub doc_address: DocAddress,
+    /// Rank starting from `1` and score of the document in every source, `None` if the source has not retrieved the document
+    pub source_hits: Vec<Option<(u32, f64)>>,
+}
+
+/// `Fusion` collector retrieves best `window_size` documents of every source and merges their rankings
+///
+/// Sources are searched with their own queries over the same segments, documents streamed by the search query are ignored.
+pub struct Fusion<TScoreTweaker> {
+    searcher: Searcher,
+    sources: Vec<FusionSource<TScoreTweaker>>,
+    method: FusionMethod,
+    window_size: usize,
+    limit: usize,
+    offset: usize,
+}
+
+impl<TScoreTweaker> Fusion<TScoreTweaker> {
+    /// Creates collector returning `limit` fused documents after skipping `offset` ones
+    pub fn new(searcher: Searcher, sources: Vec<FusionSource<TScoreTweaker>>, method: FusionMethod, window_size: usize, limit: usize, offset: usize) -> Self {
+        Fusion {
+            searcher,
+            sources,
+            method,
+            window_size,
+            limit,
+            offset,
+        }
+    }
+
+    fn contribution(&self, source: &FusionSource<TScoreTweaker>, rank: u32, score: f64, min_score: f64, max_score: f64) -> f64 {
+        match self.method {
+            FusionMethod::ReciprocalRank { rank_constant } => source.weight / (rank_constant + rank as f64),
+            FusionMethod::WeightedScore if max_score > min_score => source.weight * (score - min_score) / (max_score - min_score),
+            // All documents of the source window are scored equally
+            FusionMethod::WeightedScore => source.weight,
+        }
+    }
+}
+
+#[async_trait]
+impl<TScoreTweaker> Collector for Fusion<TScoreTweaker>
+where
+    TScoreTweaker: ScoreTweaker<f64> + Send + Sync,
+    TScoreTweaker::Child: Send,
+{
+    type Fruit = Vec<FusedHit>;
+
+    type Child = SegmentFusion<TScoreTweaker::Child>;
+
+    fn for_segment(&self, segment_ord: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        let mut segment_sources = Vec::with_capacity(self.sources.len());
+        for source in &self.sources {
+            let weight = source.query.weight(EnableScoring::enabled_from_searcher(&self.searcher))?;
+            segment_sources.push(SegmentFusionSource {
+                scorer: weight.scorer(segment_reader, 1.0)?,
+                segment_score_tweaker: source.score_tweaker.segment_tweaker(segment_reader)?,
+            });
+        }
+        Ok(SegmentFusion {
+            segment_ord,
+            alive_bitset: segment_reader.alive_bitset().cloned(),
+            segment_sources,
+            window_size: self.window_size,
+        })
+    }
+
+    async fn for_segment_async(&self, segment_ord: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        let mut segment_sources = Vec::with_capacity(self.sources.len());
+        for source in &self.sources {
+            let weight = source.query.weight_async(EnableScoring::enabled_from_searcher(&self.searcher)).await?;
+            segment_sources.push(SegmentFusionSource {
+                scorer: weight.scorer_async(segment_reader, 1.0).await?,
+                segment_score_tweaker: source.score_tweaker.segment_tweaker_async(segment_reader).await?,
+            });
+        }
+        Ok(SegmentFusion {
+            segment_ord,
+            alive_bitset: segment_reader.alive_bitset().cloned(),
+            segment_sources,
+            window_size: self.window_size,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Vec<Vec<(f64, DocAddress)>>>) -> tantivy::Result<Vec<FusedHit>> {
+        let mut source_windows = vec![vec![]; self.sources.len()];
+        for segment_fruit in segment_fruits {
+            for (source_window, segment_hits) in source_windows.iter_mut().zip(segment_fruit) {
+                source_window.extend(segment_hits);
+            }
+        }
+        let mut fused_hits: HashMap<DocAddress, FusedHit> = HashMap::new();
+        for (source_ord, (source, mut source_window)) in self.sources.iter().zip(source_windows).enumerate() {
+            source_window.sort_by(compare_hits);
+            source_window.truncate(self.window_size);
+            let min_score = source_window.iter().map(|(score, _)| *score).fold(f64::INFINITY, f64::min);
+            let max_score = source_window.iter().map(|(score, _)| *score).fold(f64::NEG_INFINITY, f64::max);
+            for (position, (score, doc_address)) in source_window.into_iter().enumerate() {
+                let rank = position as u32 + 1;
+                let fused_hit = fused_hits.entry(doc_address).or_insert_with(|| FusedHit {
+                    score: 0.0,
+                    doc_address,
+                    source_hits: vec![None; self.sources.len()],
+                });
+                fused_hit.score += self.contribution(source, rank, score, min_score, max_score);
+                fused_hit.source_hits[source_ord] = Some((rank, score));
+            }
+        }
+        let mut fused_hits: Vec<_> = fused_hits.into_values().collect();
+        fused_hits.sort_by(|left, right| {
+            right
+                .score
+                .partial_cmp(&left.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.doc_address.cmp(&right.doc_address))
+        });
+        Ok(fused_hits.into_iter().skip(self.offset).take(self.limit).collect())
+    }
+}
+
+struct SegmentFusionSource<TSegmentScoreTweaker> {
+    scorer: Box<dyn Scorer>,
+    segment_score_tweaker: TSegmentScoreTweaker,
+}
+
+pub struct SegmentFusion<TSegmentScoreTweaker> {
+    segment_ord: SegmentOrdinal,
+    alive_bitset: Option<AliveBitSet>,
+    segment_sources: Vec<SegmentFusionSource<TSegmentScoreTweaker>>,
+    window_size: usize,
+}
+
+impl<TSegmentScoreTweaker> SegmentCollector for SegmentFusion<TSegmentScoreTweaker>
+where
+    TSegmentScoreTweaker: 'static + ScoreSegmentTweaker<f64> + Send,
+{
+    type Fruit = Vec<Vec<(f64, DocAddress)>>;
+
+    fn collect(&mut self, _: DocId, _: Score) {}
+
+    fn harvest(self) -> Self::Fruit {
+        let alive_bitset = self.alive_bitset;
+        self.segment_sources
+            .into_iter()
+            .map(|mut segment_source| {
+                let mut hits = TopHits::new(self.window_size);
+                let mut doc = segment_source.scorer.doc();
+                while doc != TERMINATED {
+                    if !matches!(&alive_bitset, Some(alive_bitset) if !alive_bitset.is_alive(doc)) {
+                        let score = segment_source.segment_score_tweaker.score(doc, segment_source.scorer.score());
+                        hits.push(score, doc);
+                    }
+                    doc = segment_source.scorer.advance();
+                }
+                hits.into_doc_addresses(self.segment_ord)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::collector::ScoreTweaker;
+    use tantivy::query::{AllQuery, TermQuery};
+    use tantivy::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use tantivy::{doc, DocId, Score, SegmentReader, Term};
+
+    use super::{Fusion, FusionMethod, FusionSource};
+    use crate::components::test_utils::create_test_index;
+
+    struct FastFieldScore(&'static str);
+
+    #[async_trait]
+    impl ScoreTweaker<f64> for FastFieldScore {
+        type Child = Box<dyn FnMut(DocId, Score) -> f64 + Send>;
+
+        fn segment_tweaker(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+            let column = segment_reader.fast_fields().f64(self.0)?;
+            Ok(Box::new(move |doc, _| column.first(doc).unwrap_or_default()))
+        }
+
+        async fn segment_tweaker_async(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+            self.segment_tweaker(segment_reader)
+        }
+    }
+
+    #[test]
+    fn test_fusion() {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let rank = schema_builder.add_f64_field("rank", FAST);
+        let freshness = schema_builder.add_f64_field("freshness", FAST);
+        let index = create_test_index(
+            schema_builder.build(),
+            [("a", 1.0, 10.0), ("a", 3.0, 0.0), ("b", 2.0, 5.0)]
+                .map(|(tag_value, rank_value, freshness_value)| [doc!(tag => tag_value, rank => rank_value, freshness => freshness_value)]),
+        );
+
+        let searcher = index.reader().unwrap().searcher();
+        let sources = || {
+            vec![
+                FusionSource::new(Box::new(AllQuery), FastFieldScore("rank"), 1.0),
+                FusionSource::new(
+                    Box::new(TermQuery::new(Term::from_field_text(tag, "a"), IndexRecordOption::Basic)),
+                    FastFieldScore("freshness"),
+                    1.5,
+                ),
+            ]
+        };
+        let fusion = Fusion::new(searcher.clone(), sources(), FusionMethod::ReciprocalRank { rank_constant: 60.0 }, 10, 10, 0);
+        let fused_hits = searcher.search(&AllQuery, &fusion).unwrap();
+        assert_eq!(
+            fused_hits.iter().map(|fused_hit| fused_hit.source_hits.clone()).collect::<Vec<_>>(),
+            vec![
+                vec![Some((1, 3.0)), Some((2, 0.0))],
+                vec![Some((3, 1.0)), Some((1, 10.0))],
+                vec![Some((2, 2.0)), None],
+            ]
+        );
+        assert!((fused_hits[0].score - (1.0 / 61.0 + 1.5 / 62.0)).abs() < 1e-9);
+
+        let fusion = Fusion::new(searcher.clone(), sources(), FusionMethod::WeightedScore, 10, 1, 0);
+        let fused_hits = searcher.search(&AllQuery, &fusion).unwrap();
+        assert_eq!(fused_hits[0].source_hits, vec![Some((3, 1.0)), Some((1, 10.0))]);
+        assert!((fused_hits[0].score - 1.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,258 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::{ColumnType, StrColumn};
+use tantivy::schema::Facet;
+use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// Separator of facet segments in the encoded form stored in fast fields
+const FACET_SEP: char = '\u{0}';
+
+/// Facet with the number of matched documents and counts of its children
+#[derive(Clone, Debug, PartialEq)]
+pub struct FacetNode {
+    pub facet: Facet,
+    pub count: u64,
+    /// Children in descending order of counts
+    pub children: Vec<FacetNode>,
+}
+
+/// `HierarchicalFacets` collector counts documents for every requested facet and its descendants up to `depth` levels
+///
+/// Every document is counted at most once per node even if it has several facets under the node. Children of every node
+/// are sorted by descending counts and then by facet and truncated to `top_k` if it is set.
+pub struct HierarchicalFacets {
+    field_name: String,
+    facets: Vec<Vec<String>>,
+    depth: usize,
+    top_k: Option<usize>,
+}
+
+impl HierarchicalFacets {
+    /// Creates collector counting children of `facets` or children of the root facet if `facets` is empty
+    pub fn new(field_name: &str, facets: &[Facet], depth: usize, top_k: Option<usize>) -> Self {
+        let facets = if facets.is_empty() {
+            vec![vec![]]
+        } else {
+            facets.iter().map(|facet| facet.to_path().into_iter().map(str::to_string).collect()).collect()
+        };
+        HierarchicalFacets {
+            field_name: field_name.to_string(),
+            facets,
+            depth,
+            top_k,
+        }
+    }
+
+    fn segment_collector(&self, facet_column: Option<StrColumn>) -> SegmentHierarchicalFacets {
+        SegmentHierarchicalFacets {
+            facet_column,
+            facets: self.facets.clone(),
+            depth: self.depth,
+            ord_nodes: HashMap::new(),
+            nodes: HashMap::new(),
+            paths: vec![],
+            counts: vec![],
+            doc_nodes: vec![],
+            error: None,
+        }
+    }
+
+    fn node(&self, path: Vec<String>, counts: &HashMap<Vec<String>, u64>, children: &HashMap<&[String], Vec<&Vec<String>>>, level: usize) -> FacetNode {
+        let mut child_nodes = vec![];
+        if level < self.depth {
+            if let Some(child_paths) = children.get(path.as_slice()) {
+                let mut child_paths = child_paths.clone();
+                child_paths.sort_by(|left, right| compare_counts(counts[*left], left, counts[*right], right));
+                if let Some(top_k) = self.top_k {
+                    child_paths.truncate(top_k);
+                }
+                child_nodes = child_paths
+                    .into_iter()
+                    .map(|child_path| self.node(child_path.clone(), counts, children, level + 1))
+                    .collect();
+            }
+        }
+        FacetNode {
+            count: counts.get(&path).copied().unwrap_or_default(),
+            facet: Facet::from_path(path),
+            children: child_nodes,
+        }
+    }
+}
+
+/// Orders nodes by descending counts and then by ascending paths to make results stable
+fn compare_counts(left_count: u64, left_path: &[String], right_count: u64, right_path: &[String]) -> Ordering {
+    right_count.cmp(&left_count).then_with(|| left_path.cmp(right_path))
+}
+
+async fn open_facet_column_async(segment_reader: &SegmentReader, field_name: &str) -> tantivy::Result<Option<StrColumn>> {
+    match segment_reader.fast_fields().dynamic_column_handle_async(field_name, ColumnType::Str).await? {
+        Some(dynamic_column_handle) => Ok(dynamic_column_handle.open_async().await?.into()),
+        None => Ok(None),
+    }
+}
+
+#[async_trait]
+impl Collector for HierarchicalFacets {
+    type Fruit = Vec<FacetNode>;
+
+    type Child = SegmentHierarchicalFacets;
+
+    fn for_segment(&self, _: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(self.segment_collector(segment_reader.fast_fields().str(&self.field_name)?))
+    }
+
+    async fn for_segment_async(&self, _: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(self.segment_collector(open_facet_column_async(segment_reader, &self.field_name).await?))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<tantivy::Result<HashMap<Vec<String>, u64>>>) -> tantivy::Result<Vec<FacetNode>> {
+        let mut counts: HashMap<Vec<String>, u64> = HashMap::new();
+        for segment_fruit in segment_fruits {
+            for (path, count) in segment_fruit? {
+                *counts.entry(path).or_default() += count;
+            }
+        }
+        let mut children: HashMap<&[String], Vec<&Vec<String>>> = HashMap::new();
+        for path in counts.keys() {
+            if let Some((_, parent)) = path.split_last() {
+                children.entry(parent).or_default().push(path);
+            }
+        }
+        Ok(self.facets.iter().map(|facet| self.node(facet.clone(), &counts, &children, 0)).collect())
+    }
+}
+
+pub struct SegmentHierarchicalFacets {
+    facet_column: Option<StrColumn>,
+    facets: Vec<Vec<String>>,
+    depth: usize,
+    // Nodes affected by the facet ordinal are resolved once per segment
+    ord_nodes: HashMap<u64, Vec<usize>>,
+    nodes: HashMap<Vec<String>, usize>,
+    paths: Vec<Vec<String>>,
+    counts: Vec<u64>,
+    doc_nodes: Vec<usize>,
+    error: Option<std::io::Error>,
+}
+
+impl SegmentHierarchicalFacets {
+    /// Returns indices of requested facets and their descendants that are prefixes of the facet
+    fn resolve_nodes(&mut self, facet_column: &StrColumn, ord: u64) -> std::io::Result<Vec<usize>> {
+        let mut encoded_facet = String::new();
+        facet_column.ord_to_str(ord, &mut encoded_facet)?;
+        let path: Vec<&str> = encoded_facet.split(FACET_SEP).collect();
+        let mut node_indices = vec![];
+        for facet in &self.facets {
+            if facet.len() > path.len() || facet.iter().zip(path.iter()).any(|(left, right)| left != right) {
+                continue;
+            }
+            for length in facet.len()..=std::cmp::min(path.len(), facet.len() + self.depth) {
+                let node_path: Vec<String> = path[..length].iter().map(|segment| segment.to_string()).collect();
+                let node_index = match self.nodes.get(&node_path) {
+                    Some(node_index) => *node_index,
+                    None => {
+                        let node_index = self.paths.len();
+                        self.nodes.insert(node_path.clone(), node_index);
+                        self.paths.push(node_path);
+                        self.counts.push(0);
+                        node_index
+                    }
+                };
+                node_indices.push(node_index);
+            }
+        }
+        Ok(node_indices)
+    }
+}
+
+impl SegmentCollector for SegmentHierarchicalFacets {
+    type Fruit = tantivy::Result<HashMap<Vec<String>, u64>>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let Some(facet_column) = self.facet_column.take() else {
+            return;
+        };
+        self.doc_nodes.clear();
+        for ord in facet_column.ords().values_for_doc(doc) {
+            if !self.ord_nodes.contains_key(&ord) {
+                match self.resolve_nodes(&facet_column, ord) {
+                    Ok(node_indices) => {
+                        self.ord_nodes.insert(ord, node_indices);
+                    }
+                    Err(error) => {
+                        self.error.get_or_insert(error);
+                        continue;
+                    }
+                }
+            }
+            self.doc_nodes.extend_from_slice(&self.ord_nodes[&ord]);
+        }
+        self.doc_nodes.sort_unstable();
+        self.doc_nodes.dedup();
+        for node_index in &self.doc_nodes {
+            self.counts[*node_index] += 1;
+        }
+        self.facet_column = Some(facet_column);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        if let Some(error) = self.error {
+            return Err(error.into());
+        }
+        Ok(self.paths.into_iter().zip(self.counts).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::doc;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{Facet, FacetOptions, Schema};
+
+    use super::HierarchicalFacets;
+    use crate::components::test_utils::create_test_index;
+
+    #[test]
+    fn test_hierarchical_facets() {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_facet_field("category", FacetOptions::default());
+        let index = create_test_index(
+            schema_builder.build(),
+            [
+                [
+                    doc!(category => Facet::from("/genre/fiction/scifi"), category => Facet::from("/genre/fiction/fantasy")),
+                    doc!(category => Facet::from("/genre/fiction/scifi")),
+                ],
+                [doc!(category => Facet::from("/genre/poetry")), doc!(category => Facet::from("/topic/biology"))],
+            ],
+        );
+
+        let searcher = index.reader().unwrap().searcher();
+        let nodes = searcher
+            .search(&AllQuery, &HierarchicalFacets::new("category", &[Facet::from("/genre")], 2, None))
+            .unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].facet, Facet::from("/genre"));
+        assert_eq!(nodes[0].count, 3);
+        let children: Vec<_> = nodes[0].children.iter().map(|node| (node.facet.to_string(), node.count)).collect();
+        assert_eq!(children, vec![("/genre/fiction".to_string(), 2), ("/genre/poetry".to_string(), 1)]);
+        let grandchildren: Vec<_> = nodes[0].children[0].children.iter().map(|node| (node.facet.to_string(), node.count)).collect();
+        assert_eq!(
+            grandchildren,
+            vec![("/genre/fiction/scifi".to_string(), 2), ("/genre/fiction/fantasy".to_string(), 1)]
+        );
+
+        let nodes = searcher.search(&AllQuery, &HierarchicalFacets::new("category", &[], 1, Some(1))).unwrap();
+        assert_eq!(nodes[0].count, 4);
+        let children: Vec<_> = nodes[0].children.iter().map(|node| (node.facet.to_string(), node.count)).collect();
+        assert_eq!(children, vec![("/genre".to_string(), 3)]);
+        assert!(nodes[0].children[0].children.is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use tantivy::collector::{Collector, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector};
+use tantivy::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+use super::collapsing_top_docs_collector::{compare_hits, TopHits};
+
+/// `MinScoreTopDocs` collector returns top documents scored not lower than `min_score`
+///
+/// Documents below the threshold are dropped right after scoring and never occupy the buffer of best hits.
+/// Scores are produced by `ScoreTweaker` so the threshold applies to relevance scores as well as to custom scorers.
+pub struct MinScoreTopDocs<TScoreTweaker, TScore> {
+    score_tweaker: TScoreTweaker,
+    limit: usize,
+    offset: usize,
+    min_score: TScore,
+}
+
+impl<TScoreTweaker, TScore> MinScoreTopDocs<TScoreTweaker, TScore> {
+    /// Creates collector returning `limit` documents after skipping `offset` ones
+    pub fn new(score_tweaker: TScoreTweaker, limit: usize, offset: usize, min_score: TScore) -> Self {
+        MinScoreTopDocs {
+            score_tweaker,
+            limit,
+            offset,
+            min_score,
+        }
+    }
+}
+
+#[async_trait]
+impl<TScoreTweaker, TScore> Collector for MinScoreTopDocs<TScoreTweaker, TScore>
+where
+    TScoreTweaker: ScoreTweaker<TScore> + Send + Sync,
+    TScoreTweaker::Child: Send,
+    TScore: 'static + PartialOrd + Clone + Send + Sync,
+{
+    type Fruit = Vec<(TScore, DocAddress)>;
+
+    type Child = SegmentMinScoreTopDocs<TScoreTweaker::Child, TScore>;
+
+    fn for_segment(&self, segment_ord: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(SegmentMinScoreTopDocs {
+            segment_ord,
+            segment_score_tweaker: self.score_tweaker.segment_tweaker(segment_reader)?,
+            min_score: self.min_score.clone(),
+            hits: TopHits::new(self.offset + self.limit),
+        })
+    }
+
+    async fn for_segment_async(&self, segment_ord: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(SegmentMinScoreTopDocs {
+            segment_ord,
+            segment_score_tweaker: self.score_tweaker.segment_tweaker_async(segment_reader).await?,
+            min_score: self.min_score.clone(),
+            hits: TopHits::new(self.offset + self.limit),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Vec<(TScore, DocAddress)>>) -> tantivy::Result<Vec<(TScore, DocAddress)>> {
+        let mut hits: Vec<_> = segment_fruits.into_iter().flatten().collect();
+        hits.sort_by(compare_hits);
+        Ok(hits.into_iter().skip(self.offset).take(self.limit).collect())
+    }
+}
+
+pub struct SegmentMinScoreTopDocs<TSegmentScoreTweaker, TScore> {
+    segment_ord: SegmentOrdinal,
+    segment_score_tweaker: TSegmentScoreTweaker,
+    min_score: TScore,
+    hits: TopHits<TScore>,
+}
+
+impl<TSegmentScoreTweaker, TScore> SegmentCollector for SegmentMinScoreTopDocs<TSegmentScoreTweaker, TScore>
+where
+    TSegmentScoreTweaker: 'static + ScoreSegmentTweaker<TScore> + Send,
+    TScore: 'static + PartialOrd + Clone + Send + Sync,
+{
+    type Fruit = Vec<(TScore, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let score = self.segment_score_tweaker.score(doc, score);
+        if score >= self.min_score {
+            self.hits.push(score, doc);
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.hits.into_doc_addresses(self.segment_ord)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::collector::ScoreTweaker;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{Schema, FAST};
+    use tantivy::{doc, DocId, Score, SegmentReader};
+
+    use super::MinScoreTopDocs;
+    use crate::components::test_utils::create_test_index;
+
+    struct FastFieldScore;
+
+    #[async_trait]
+    impl ScoreTweaker<u64> for FastFieldScore {
+        type Child = Box<dyn FnMut(DocId, Score) -> u64 + Send>;
+
+        fn segment_tweaker(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+            let column = segment_reader.fast_fields().u64("rank")?;
+            Ok(Box::new(move |doc, _| column.first(doc).unwrap_or_default()))
+        }
+
+        async fn segment_tweaker_async(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+            self.segment_tweaker(segment_reader)
+        }
+    }
+
+    #[test]
+    fn test_min_score() {
+        let mut schema_builder = Schema::builder();
+        let rank = schema_builder.add_u64_field("rank", FAST);
+        let documents = (0..10u64).map(|i| doc!(rank => i)).collect::<Vec<_>>();
+        let index = create_test_index(schema_builder.build(), documents.chunks(4).map(<[_]>::to_vec));
+
+        let searcher = index.reader().unwrap().searcher();
+        let top_docs = searcher.search(&AllQuery, &MinScoreTopDocs::new(FastFieldScore, 10, 0, 6)).unwrap();
+        assert_eq!(top_docs.iter().map(|hit| hit.0).collect::<Vec<_>>(), vec![9, 8, 7, 6]);
+
+        let top_docs = searcher.search(&AllQuery, &MinScoreTopDocs::new(FastFieldScore, 2, 3, 5)).unwrap();
+        assert_eq!(top_docs.iter().map(|hit| hit.0).collect::<Vec<_>>(), vec![6, 5]);
+    }
+}
@@ -1,3 +1,18 @@
+mod cardinality_collector;
+mod collapsing_top_docs_collector;
+mod fusion_collector;
+mod hierarchical_facet_collector;
+mod min_score_top_docs_collector;
+mod rescoring_top_docs_collector;
 mod reservoir_sampling_collector;
+mod significant_terms_collector;
+mod value_column;
 
+pub use cardinality_collector::{Cardinality, DistinctValues};
+pub use collapsing_top_docs_collector::{CollapseKey, CollapsedGroup, CollapsedTopDocs, CollapsingTopDocs};
+pub use fusion_collector::{FusedHit, Fusion, FusionMethod, FusionSource};
+pub use hierarchical_facet_collector::{FacetNode, HierarchicalFacets};
+pub use min_score_top_docs_collector::MinScoreTopDocs;
+pub use rescoring_top_docs_collector::{RescoredHit, RescoringTopDocs};
 pub use reservoir_sampling_collector::ReservoirSampling;
+pub use significant_terms_collector::{SignificanceHeuristic, SignificantTerm, SignificantTerms};
@@ -0,0 +1,212 @@
+use std::cmp::Ordering;
+
+use tantivy::collector::{Collector, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector};
+use tantivy::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+use super::collapsing_top_docs_collector::{compare_hits, TopHits};
+
+/// Document of the first phase window with its relevance score and the score of the second phase
+pub struct RescoredHit<TScore> {
+    score: TScore,
+    rescore: f64,
+    doc_address: DocAddress,
+}
+
+/// `RescoringTopDocs` collector re-ranks best documents of the first phase with the second phase scorer
+///
+/// Every segment keeps its best `window_size` documents by the first phase score and scores them with `rescore_tweaker`
+/// on harvesting, so the expensive second phase scorer never runs over all matched documents. The final score of the
+/// global window is `query_weight * score + rescore_query_weight * rescore`, `offset` and `limit` are applied after re-ranking.
+pub struct RescoringTopDocs<TScoreTweaker, TScore, TRescoreTweaker> {
+    score_tweaker: TScoreTweaker,
+    rescore_tweaker: TRescoreTweaker,
+    window_size: usize,
+    query_weight: f64,
+    rescore_query_weight: f64,
+    limit: usize,
+    offset: usize,
+    min_score: Option<TScore>,
+}
+
+impl<TScoreTweaker, TScore, TRescoreTweaker> RescoringTopDocs<TScoreTweaker, TScore, TRescoreTweaker> {
+    /// Creates collector returning `limit` documents of the rescored window after skipping `offset` ones
+    pub fn new(
+        score_tweaker: TScoreTweaker,
+        rescore_tweaker: TRescoreTweaker,
+        window_size: usize,
+        query_weight: f64,
+        rescore_query_weight: f64,
+        limit: usize,
+        offset: usize,
+    ) -> Self {
+        RescoringTopDocs {
+            score_tweaker,
+            rescore_tweaker,
+            window_size,
+            query_weight,
+            rescore_query_weight,
+            limit,
+            offset,
+            min_score: None,
+        }
+    }
+
+    /// Drops documents scored lower than `min_score` by the first phase
+    pub fn with_min_score(mut self, min_score: TScore) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+}
+
+#[async_trait]
+impl<TScoreTweaker, TScore, TRescoreTweaker> Collector for RescoringTopDocs<TScoreTweaker, TScore, TRescoreTweaker>
+where
+    TScoreTweaker: ScoreTweaker<TScore> + Send + Sync,
+    TScoreTweaker::Child: Send,
+    TScore: 'static + PartialOrd + Clone + Into<f64> + Send + Sync,
+    TRescoreTweaker: ScoreTweaker<f64> + Send + Sync,
+    TRescoreTweaker::Child: Send,
+{
+    type Fruit = Vec<(f64, DocAddress)>;
+
+    type Child = SegmentRescoringTopDocs<TScoreTweaker::Child, TScore, TRescoreTweaker::Child>;
+
+    fn for_segment(&self, segment_ord: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(SegmentRescoringTopDocs {
+            segment_ord,
+            segment_score_tweaker: self.score_tweaker.segment_tweaker(segment_reader)?,
+            segment_rescore_tweaker: self.rescore_tweaker.segment_tweaker(segment_reader)?,
+            min_score: self.min_score.clone(),
+            hits: TopHits::new(self.window_size),
+        })
+    }
+
+    async fn for_segment_async(&self, segment_ord: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(SegmentRescoringTopDocs {
+            segment_ord,
+            segment_score_tweaker: self.score_tweaker.segment_tweaker_async(segment_reader).await?,
+            segment_rescore_tweaker: self.rescore_tweaker.segment_tweaker_async(segment_reader).await?,
+            min_score: self.min_score.clone(),
+            hits: TopHits::new(self.window_size),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Vec<RescoredHit<TScore>>>) -> tantivy::Result<Vec<(f64, DocAddress)>> {
+        let mut window: Vec<_> = segment_fruits.into_iter().flatten().collect();
+        window.sort_by(|left, right| {
+            right
+                .score
+                .partial_cmp(&left.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.doc_address.cmp(&right.doc_address))
+        });
+        window.truncate(self.window_size);
+        let mut hits: Vec<_> = window
+            .into_iter()
+            .map(|hit| (self.query_weight * hit.score.into() + self.rescore_query_weight * hit.rescore, hit.doc_address))
+            .collect();
+        hits.sort_by(compare_hits);
+        Ok(hits.into_iter().skip(self.offset).take(self.limit).collect())
+    }
+}
+
+pub struct SegmentRescoringTopDocs<TSegmentScoreTweaker, TScore, TSegmentRescoreTweaker> {
+    segment_ord: SegmentOrdinal,
+    segment_score_tweaker: TSegmentScoreTweaker,
+    segment_rescore_tweaker: TSegmentRescoreTweaker,
+    min_score: Option<TScore>,
+    // Relevance score of the search query is kept for the second phase scorer
+    hits: TopHits<(TScore, Score)>,
+}
+
+impl<TSegmentScoreTweaker, TScore, TSegmentRescoreTweaker> SegmentCollector for SegmentRescoringTopDocs<TSegmentScoreTweaker, TScore, TSegmentRescoreTweaker>
+where
+    TSegmentScoreTweaker: 'static + ScoreSegmentTweaker<TScore> + Send,
+    TScore: 'static + PartialOrd + Clone + Send + Sync,
+    TSegmentRescoreTweaker: 'static + ScoreSegmentTweaker<f64> + Send,
+{
+    type Fruit = Vec<RescoredHit<TScore>>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let tweaked_score = self.segment_score_tweaker.score(doc, score);
+        if matches!(&self.min_score, Some(min_score) if tweaked_score < *min_score) {
+            return;
+        }
+        self.hits.push((tweaked_score, score), doc);
+    }
+
+    fn harvest(mut self) -> Self::Fruit {
+        let mut window = self.hits.into_doc_addresses(self.segment_ord);
+        // Second phase scorers may iterate over postings, so documents are rescored in ascending order
+        window.sort_by_key(|(_, doc_address)| doc_address.doc_id);
+        window
+            .into_iter()
+            .map(|((score, original_score), doc_address)| RescoredHit {
+                rescore: self.segment_rescore_tweaker.score(doc_address.doc_id, original_score),
+                score,
+                doc_address,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::collector::ScoreTweaker;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{Schema, FAST};
+    use tantivy::{doc, DocId, Score, SegmentReader};
+
+    use super::RescoringTopDocs;
+    use crate::components::test_utils::create_test_index;
+
+    struct FastFieldScore(&'static str);
+
+    #[async_trait]
+    impl ScoreTweaker<f64> for FastFieldScore {
+        type Child = Box<dyn FnMut(DocId, Score) -> f64 + Send>;
+
+        fn segment_tweaker(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+            let column = segment_reader.fast_fields().f64(self.0)?;
+            Ok(Box::new(move |doc, _| column.first(doc).unwrap_or_default()))
+        }
+
+        async fn segment_tweaker_async(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+            self.segment_tweaker(segment_reader)
+        }
+    }
+
+    #[test]
+    fn test_rescoring() {
+        let mut schema_builder = Schema::builder();
+        let rank = schema_builder.add_f64_field("rank", FAST);
+        let quality = schema_builder.add_f64_field("quality", FAST);
+        let index = create_test_index(
+            schema_builder.build(),
+            [(5.0, 0.0), (4.0, 10.0), (3.0, 0.0), (2.0, 20.0), (1.0, 100.0)]
+                .map(|(rank_value, quality_value)| [doc!(rank => rank_value, quality => quality_value)]),
+        );
+
+        let searcher = index.reader().unwrap().searcher();
+        let top_docs = searcher
+            .search(
+                &AllQuery,
+                &RescoringTopDocs::new(FastFieldScore("rank"), FastFieldScore("quality"), 4, 1.0, 1.0, 10, 0),
+            )
+            .unwrap();
+        // The best document by quality is outside of the window
+        assert_eq!(top_docs.iter().map(|hit| hit.0).collect::<Vec<_>>(), vec![22.0, 14.0, 5.0, 3.0]);
+
+        let top_docs = searcher
+            .search(
+                &AllQuery,
+                &RescoringTopDocs::new(FastFieldScore("rank"), FastFieldScore("quality"), 4, 1.0, 0.0, 1, 1).with_min_score(3.0),
+            )
+            .unwrap();
+        assert_eq!(top_docs.iter().map(|hit| hit.0).collect::<Vec<_>>(), vec![4.0]);
+    }
+}
@@ -0,0 +1,245 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocId, DocSet, InvertedIndexReader, Score, Searcher, SegmentOrdinal, SegmentReader, Term, TERMINATED};
+
+/// Measures how much the term is over-represented in the foreground set comparing to the background set
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SignificanceHeuristic {
+    /// Product of absolute and relative changes of the term probability
+    Jlh,
+    ChiSquare,
+    MutualInformation,
+}
+
+impl SignificanceHeuristic {
+    /// Scores the term found in `foreground_count` of `foreground_size` documents and in `background_count` of `background_size`
+    /// documents. The background set is expected to include the foreground set, negative correlations are scored with `0`
+    pub fn score(&self, foreground_count: u64, foreground_size: u64, background_count: u64, background_size: u64) -> f64 {
+        if foreground_size == 0 || background_count == 0 || background_size == 0 {
+            return 0.0;
+        }
+        let subset_probability = foreground_count as f64 / foreground_size as f64;
+        let superset_probability = background_count as f64 / background_size as f64;
+        if subset_probability <= superset_probability {
+            return 0.0;
+        }
+        // Contingency table of the term presence and the membership in the foreground set
+        let n = background_size as f64;
+        let n11 = foreground_count as f64;
+        let n10 = background_count.saturating_sub(foreground_count) as f64;
+        let n01 = foreground_size.saturating_sub(foreground_count) as f64;
+        let n00 = (n - n11 - n10 - n01).max(0.0);
+        let (n1_, n0_, n_1, n_0) = (n11 + n10, n01 + n00, n11 + n01, n10 + n00);
+        match self {
+            SignificanceHeuristic::Jlh => (subset_probability - superset_probability) * (subset_probability / superset_probability),
+            SignificanceHeuristic::ChiSquare => {
+                let denominator = n1_ * n0_ * n_1 * n_0;
+                if denominator == 0.0 {
+                    return 0.0;
+                }
+                n * (n11 * n00 - n10 * n01).powi(2) / denominator
+            }
+            SignificanceHeuristic::MutualInformation => {
+                let cell = |nij: f64, ni: f64, nj: f64| {
+                    if nij == 0.0 || ni == 0.0 || nj == 0.0 {
+                        0.0
+                    } else {
+                        nij / n * (n * nij / (ni * nj)).log2()
+                    }
+                };
+                cell(n11, n1_, n_1) + cell(n01, n0_, n_1) + cell(n10, n1_, n_0) + cell(n00, n0_, n_0)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignificantTerm {
+    pub term: String,
+    pub score: f64,
+    /// Number of matched documents containing the term
+    pub doc_count: u64,
+    /// Number of all documents containing the term
+    pub background_doc_count: u64,
+}
+
+/// `SignificantTerms` collector returns terms that are over-represented among matched documents comparing to the whole index
+///
+/// Foreground counts are computed by intersecting postings of every term of the segment with matched documents, so the collector
+/// fits fields with moderate vocabularies such as tags or keywords better than long texts.
+/// Background counts are document frequencies of terms in the whole index.
+pub struct SignificantTerms {
+    searcher: Searcher,
+    field: Field,
+    top_k: usize,
+    heuristic: SignificanceHeuristic,
+    min_doc_count: u64,
+}
+
+impl SignificantTerms {
+    pub fn new(searcher: Searcher, field: Field, top_k: usize, heuristic: SignificanceHeuristic, min_doc_count: u64) -> Self {
+        SignificantTerms {
+            searcher,
+            field,
+            top_k,
+            heuristic,
+            min_doc_count,
+        }
+    }
+}
+
+impl Collector for SignificantTerms {
+    type Fruit = Vec<SignificantTerm>;
+
+    type Child = SegmentSignificantTerms;
+
+    fn for_segment(&self, _: SegmentOrdinal, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(SegmentSignificantTerms {
+            inverted_index: segment_reader.inverted_index(self.field)?,
+            docs: vec![],
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<tantivy::Result<SegmentForeground>>) -> tantivy::Result<Vec<SignificantTerm>> {
+        let mut foreground_size = 0;
+        let mut doc_counts: HashMap<String, u64> = HashMap::new();
+        for segment_fruit in segment_fruits {
+            let segment_fruit = segment_fruit?;
+            foreground_size += segment_fruit.size;
+            for (term, doc_count) in segment_fruit.doc_counts {
+                *doc_counts.entry(term).or_default() += doc_count;
+            }
+        }
+        let background_size = self.searcher.num_docs();
+        let mut significant_terms = vec![];
+        for (term, doc_count) in doc_counts {
+            if doc_count < self.min_doc_count {
+                continue;
+            }
+            let background_doc_count = self.searcher.doc_freq(&Term::from_field_text(self.field, &term))?;
+            let score = self.heuristic.score(doc_count, foreground_size, background_doc_count, background_size);
+            if score > 0.0 {
+                significant_terms.push(SignificantTerm {
+                    term,
+                    score,
+                    doc_count,
+                    background_doc_count,
+                })
+            }
+        }
+        significant_terms.sort_by(|left, right| {
+            right
+                .score
+                .partial_cmp(&left.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.term.cmp(&right.term))
+        });
+        significant_terms.truncate(self.top_k);
+        Ok(significant_terms)
+    }
+}
+
+pub struct SegmentForeground {
+    size: u64,
+    doc_counts: HashMap<String, u64>,
+}
+
+pub struct SegmentSignificantTerms {
+    inverted_index: Arc<InvertedIndexReader>,
+    // Documents are collected in ascending order
+    docs: Vec<DocId>,
+}
+
+impl SegmentCollector for SegmentSignificantTerms {
+    type Fruit = tantivy::Result<SegmentForeground>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        self.docs.push(doc);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let mut doc_counts = HashMap::new();
+        if !self.docs.is_empty() {
+            let mut term_stream = self.inverted_index.terms().stream()?;
+            while let Some((term, term_info)) = term_stream.next() {
+                let Ok(term) = std::str::from_utf8(term) else {
+                    continue;
+                };
+                let mut postings = self.inverted_index.read_postings_from_terminfo(term_info, IndexRecordOption::Basic)?;
+                let mut doc_count = 0;
+                let mut posting_doc = postings.doc();
+                for doc in &self.docs {
+                    if posting_doc < *doc {
+                        posting_doc = postings.seek(*doc);
+                    }
+                    if posting_doc == TERMINATED {
+                        break;
+                    }
+                    if posting_doc == *doc {
+                        doc_count += 1;
+                    }
+                }
+                if doc_count > 0 {
+                    doc_counts.insert(term.to_string(), doc_count);
+                }
+            }
+        }
+        Ok(SegmentForeground {
+            size: self.docs.len() as u64,
+            doc_counts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::query::TermQuery;
+    use tantivy::schema::{IndexRecordOption, Schema, STRING};
+    use tantivy::{doc, Term};
+
+    use super::{SignificanceHeuristic, SignificantTerms};
+    use crate::components::test_utils::create_test_index;
+
+    #[test]
+    fn test_significant_terms() {
+        let mut schema_builder = Schema::builder();
+        let topic = schema_builder.add_text_field("topic", STRING);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let documents = (0..100)
+            .map(|i| {
+                let topic_value = if i < 20 { "biology" } else { "history" };
+                let tag_value = match i {
+                    0..=14 => "dna",
+                    15..=59 => "common",
+                    _ => "war",
+                };
+                doc!(topic => topic_value, tag => tag_value, tag => "everywhere")
+            })
+            .collect::<Vec<_>>();
+        let index = create_test_index(schema_builder.build(), documents.chunks(30).map(<[_]>::to_vec));
+
+        let searcher = index.reader().unwrap().searcher();
+        let query = TermQuery::new(Term::from_field_text(topic, "biology"), IndexRecordOption::Basic);
+        for heuristic in [
+            SignificanceHeuristic::Jlh,
+            SignificanceHeuristic::ChiSquare,
+            SignificanceHeuristic::MutualInformation,
+        ] {
+            let significant_terms = searcher
+                .search(&query, &SignificantTerms::new(searcher.clone(), tag, 10, heuristic, 3))
+                .unwrap();
+            assert_eq!(significant_terms[0].term, "dna");
+            assert_eq!(significant_terms[0].doc_count, 15);
+            assert_eq!(significant_terms[0].background_doc_count, 15);
+            assert!(significant_terms.iter().all(|significant_term| significant_term.term != "everywhere"));
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use tantivy::columnar::{Column, ColumnType, StrColumn};
+use tantivy::SegmentReader;
+
+/// Fast field column whose values are mapped to `u64`: string columns yield segment-local term ordinals
+pub(super) enum ValueColumn {
+    Numeric(Column<u64>),
+    Str(StrColumn),
+}
+
+impl ValueColumn {
+    pub(super) fn open(segment_reader: &SegmentReader, field_name: &str) -> tantivy::Result<Option<ValueColumn>> {
+        let fast_fields = segment_reader.fast_fields();
+        // `u64_lenient` maps strings to segment-local term ordinals, so string columns have to be opened first
+        if let Some(str_column) = fast_fields.str(field_name)? {
+            return Ok(Some(ValueColumn::Str(str_column)));
+        }
+        Ok(fast_fields.u64_lenient(field_name)?.map(|(column, _)| ValueColumn::Numeric(column)))
+    }
+
+    pub(super) async fn open_async(segment_reader: &SegmentReader, field_name: &str) -> tantivy::Result<Option<ValueColumn>> {
+        let fast_fields = segment_reader.fast_fields();
+        if let Some(dynamic_column_handle) = fast_fields.dynamic_column_handle_async(field_name, ColumnType::Str).await? {
+            let str_column: Option<StrColumn> = dynamic_column_handle.open_async().await?.into();
+            return Ok(str_column.map(ValueColumn::Str));
+        }
+        Ok(fast_fields.u64_lenient_async(field_name).await?.map(|(column, _)| ValueColumn::Numeric(column)))
+    }
+}
@@ -0,0 +1,259 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rand::RngCore;
+use summa_proto::proto;
+use tantivy::schema::{Field, OwnedValue, Schema, Type};
+use tantivy::TantivyDocument;
+
+use crate::errors::{SummaResult, ValidationError};
+
+const CROCKFORD_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+#[inline]
+fn generate_id() -> String {
+    base36::encode(&random_bytes())
+}
+
+#[inline]
+fn random_bytes() -> [u8; 16] {
+    let mut data = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut data);
+    data
+}
+
+#[inline]
+fn unix_millis() -> u64 {
+    instant::now() as u64
+}
+
+/// 48-bit timestamp followed by 80 random bits encoded with Crockford's base32
+fn encode_ulid(unix_millis: u64, random: u128) -> String {
+    let value = (u128::from(unix_millis) << 80) | (random & ((1 << 80) - 1));
+    (0..26)
+        .rev()
+        .map(|i| CROCKFORD_ALPHABET[((value >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// UUID version 7 with 48-bit timestamp and random bits in the rest
+fn encode_uuid_v7(unix_millis: u64, random: u128) -> String {
+    let value = (u128::from(unix_millis) << 80) | (0x7 << 76) | (((random >> 64) & 0xfff) << 64) | (0b10 << 62) | (random & ((1 << 62) - 1));
+    let hex = format!("{value:032x}");
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+/// 128-bit FNV-1a, its values must be stable across releases as they are stored in indices
+#[inline]
+fn fnv1a(hash: u128, bytes: &[u8]) -> u128 {
+    bytes.iter().fold(hash, |hash, byte| (hash ^ u128::from(*byte)).wrapping_mul(FNV_PRIME))
+}
+
+/// Fills `IndexAttributes.auto_id_field` of documents missing it according to `IndexAttributes.auto_id_strategy`
+///
+/// Both text and `u64` fields are supported. Time-sortable strategies put milliseconds into the upper 48 bits of `u64` ids
+/// and a counter of ids generated within the millisecond into the lower 16 bits. Ids stay unique and increasing even if the counter
+/// overflows, in this case they run ahead of the clock until it catches up.
+pub struct AutoId {
+    field: Field,
+    field_type: Type,
+    strategy: proto::AutoIdStrategy,
+    hashed_fields: Vec<(String, Field)>,
+    last_u64_id: AtomicU64,
+}
+
+impl AutoId {
+    pub fn new(schema: &Schema, index_attributes: &proto::IndexAttributes) -> SummaResult<Option<AutoId>> {
+        let Some(field_name) = &index_attributes.auto_id_field else {
+            return Ok(None);
+        };
+        let field = schema
+            .get_field(field_name)
+            .map_err(|_| ValidationError::MissingField(field_name.to_string()))?;
+        let field_type = schema.get_field_entry(field).field_type().value_type();
+        if !matches!(field_type, Type::Str | Type::U64) {
+            return Err(ValidationError::InvalidSchema(format!("auto id field `{field_name}` must be text or u64")).into());
+        }
+        let strategy = index_attributes.auto_id_strategy();
+        let hashed_fields = index_attributes
+            .auto_id_hashed_fields
+            .iter()
+            .map(|hashed_field| {
+                schema
+                    .get_field(hashed_field)
+                    .map(|field| (hashed_field.to_string(), field))
+                    .map_err(|_| ValidationError::MissingField(hashed_field.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if matches!(strategy, proto::AutoIdStrategy::ContentHash) && hashed_fields.is_empty() {
+            return Err(ValidationError::EmptyArgument("auto_id_hashed_fields".to_string()).into());
+        }
+        Ok(Some(AutoId {
+            field,
+            field_type,
+            strategy,
+            hashed_fields,
+            last_u64_id: AtomicU64::new(0),
+        }))
+    }
+
+    /// Adds id to `document` if it has no value for the id field
+    pub fn setup(&self, document: &mut TantivyDocument) -> SummaResult<()> {
+        if document.get_first(self.field).is_some() {
+            return Ok(());
+        }
+        match self.field_type {
+            Type::U64 => {
+                let id = self.generate_u64(document)?;
+                document.add_u64(self.field, id)
+            }
+            _ => {
+                let id = self.generate_text(document)?;
+                document.add_text(self.field, id)
+            }
+        }
+        Ok(())
+    }
+
+    fn generate_text(&self, document: &TantivyDocument) -> SummaResult<String> {
+        Ok(match self.strategy {
+            proto::AutoIdStrategy::Random => generate_id(),
+            proto::AutoIdStrategy::Ulid => encode_ulid(unix_millis(), u128::from_le_bytes(random_bytes())),
+            proto::AutoIdStrategy::UuidV7 => encode_uuid_v7(unix_millis(), u128::from_le_bytes(random_bytes())),
+            proto::AutoIdStrategy::ContentHash => base36::encode(&self.content_hash(document)?.to_be_bytes()),
+        })
+    }
+
+    fn generate_u64(&self, document: &TantivyDocument) -> SummaResult<u64> {
+        Ok(match self.strategy {
+            proto::AutoIdStrategy::Random => rand::thread_rng().next_u64(),
+            proto::AutoIdStrategy::Ulid | proto::AutoIdStrategy::UuidV7 => self.next_time_sortable_u64(),
+            proto::AutoIdStrategy::ContentHash => {
+                let hash = self.content_hash(document)?;
+                (hash >> 64) as u64 ^ hash as u64
+            }
+        })
+    }
+
+    fn next_time_sortable_u64(&self) -> u64 {
+        let millis_id = unix_millis() << 16;
+        let previous_id = self
+            .last_u64_id
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last_id| Some(millis_id.max(last_id + 1)))
+            .expect("update function always returns new value");
+        millis_id.max(previous_id + 1)
+    }
+
+    /// Hash of names and values of hashed fields, so documents with the same content get the same id
+    fn content_hash(&self, document: &TantivyDocument) -> SummaResult<u128> {
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut has_values = false;
+        for (field_name, field) in &self.hashed_fields {
+            hash = fnv1a(hash, field_name.as_bytes());
+            for value in document.get_all(*field) {
+                has_values = true;
+                // JSON escapes control characters, so zero bytes unambiguously separate values
+                hash = fnv1a(hash, &[0]);
+                hash = fnv1a(hash, serde_json::to_string(&OwnedValue::from(value)).expect("cannot serialize").as_bytes());
+            }
+            hash = fnv1a(hash, &[1]);
+        }
+        if !has_values {
+            let hashed_fields = self.hashed_fields.iter().map(|(field_name, _)| field_name.as_str()).collect::<Vec<_>>();
+            return Err(ValidationError::MissingField(hashed_fields.join(", ")).into());
+        }
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use summa_proto::proto;
+    use tantivy::schema::{OwnedValue, SchemaBuilder, Value, INDEXED, STORED, STRING, TEXT};
+    use tantivy::{doc, TantivyDocument};
+
+    use super::{encode_ulid, encode_uuid_v7, AutoId};
+
+    #[test]
+    fn test_time_sortable_ids() {
+        let first_ulid = encode_ulid(1_700_000_000_000, u128::MAX);
+        let second_ulid = encode_ulid(1_700_000_000_001, 0);
+        assert_eq!(first_ulid.len(), 26);
+        assert!(first_ulid < second_ulid);
+        assert_eq!(encode_ulid(0, 0), "00000000000000000000000000");
+
+        let uuid = encode_uuid_v7(0x0189_abcd_ef01, u128::MAX);
+        assert_eq!(uuid, "0189abcd-ef01-7fff-bfff-ffffffffffff");
+        assert!(encode_uuid_v7(1_700_000_000_000, u128::MAX) < encode_uuid_v7(1_700_000_000_001, 0));
+    }
+
+    #[test]
+    fn test_time_sortable_u64_ids() {
+        let mut schema_builder = SchemaBuilder::new();
+        let id = schema_builder.add_u64_field("id", INDEXED | STORED);
+        let schema = schema_builder.build();
+        let auto_id = AutoId::new(
+            &schema,
+            &proto::IndexAttributes {
+                auto_id_field: Some("id".to_string()),
+                auto_id_strategy: proto::AutoIdStrategy::Ulid.into(),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .unwrap();
+        let ids = (0..100_000)
+            .map(|_| {
+                let mut document = doc!();
+                auto_id.setup(&mut document).unwrap();
+                document.get_first(id).and_then(|value| value.as_u64()).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(ids.windows(2).all(|window| window[0] < window[1]));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let mut schema_builder = SchemaBuilder::new();
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        let u64_id = schema_builder.add_u64_field("u64_id", INDEXED | STORED);
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let schema = schema_builder.build();
+
+        let index_attributes = |auto_id_field: &str| proto::IndexAttributes {
+            auto_id_field: Some(auto_id_field.to_string()),
+            auto_id_strategy: proto::AutoIdStrategy::ContentHash.into(),
+            auto_id_hashed_fields: vec!["title".to_string(), "body".to_string()],
+            ..Default::default()
+        };
+        let auto_id = AutoId::new(&schema, &index_attributes("id")).unwrap().unwrap();
+        let u64_auto_id = AutoId::new(&schema, &index_attributes("u64_id")).unwrap().unwrap();
+
+        let mut first_document = doc!(title => "title", body => "body");
+        let mut second_document = doc!(title => "title", body => "body");
+        let mut third_document = doc!(title => "titlebody");
+        for document in [&mut first_document, &mut second_document, &mut third_document] {
+            auto_id.setup(document).unwrap();
+            u64_auto_id.setup(document).unwrap();
+        }
+        let value = |document: &TantivyDocument, field| document.get_first(field).map(OwnedValue::from).unwrap();
+        assert_eq!(value(&first_document, id), value(&second_document, id));
+        assert_ne!(value(&first_document, id), value(&third_document, id));
+        assert_eq!(value(&first_document, u64_id), value(&second_document, u64_id));
+        assert_ne!(value(&first_document, u64_id), value(&third_document, u64_id));
+
+        assert!(auto_id.setup(&mut doc!(id => "existing_id")).is_ok());
+        assert!(auto_id.setup(&mut doc!()).is_err());
+        assert!(AutoId::new(
+            &schema,
+            &proto::IndexAttributes {
+                auto_id_field: Some("id".to_string()),
+                auto_id_strategy: proto::AutoIdStrategy::ContentHash.into(),
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+}
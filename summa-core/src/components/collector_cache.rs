@@ -1,14 +1,62 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
 use izihawa_ttl_cache::TtlCache;
+use serde::Serialize;
 use summa_proto::proto;
+use tantivy::Searcher;
 
 use crate::components::IntermediateExtractionResult;
-use crate::configs::core::CollectorCacheConfig;
+use crate::configs::core::{CollectorCacheConfig, SearchLimits};
 
 const BLOCK_SIZE: u32 = 100;
 
+/// Hashes the canonical JSON representation of the message
+///
+/// Keys of JSON objects are sorted, so messages with equal fields have equal hashes regardless of the order of their maps
+fn canonical_hash<T: Serialize>(message: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_value(message)
+        .expect("proto messages are serializable")
+        .to_string()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Identifies searched data and the query shared by all collectors of the search
+///
+/// The searcher generation is a hash of segment IDs and their delete opstamps, so outputs cached before the reload
+/// of the reader with new or changed segments are never matched again and are left for expiration
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct QueryCachingKey {
+    searcher_generation: u64,
+    query: u64,
+}
+
+impl QueryCachingKey {
+    /// `query_parser_config` and `search_limits` are a part of the key because they change the parsed query,
+    /// i.e. default fields, boosts or the number of terms in `MoreLikeThisQuery`
+    pub fn new(
+        searcher: &Searcher,
+        query: &proto::query::Query,
+        query_parser_config: &proto::QueryParserConfig,
+        is_fieldnorms_scoring_enabled: Option<bool>,
+        search_limits: &SearchLimits,
+    ) -> QueryCachingKey {
+        let mut hasher = DefaultHasher::new();
+        searcher.generation().segments().hash(&mut hasher);
+        QueryCachingKey {
+            searcher_generation: hasher.finish(),
+            query: canonical_hash(&(query, query_parser_config, is_fieldnorms_scoring_enabled, &search_limits.0)),
+        }
+    }
+}
+
 pub struct CollectorCache {
-    cache: TtlCache<String, IntermediateExtractionResult>,
+    cache: TtlCache<(QueryCachingKey, u64), IntermediateExtractionResult>,
     ttl_interval_ms: instant::Duration,
+    hits: u64,
+    misses: u64,
 }
 
 impl CollectorCache {
@@ -16,6 +64,8 @@ impl CollectorCache {
         CollectorCache {
             ttl_interval_ms: instant::Duration::from_millis(config.ttl_interval_ms.unwrap_or(120000)),
             cache: TtlCache::new(config.size),
+            hits: 0,
+            misses: 0,
         }
     }
 
@@ -23,17 +73,23 @@ impl CollectorCache {
         self.cache.remove_expired();
     }
 
-    pub fn caching_key(&self, caching_key: &str, collector: &proto::Collector) -> String {
+    /// Numbers of hits and misses since the creation of the cache
+    pub fn hits_and_misses(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+
+    /// Hashes fields of the collector that affect collected outputs
+    ///
+    /// Cached document references carry snippet configs, requested fields and score normalization, so only `explain` is left out
+    fn collector_hash(collector: &proto::Collector) -> u64 {
         match collector {
             proto::Collector {
                 collector: Some(proto::collector::Collector::TopDocs(top_docs)),
-            } => {
-                format!(
-                    "{}|TopDocsCollector {{ limit={}, offset={}, scorer={:?} }}|",
-                    caching_key, top_docs.limit, top_docs.offset, top_docs.scorer
-                )
-            }
-            other => format!("{}|{:?}", caching_key, other),
+            } => canonical_hash(&proto::TopDocsCollector {
+                explain: false,
+                ..top_docs.clone()
+            }),
+            other => canonical_hash(other),
         }
     }
 
@@ -97,13 +153,79 @@ impl CollectorCache {
         }
     }
 
-    pub fn get(&mut self, caching_key: &str, adjusted_collector: &proto::Collector, collector: &proto::Collector) -> Option<IntermediateExtractionResult> {
-        let caching_key = self.caching_key(caching_key, adjusted_collector);
-        self.cache.get(&caching_key).map(|cache_value| Self::adjust_result(cache_value, collector))
+    pub fn get(
+        &mut self,
+        caching_key: &QueryCachingKey,
+        adjusted_collector: &proto::Collector,
+        collector: &proto::Collector,
+    ) -> Option<IntermediateExtractionResult> {
+        let caching_key = (*caching_key, Self::collector_hash(adjusted_collector));
+        let result = self.cache.get(&caching_key).map(|cache_value| Self::adjust_result(cache_value, collector));
+        match result {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        result
+    }
+
+    pub fn put(&mut self, caching_key: &QueryCachingKey, collector: &proto::Collector, value: IntermediateExtractionResult) {
+        let caching_key = (*caching_key, Self::collector_hash(collector));
+        self.cache.insert(caching_key, value, self.ttl_interval_ms);
     }
+}
 
-    pub fn put(&mut self, caching_key: &str, collector: &proto::Collector, value: IntermediateExtractionResult) {
-        let caching_key = self.caching_key(caching_key, collector);
-        self.cache.insert(caching_key.clone(), value.clone(), self.ttl_interval_ms);
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use summa_proto::proto;
+
+    use super::{canonical_hash, CollectorCache};
+
+    #[test]
+    fn test_canonical_keys() {
+        let match_query = |field_boosts: Vec<(&str, f32)>| {
+            proto::query::Query::Match(proto::MatchQuery {
+                value: "game of thrones".to_string(),
+                query_parser_config: Some(proto::QueryParserConfig {
+                    field_boosts: HashMap::from_iter(field_boosts.into_iter().map(|(field, boost)| (field.to_string(), boost))),
+                    ..Default::default()
+                }),
+            })
+        };
+        assert_eq!(
+            canonical_hash(&match_query(vec![("title", 2.0), ("body", 1.0), ("tags", 0.5)])),
+            canonical_hash(&match_query(vec![("tags", 0.5), ("body", 1.0), ("title", 2.0)]))
+        );
+        assert_ne!(
+            canonical_hash(&match_query(vec![("title", 2.0)])),
+            canonical_hash(&match_query(vec![("title", 3.0)]))
+        );
+
+        let top_docs = |limit, explain| proto::Collector {
+            collector: Some(proto::collector::Collector::TopDocs(proto::TopDocsCollector {
+                limit,
+                explain,
+                ..Default::default()
+            })),
+        };
+        assert_eq!(
+            CollectorCache::collector_hash(&top_docs(10, false)),
+            CollectorCache::collector_hash(&top_docs(10, true))
+        );
+        assert_ne!(
+            CollectorCache::collector_hash(&top_docs(10, false)),
+            CollectorCache::collector_hash(&top_docs(20, false))
+        );
+
+        let mut with_snippets = top_docs(10, false);
+        if let Some(proto::collector::Collector::TopDocs(top_docs)) = &mut with_snippets.collector {
+            top_docs.snippet_field_configs.insert("title".to_string(), proto::SnippetConfig::default());
+            top_docs.score_normalization = proto::ScoreNormalization::Max.into();
+        }
+        assert_ne!(
+            CollectorCache::collector_hash(&top_docs(10, false)),
+            CollectorCache::collector_hash(&with_snippets)
+        );
     }
 }
@@ -0,0 +1,110 @@
+use std::ops::Bound;
+
+use summa_proto::proto;
+use tantivy::query::{Query, RangeQuery};
+use tantivy::schema::{Field, Schema, Type};
+use tantivy::{DateTime, Term};
+
+use crate::errors::{SummaResult, ValidationError};
+
+/// Selects documents expired according to `IndexAttributes.expiry`
+pub struct DocumentExpiry {
+    field: Field,
+    field_type: Type,
+    ttl_seconds: i64,
+}
+
+impl DocumentExpiry {
+    pub fn new(schema: &Schema, document_expiry: &proto::DocumentExpiry) -> SummaResult<DocumentExpiry> {
+        let field = schema
+            .get_field(&document_expiry.field)
+            .map_err(|_| ValidationError::MissingField(document_expiry.field.to_string()))?;
+        let field_entry = schema.get_field_entry(field);
+        let field_type = field_entry.field_type().value_type();
+        if !matches!(field_type, Type::Date | Type::I64 | Type::U64) || !(field_entry.is_indexed() || field_entry.is_fast()) {
+            return Err(ValidationError::InvalidSchema(format!(
+                "expiry field `{}` must be indexed or fast date, i64 or u64 field",
+                document_expiry.field
+            ))
+            .into());
+        }
+        let ttl_seconds = i64::try_from(document_expiry.ttl_seconds.unwrap_or_default())
+            .map_err(|_| ValidationError::InvalidSchema(format!("too large ttl_seconds: {:?}", document_expiry.ttl_seconds)))?;
+        Ok(DocumentExpiry {
+            field,
+            field_type,
+            ttl_seconds,
+        })
+    }
+
+    /// Query matching documents expired at `now` given in unix seconds
+    pub fn expired_query(&self, now: i64) -> Box<dyn Query> {
+        let threshold = now.saturating_sub(self.ttl_seconds);
+        let term = match self.field_type {
+            Type::Date => Term::from_field_date(self.field, DateTime::from_timestamp_secs(threshold)),
+            Type::I64 => Term::from_field_i64(self.field, threshold),
+            _ => Term::from_field_u64(self.field, u64::try_from(threshold).unwrap_or_default()),
+        };
+        Box::new(RangeQuery::new(Bound::Unbounded, Bound::Excluded(term)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use summa_proto::proto;
+    use tantivy::collector::Count;
+    use tantivy::doc;
+    use tantivy::schema::{SchemaBuilder, FAST, INDEXED, STORED};
+
+    use super::DocumentExpiry;
+    use crate::components::test_utils::create_test_index;
+
+    #[test]
+    fn test_expired_query() {
+        let mut schema_builder = SchemaBuilder::new();
+        let issued_at = schema_builder.add_i64_field("issued_at", INDEXED | STORED);
+        let expires_at = schema_builder.add_date_field("expires_at", FAST);
+        let title = schema_builder.add_text_field("title", STORED);
+        let schema = schema_builder.build();
+        let index = create_test_index(
+            schema.clone(),
+            [[
+                doc!(issued_at => 100i64, expires_at => tantivy::DateTime::from_timestamp_secs(1000)),
+                doc!(issued_at => 900i64, expires_at => tantivy::DateTime::from_timestamp_secs(2000)),
+                doc!(title => "never expires"),
+            ]],
+        );
+        let searcher = index.reader().unwrap().searcher();
+
+        let ttl_expiry = DocumentExpiry::new(
+            &schema,
+            &proto::DocumentExpiry {
+                field: "issued_at".to_string(),
+                ttl_seconds: Some(500),
+            },
+        )
+        .unwrap();
+        assert_eq!(searcher.search(ttl_expiry.expired_query(1000).as_ref(), &Count).unwrap(), 1);
+        assert_eq!(searcher.search(ttl_expiry.expired_query(2000).as_ref(), &Count).unwrap(), 2);
+
+        let absolute_expiry = DocumentExpiry::new(
+            &schema,
+            &proto::DocumentExpiry {
+                field: "expires_at".to_string(),
+                ttl_seconds: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(searcher.search(absolute_expiry.expired_query(1000).as_ref(), &Count).unwrap(), 0);
+        assert_eq!(searcher.search(absolute_expiry.expired_query(1500).as_ref(), &Count).unwrap(), 1);
+
+        assert!(DocumentExpiry::new(
+            &schema,
+            &proto::DocumentExpiry {
+                field: "title".to_string(),
+                ttl_seconds: None,
+            },
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+use summa_proto::proto;
+use tantivy::schema::{Field, FieldType, IndexRecordOption, Value};
+use tantivy::{DocSet, Index, IndexReader, ReloadPolicy, TantivyDocument, Term, TERMINATED};
+
+use crate::errors::{SummaResult, ValidationError};
+
+/// Result of indexing a single document
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndexingOutcome {
+    Indexed,
+    /// The document has been skipped because a document with the same unique fields and not older version is already indexed
+    Skipped,
+}
+
+/// Prevents documents from being overwritten by older versions according to `IndexAttributes.version_field`
+///
+/// Versions of committed documents are read from the fast field through a reader that is reloaded on every commit.
+/// Versions of documents indexed after the last commit are kept in memory until the next commit.
+pub(crate) struct DocumentVersions {
+    field: Field,
+    field_name: String,
+    stale_version_strategy: proto::StaleVersionStrategy,
+    index_reader: IndexReader,
+    pending_versions: Mutex<HashMap<Term, u64>>,
+}
+
+impl DocumentVersions {
+    pub fn new(index: &Index, index_attributes: &proto::IndexAttributes) -> SummaResult<Option<DocumentVersions>> {
+        let Some(field_name) = &index_attributes.version_field else {
+            return Ok(None);
+        };
+        let schema = index.schema();
+        let field = schema
+            .get_field(field_name)
+            .map_err(|_| ValidationError::MissingField(field_name.to_string()))?;
+        let field_entry = schema.get_field_entry(field);
+        if !matches!(field_entry.field_type(), FieldType::U64(_)) || !field_entry.is_fast() {
+            return Err(ValidationError::InvalidSchema(format!("version field `{field_name}` must be fast u64 field")).into());
+        }
+        if index_attributes.unique_fields.is_empty() {
+            return Err(ValidationError::InvalidSchema("version field requires unique fields".to_string()).into());
+        }
+        let index_reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
+        Ok(Some(DocumentVersions {
+            field,
+            field_name: field_name.to_string(),
+            stale_version_strategy: index_attributes.stale_version_strategy(),
+            index_reader,
+            pending_versions: Mutex::default(),
+        }))
+    }
+
+    /// Version of the incoming document
+    pub fn version(&self, document: &TantivyDocument) -> SummaResult<u64> {
+        document
+            .get_first(self.field)
+            .and_then(|value| value.as_u64())
+            .ok_or_else(|| ValidationError::MissingField(self.field_name.to_string()).into())
+    }
+
+    /// Runs `write` if `version` is greater than versions of all documents having any of `unique_terms`
+    ///
+    /// Checks and writes are serialized, so concurrent writers of the same document cannot both pass the check.
+    pub fn write_if_newer(&self, version: u64, unique_terms: &[Term], write: impl FnOnce() -> SummaResult<()>) -> SummaResult<IndexingOutcome> {
+        let mut pending_versions = self.pending_versions.lock();
+        let mut stored_version = None;
+        for term in unique_terms {
+            stored_version = stored_version.max(pending_versions.get(term).copied()).max(self.committed_version(term)?);
+        }
+        if let Some(stored_version) = stored_version {
+            if version <= stored_version {
+                return match self.stale_version_strategy {
+                    proto::StaleVersionStrategy::Reject => Err(ValidationError::StaleVersion { version, stored_version }.into()),
+                    proto::StaleVersionStrategy::Skip => Ok(IndexingOutcome::Skipped),
+                };
+            }
+        }
+        write()?;
+        for term in unique_terms {
+            pending_versions.insert(term.clone(), version);
+        }
+        Ok(IndexingOutcome::Indexed)
+    }
+
+    /// Forgets pending versions and reads committed ones again, must be called after commits and rollbacks
+    pub fn reload(&self) -> SummaResult<()> {
+        let mut pending_versions = self.pending_versions.lock();
+        self.index_reader.reload()?;
+        pending_versions.clear();
+        Ok(())
+    }
+
+    fn committed_version(&self, term: &Term) -> SummaResult<Option<u64>> {
+        let searcher = self.index_reader.searcher();
+        let mut version = None;
+        for segment_reader in searcher.segment_readers() {
+            let Some(mut postings) = segment_reader.inverted_index(term.field())?.read_postings(term, IndexRecordOption::Basic)? else {
+                continue;
+            };
+            let column = segment_reader.fast_fields().u64(&self.field_name)?;
+            let alive_bitset = segment_reader.alive_bitset();
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if !alive_bitset.is_some_and(|alive_bitset| alive_bitset.is_deleted(doc)) {
+                    version = version.max(column.first(doc));
+                }
+                doc = postings.advance();
+            }
+        }
+        Ok(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use summa_proto::proto;
+    use tantivy::collector::Count;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{SchemaBuilder, FAST, INDEXED, STORED, STRING};
+    use tantivy::{doc, IndexBuilder};
+
+    use super::IndexingOutcome;
+    use crate::components::test_utils::create_test_index_writer_holder;
+    use crate::components::IndexWriterHolder;
+
+    #[test]
+    fn test_document_versions() {
+        let mut schema_builder = SchemaBuilder::new();
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        let version = schema_builder.add_u64_field("version", FAST | INDEXED | STORED);
+        let schema = schema_builder.build();
+        let index_with_strategy = |stale_version_strategy: proto::StaleVersionStrategy| {
+            IndexBuilder::new()
+                .schema(schema.clone())
+                .index_attributes(proto::IndexAttributes {
+                    unique_fields: vec!["id".to_string()],
+                    version_field: Some("version".to_string()),
+                    stale_version_strategy: stale_version_strategy.into(),
+                    ..Default::default()
+                })
+                .create_in_ram()
+                .unwrap()
+        };
+
+        let index = index_with_strategy(proto::StaleVersionStrategy::Skip);
+        let mut index_writer_holder = create_test_index_writer_holder(&index);
+        let conflict_strategy = proto::ConflictStrategy::OverwriteAlways;
+        let index_document = |index_writer_holder: &IndexWriterHolder, document_version: u64| {
+            index_writer_holder
+                .index_document(doc!(id => "1", version => document_version), conflict_strategy)
+                .unwrap()
+        };
+        assert_eq!(index_document(&index_writer_holder, 2), IndexingOutcome::Indexed);
+        assert_eq!(index_document(&index_writer_holder, 1), IndexingOutcome::Skipped);
+        assert_eq!(index_document(&index_writer_holder, 2), IndexingOutcome::Skipped);
+        index_writer_holder.commit().unwrap();
+        assert_eq!(index_document(&index_writer_holder, 1), IndexingOutcome::Skipped);
+        assert_eq!(index_document(&index_writer_holder, 3), IndexingOutcome::Indexed);
+        index_writer_holder.commit().unwrap();
+
+        let reader = index.reader().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count).unwrap(), 1);
+        assert!(index_writer_holder.index_document(doc!(id => "2"), conflict_strategy).is_err());
+
+        let index = index_with_strategy(proto::StaleVersionStrategy::Reject);
+        let index_writer_holder = create_test_index_writer_holder(&index);
+        assert_eq!(index_document(&index_writer_holder, 2), IndexingOutcome::Indexed);
+        assert!(index_writer_holder.index_document(doc!(id => "1", version => 2u64), conflict_strategy).is_err());
+    }
+}
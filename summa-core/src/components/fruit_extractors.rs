@@ -6,15 +6,18 @@ use summa_proto::proto;
 use tantivy::aggregation::agg_req::Aggregations;
 use tantivy::aggregation::agg_result::AggregationResults;
 use tantivy::aggregation::AggregationLimitsGuard;
-use tantivy::collector::{FacetCounts, FruitHandle, MultiCollector, MultiFruit};
-use tantivy::query::Query;
-use tantivy::schema::Field;
-use tantivy::{Order, Searcher};
+use tantivy::collector::{FruitHandle, MultiCollector, MultiFruit, ScoreTweaker};
+use tantivy::query::{AllQuery, BooleanQuery, Occur, Query};
+use tantivy::schema::{Field, FieldType, Schema};
+use tantivy::{Order, Score, Searcher};
 
 use crate::components::snippet_generator::SnippetGeneratorConfig;
 use crate::components::IndexHolder;
-use crate::errors::{BuilderError, SummaResult};
+use crate::configs::core::SearchLimits;
+use crate::errors::{BuilderError, Error, SummaResult, ValidationError};
+use crate::proto_traits::Wrapper;
 use crate::scorers::eval_scorer_tweaker::EvalScorerTweaker;
+use crate::scorers::score_tweakers::{FastFieldScoreTweaker, FusionScoreTweaker, QueryScoreTweaker, RelevanceScoreTweaker, RescoreTweaker};
 use crate::scorers::EvalScorer;
 use crate::{collectors, validators};
 
@@ -22,6 +25,21 @@ use crate::{collectors, validators};
 pub struct ScoredDocAddress {
     pub doc_address: tantivy::DocAddress,
     pub score: Option<proto::Score>,
+    pub inner_hits: Vec<ScoredDocAddress>,
+    pub group_size: Option<u64>,
+    pub source_ranks: Vec<proto::SourceRank>,
+}
+
+impl ScoredDocAddress {
+    pub fn new(doc_address: tantivy::DocAddress, score: proto::Score) -> ScoredDocAddress {
+        ScoredDocAddress {
+            doc_address,
+            score: Some(score),
+            inner_hits: vec![],
+            group_size: None,
+            source_ranks: vec![],
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -48,15 +66,20 @@ pub struct PreparedDocumentReferences {
     pub snippet_generator_config: Option<SnippetGeneratorConfig>,
     pub scored_doc_addresses: Vec<ScoredDocAddress>,
     pub has_next: bool,
+    pub groups_count: Option<u64>,
     pub limit: u32,
     pub offset: u32,
+    pub score_normalization: proto::ScoreNormalization,
 }
 
 #[derive(Clone)]
 pub enum ReadyCollectorOutput {
     Aggregation(proto::AggregationCollectorOutput),
+    TypedAggregation(proto::TypedAggregationCollectorOutput),
+    Cardinality(proto::CardinalityCollectorOutput),
     Count(proto::CountCollectorOutput),
     Facet(proto::FacetCollectorOutput),
+    SignificantTerms(proto::SignificantTermsCollectorOutput),
 }
 
 #[derive(Clone)]
@@ -96,46 +119,116 @@ pub fn build_fruit_extractor(
     collector_proto: proto::Collector,
     query: &dyn Query,
     multi_collector: &mut MultiCollector,
+    search_limits: &SearchLimits,
 ) -> SummaResult<Box<dyn FruitExtractor>> {
     match collector_proto.collector {
         Some(proto::collector::Collector::TopDocs(top_docs_collector_proto)) => {
             let query_fields = validators::parse_fields(searcher.schema(), &top_docs_collector_proto.fields, &top_docs_collector_proto.excluded_fields)?;
             let query_fields = (!query_fields.is_empty()).then(|| HashSet::from_iter(query_fields.into_iter().map(|x| x.0)));
-            Ok(match top_docs_collector_proto.scorer {
-                None | Some(proto::Scorer { scorer: None }) => Box::new(
+            if let Some(rescore_config) = &top_docs_collector_proto.rescore {
+                let handle = rescoring_top_docs_handle(
+                    index_holder,
+                    &searcher,
+                    &top_docs_collector_proto,
+                    rescore_config,
+                    multi_collector,
+                    search_limits,
+                )?;
+                return Ok(Box::new(
                     TopDocsBuilder::default()
-                        .handle(
-                            multi_collector.add_collector(
-                                tantivy::collector::TopDocs::with_limit((top_docs_collector_proto.limit + 1) as usize)
-                                    .and_offset(top_docs_collector_proto.offset as usize),
-                            ),
-                        )
+                        .handle(handle)
                         .index_alias(index_alias.to_string())
                         .searcher(searcher)
                         .query(query.box_clone())
                         .limit(top_docs_collector_proto.limit)
                         .offset(top_docs_collector_proto.offset)
-                        .snippet_configs(top_docs_collector_proto.snippet_configs)
+                        .snippet_configs(merge_snippet_configs(&top_docs_collector_proto))
+                        .score_normalization(top_docs_collector_proto.score_normalization())
                         .multi_fields(index_holder.multi_fields().clone())
                         .query_fields(query_fields)
                         .build()?,
-                ) as Box<dyn FruitExtractor>,
+                ));
+            }
+            if let Some(collapse_config) = &top_docs_collector_proto.collapse {
+                let collapse_field = searcher
+                    .schema()
+                    .get_field(&collapse_config.field)
+                    .map_err(|_| ValidationError::MissingField(collapse_config.field.to_string()))?;
+                if !searcher.schema().get_field_entry(collapse_field).is_fast() {
+                    return Err(ValidationError::RequiredFastField(collapse_config.field.to_string()).into());
+                }
+                // `order_by` scores are not comparable with `min_score`
+                let min_score = match &top_docs_collector_proto.scorer {
+                    Some(proto::Scorer {
+                        scorer: Some(proto::scorer::Scorer::OrderBy(_)),
+                    }) => None,
+                    _ => top_docs_collector_proto.min_score,
+                };
+                return Ok(Box::new(
+                    collapsing_top_docs_builder(
+                        f64_score_tweaker(&top_docs_collector_proto.scorer, searcher.schema())?,
+                        min_score,
+                        &top_docs_collector_proto,
+                        collapse_config,
+                        multi_collector,
+                    )
+                    .index_alias(index_alias.to_string())
+                    .searcher(searcher)
+                    .query(query.box_clone())
+                    .multi_fields(index_holder.multi_fields().clone())
+                    .query_fields(query_fields)
+                    .build()?,
+                ) as Box<dyn FruitExtractor>);
+            }
+            let snippet_configs = merge_snippet_configs(&top_docs_collector_proto);
+            let score_normalization = top_docs_collector_proto.score_normalization();
+            let limit = (top_docs_collector_proto.limit + 1) as usize;
+            let offset = top_docs_collector_proto.offset as usize;
+            Ok(match top_docs_collector_proto.scorer {
+                None | Some(proto::Scorer { scorer: None }) => {
+                    let handle = match top_docs_collector_proto.min_score {
+                        Some(min_score) => {
+                            multi_collector.add_collector(collectors::MinScoreTopDocs::new(RelevanceScoreTweaker, limit, offset, min_score as Score))
+                        }
+                        None => multi_collector.add_collector(tantivy::collector::TopDocs::with_limit(limit).and_offset(offset)),
+                    };
+                    Box::new(
+                        TopDocsBuilder::default()
+                            .handle(handle)
+                            .index_alias(index_alias.to_string())
+                            .searcher(searcher)
+                            .query(query.box_clone())
+                            .limit(top_docs_collector_proto.limit)
+                            .offset(top_docs_collector_proto.offset)
+                            .snippet_configs(snippet_configs)
+                            .score_normalization(score_normalization)
+                            .multi_fields(index_holder.multi_fields().clone())
+                            .query_fields(query_fields)
+                            .build()?,
+                    ) as Box<dyn FruitExtractor>
+                }
                 Some(proto::Scorer {
                     scorer: Some(proto::scorer::Scorer::EvalExpr(ref eval_expr)),
                 }) => {
-                    let eval_scorer_seed = EvalScorer::new(eval_expr, searcher.schema())?;
-                    let top_docs_collector = tantivy::collector::TopDocs::with_limit((top_docs_collector_proto.limit + 1) as usize)
-                        .and_offset(top_docs_collector_proto.offset as usize)
-                        .tweak_score(EvalScorerTweaker::new(eval_scorer_seed));
+                    let eval_scorer_tweaker = EvalScorerTweaker::new(EvalScorer::new(eval_expr, searcher.schema())?);
+                    let handle = match top_docs_collector_proto.min_score {
+                        Some(min_score) => multi_collector.add_collector(collectors::MinScoreTopDocs::new(eval_scorer_tweaker, limit, offset, min_score)),
+                        None => multi_collector.add_collector(
+                            tantivy::collector::TopDocs::with_limit(limit)
+                                .and_offset(offset)
+                                .tweak_score(eval_scorer_tweaker),
+                        ),
+                    };
                     Box::new(
                         TopDocsBuilder::default()
-                            .handle(multi_collector.add_collector(top_docs_collector))
+                            .handle(handle)
                             .index_alias(index_alias.to_string())
                             .searcher(searcher)
                             .query(query.box_clone())
                             .limit(top_docs_collector_proto.limit)
                             .offset(top_docs_collector_proto.offset)
-                            .snippet_configs(top_docs_collector_proto.snippet_configs)
+                            .snippet_configs(snippet_configs)
+                            .score_normalization(score_normalization)
                             .multi_fields(index_holder.multi_fields().clone())
                             .query_fields(query_fields)
                             .build()?,
@@ -144,8 +237,8 @@ pub fn build_fruit_extractor(
                 Some(proto::Scorer {
                     scorer: Some(proto::scorer::Scorer::OrderBy(field_name)),
                 }) => {
-                    let top_docs_collector = tantivy::collector::TopDocs::with_limit((top_docs_collector_proto.limit + 1) as usize)
-                        .and_offset(top_docs_collector_proto.offset as usize)
+                    let top_docs_collector = tantivy::collector::TopDocs::with_limit(limit)
+                        .and_offset(offset)
                         .order_by_fast_field(field_name, Order::Desc);
                     Box::<TopDocs<u64>>::new(
                         TopDocsBuilder::default()
@@ -155,7 +248,8 @@ pub fn build_fruit_extractor(
                             .query(query.box_clone())
                             .limit(top_docs_collector_proto.limit)
                             .offset(top_docs_collector_proto.offset)
-                            .snippet_configs(top_docs_collector_proto.snippet_configs)
+                            .snippet_configs(snippet_configs)
+                            .score_normalization(score_normalization)
                             .multi_fields(index_holder.multi_fields().clone())
                             .query_fields(query_fields)
                             .build()?,
@@ -163,6 +257,68 @@ pub fn build_fruit_extractor(
                 }
             })
         }
+        Some(proto::collector::Collector::Fusion(fusion_collector_proto)) => {
+            if fusion_collector_proto.sources.is_empty() {
+                return Err(ValidationError::EmptyArgument("sources".to_string()).into());
+            }
+            let query_fields = validators::parse_fields(searcher.schema(), &fusion_collector_proto.fields, &fusion_collector_proto.excluded_fields)?;
+            let query_fields = (!query_fields.is_empty()).then(|| HashSet::from_iter(query_fields.into_iter().map(|x| x.0)));
+            let sources = fusion_collector_proto
+                .sources
+                .iter()
+                .map(|source| {
+                    let source_query = match &source.query {
+                        Some(proto::Query { query: Some(source_query) }) => {
+                            index_holder.query_parser().parse_query_with_limits(source_query.clone(), search_limits)?
+                        }
+                        _ => query.box_clone(),
+                    };
+                    let score_tweaker = f64_score_tweaker(&source.scorer, searcher.schema())?;
+                    Ok(collectors::FusionSource::new(source_query, score_tweaker, source.weight.unwrap_or(1.0)))
+                })
+                .collect::<SummaResult<Vec<_>>>()?;
+            let source_names = fusion_collector_proto
+                .sources
+                .iter()
+                .enumerate()
+                .map(|(source_ord, source)| {
+                    if source.name.is_empty() {
+                        source_ord.to_string()
+                    } else {
+                        source.name.clone()
+                    }
+                })
+                .collect();
+            let method = match fusion_collector_proto.method() {
+                proto::FusionMethod::ReciprocalRank => collectors::FusionMethod::ReciprocalRank {
+                    rank_constant: fusion_collector_proto.rank_constant.unwrap_or(60) as f64,
+                },
+                proto::FusionMethod::WeightedScore => collectors::FusionMethod::WeightedScore,
+            };
+            let window_size = fusion_collector_proto
+                .window_size
+                .unwrap_or(fusion_collector_proto.offset + fusion_collector_proto.limit + 1);
+            let fusion_collector = collectors::Fusion::new(
+                searcher.clone(),
+                sources,
+                method,
+                window_size as usize,
+                (fusion_collector_proto.limit + 1) as usize,
+                fusion_collector_proto.offset as usize,
+            );
+            Ok(Box::new(
+                FusionBuilder::default()
+                    .handle(multi_collector.add_collector(fusion_collector))
+                    .index_alias(index_alias.to_string())
+                    .searcher(searcher)
+                    .source_names(source_names)
+                    .limit(fusion_collector_proto.limit)
+                    .offset(fusion_collector_proto.offset)
+                    .multi_fields(index_holder.multi_fields().clone())
+                    .query_fields(query_fields)
+                    .build()?,
+            ))
+        }
         Some(proto::collector::Collector::ReservoirSampling(reservoir_sampling_collector_proto)) => {
             let query_fields = validators::parse_fields(
                 searcher.schema(),
@@ -183,23 +339,133 @@ pub fn build_fruit_extractor(
             ) as Box<dyn FruitExtractor>)
         }
         Some(proto::collector::Collector::Count(_)) => Ok(Box::new(Count(multi_collector.add_collector(tantivy::collector::Count))) as Box<dyn FruitExtractor>),
-        Some(proto::collector::Collector::Facet(facet_collector_proto)) => {
-            let mut facet_collector = tantivy::collector::FacetCollector::for_field(facet_collector_proto.field);
-            for facet in &facet_collector_proto.facets {
-                facet_collector.add_facet(facet);
+        Some(proto::collector::Collector::Cardinality(cardinality_collector_proto)) => {
+            let field = searcher
+                .schema()
+                .get_field(&cardinality_collector_proto.field)
+                .map_err(|_| ValidationError::MissingField(cardinality_collector_proto.field.to_string()))?;
+            if !searcher.schema().get_field_entry(field).is_fast() {
+                return Err(ValidationError::RequiredFastField(cardinality_collector_proto.field.to_string()).into());
             }
+            let precision = cardinality_collector_proto.precision.unwrap_or(14);
+            if !(collectors::Cardinality::MIN_PRECISION as u32..=collectors::Cardinality::MAX_PRECISION as u32).contains(&precision) {
+                return Err(ValidationError::InvalidCardinalityPrecision(precision).into());
+            }
+            let cardinality_collector = collectors::Cardinality::new(
+                &cardinality_collector_proto.field,
+                precision as u8,
+                cardinality_collector_proto.exact_threshold.unwrap_or(3000) as usize,
+            );
+            Ok(Box::new(Cardinality(multi_collector.add_collector(cardinality_collector))) as Box<dyn FruitExtractor>)
+        }
+        Some(proto::collector::Collector::SignificantTerms(significant_terms_collector_proto)) => {
+            let field = searcher
+                .schema()
+                .get_field(&significant_terms_collector_proto.field)
+                .map_err(|_| ValidationError::MissingField(significant_terms_collector_proto.field.to_string()))?;
+            let field_type = searcher.schema().get_field_entry(field).field_type();
+            if !matches!(field_type, FieldType::Str(_)) || !field_type.is_indexed() {
+                return Err(Error::InvalidFieldType(significant_terms_collector_proto.field.to_string(), field_type.clone()));
+            }
+            let heuristic = match significant_terms_collector_proto.heuristic() {
+                proto::SignificanceHeuristic::Jlh => collectors::SignificanceHeuristic::Jlh,
+                proto::SignificanceHeuristic::ChiSquare => collectors::SignificanceHeuristic::ChiSquare,
+                proto::SignificanceHeuristic::MutualInformation => collectors::SignificanceHeuristic::MutualInformation,
+            };
+            let significant_terms_collector = collectors::SignificantTerms::new(
+                searcher.clone(),
+                field,
+                significant_terms_collector_proto.top_k as usize,
+                heuristic,
+                significant_terms_collector_proto.min_doc_count.unwrap_or(3),
+            );
+            Ok(Box::new(SignificantTerms(multi_collector.add_collector(significant_terms_collector))) as Box<dyn FruitExtractor>)
+        }
+        Some(proto::collector::Collector::Facet(facet_collector_proto)) => {
+            searcher
+                .schema()
+                .get_field(&facet_collector_proto.field)
+                .map_err(|_| ValidationError::MissingField(facet_collector_proto.field.to_string()))?;
+            let facets = facet_collector_proto
+                .facets
+                .iter()
+                .map(|facet| tantivy::schema::Facet::from_text(facet).map_err(|_| ValidationError::InvalidFacet(facet.to_string())))
+                .collect::<Result<Vec<_>, _>>()?;
+            let facet_collector = collectors::HierarchicalFacets::new(
+                &facet_collector_proto.field,
+                &facets,
+                facet_collector_proto.depth.unwrap_or(1) as usize,
+                facet_collector_proto.top_k.map(|top_k| top_k as usize),
+            );
             Ok(Box::new(Facet(multi_collector.add_collector(facet_collector))) as Box<dyn FruitExtractor>)
         }
         Some(proto::collector::Collector::Aggregation(aggregation_collector_proto)) => {
             let agg_req: Aggregations = serde_json::from_str(&aggregation_collector_proto.aggregations)?;
-            let aggregation_collector =
-                tantivy::aggregation::AggregationCollector::from_aggs(agg_req, AggregationLimitsGuard::new(Some(16_000_000_000), Some(100_000_000)));
+            let aggregation_collector = tantivy::aggregation::AggregationCollector::from_aggs(agg_req, aggregation_limits(search_limits));
             Ok(Box::new(Aggregation(multi_collector.add_collector(aggregation_collector))) as Box<dyn FruitExtractor>)
         }
+        Some(proto::collector::Collector::TypedAggregation(typed_aggregation_collector_proto)) => {
+            let agg_req = Aggregations::try_from(Wrapper::from(typed_aggregation_collector_proto.aggregations))?;
+            let aggregation_collector = tantivy::aggregation::AggregationCollector::from_aggs(agg_req, aggregation_limits(search_limits));
+            Ok(Box::new(TypedAggregation(multi_collector.add_collector(aggregation_collector))) as Box<dyn FruitExtractor>)
+        }
         None => Ok(Box::new(Count(multi_collector.add_collector(tantivy::collector::Count))) as Box<dyn FruitExtractor>),
     }
 }
 
+/// Returns the query for collecting drill-sideways facet collector or `None` if the collector may share the original query
+///
+/// Top-level `must` and `must_not` clauses having terms only of the facet field are dropped, so the counts of the facet field
+/// are not affected by filters on it
+pub fn drill_sideways_query(searcher: &Searcher, collector_proto: &proto::Collector, query: &dyn Query) -> SummaResult<Option<Box<dyn Query>>> {
+    let Some(proto::collector::Collector::Facet(facet_collector_proto)) = &collector_proto.collector else {
+        return Ok(None);
+    };
+    if !facet_collector_proto.drill_sideways {
+        return Ok(None);
+    }
+    let facet_field = searcher
+        .schema()
+        .get_field(&facet_collector_proto.field)
+        .map_err(|_| ValidationError::MissingField(facet_collector_proto.field.to_string()))?;
+    let is_facet_filter = |query: &dyn Query| {
+        let mut has_terms = false;
+        let mut has_other_terms = false;
+        query.query_terms(&mut |term, _| {
+            has_terms = true;
+            has_other_terms |= term.field() != facet_field;
+        });
+        has_terms && !has_other_terms
+    };
+    if is_facet_filter(query) {
+        return Ok(Some(Box::new(AllQuery)));
+    }
+    let Some(boolean_query) = query.as_any().downcast_ref::<BooleanQuery>() else {
+        return Ok(None);
+    };
+    let mut clauses: Vec<_> = boolean_query
+        .clauses()
+        .iter()
+        .filter(|(occur, clause)| *occur == Occur::Should || !is_facet_filter(clause.as_ref()))
+        .map(|(occur, clause)| (*occur, clause.box_clone()))
+        .collect();
+    if clauses.len() == boolean_query.clauses().len() {
+        return Ok(None);
+    }
+    if clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+        clauses.push((Occur::Must, Box::new(AllQuery)));
+    }
+    Ok(Some(Box::new(BooleanQuery::new(clauses))))
+}
+
+/// Memory and buckets limits shared by JSON and typed aggregations
+fn aggregation_limits(search_limits: &SearchLimits) -> AggregationLimitsGuard {
+    AggregationLimitsGuard::new(
+        Some(search_limits.0.max_aggregation_memory_bytes.unwrap_or(16_000_000_000)),
+        Some(search_limits.0.max_aggregation_buckets.unwrap_or(100_000_000)),
+    )
+}
+
 #[derive(Builder)]
 #[builder(pattern = "owned", build_fn(error = "BuilderError"))]
 pub struct TopDocs<T: 'static + Copy + Into<proto::Score> + Sync + Send> {
@@ -208,7 +474,9 @@ pub struct TopDocs<T: 'static + Copy + Into<proto::Score> + Sync + Send> {
     handle: FruitHandle<Vec<(T, tantivy::DocAddress)>>,
     limit: u32,
     offset: u32,
-    snippet_configs: HashMap<String, u32>,
+    snippet_configs: HashMap<String, proto::SnippetConfig>,
+    #[builder(default = "proto::ScoreNormalization::None")]
+    score_normalization: proto::ScoreNormalization,
     query: Box<dyn Query>,
     #[builder(default = "None")]
     query_fields: Option<HashSet<Field>>,
@@ -223,9 +491,190 @@ impl<T: 'static + Copy + Into<proto::Score> + Sync + Send> FruitExtractor for To
         let doc_addresses = fruit
             .into_iter()
             .take(std::cmp::min(self.limit as usize, length))
-            .map(|(score, doc_address)| ScoredDocAddress {
-                doc_address,
-                score: Some(score.into()),
+            .map(|(score, doc_address)| ScoredDocAddress::new(doc_address, score.into()))
+            .collect();
+        Ok(IntermediateExtractionResult::PreparedDocumentReferences(PreparedDocumentReferences {
+            index_alias: self.index_alias,
+            extraction_tooling: ExtractionTooling::new(self.searcher.clone(), self.query_fields, self.multi_fields),
+            snippet_generator_config: Some(SnippetGeneratorConfig::new(self.searcher, self.query, self.snippet_configs)),
+            scored_doc_addresses: doc_addresses,
+            has_next: length > self.limit as usize,
+            groups_count: None,
+            limit: self.limit,
+            offset: self.offset,
+            score_normalization: self.score_normalization,
+        }))
+    }
+}
+
+/// Merges legacy `snippet_configs` having only sizes of fragments with `snippet_field_configs`
+fn merge_snippet_configs(top_docs_collector_proto: &proto::TopDocsCollector) -> HashMap<String, proto::SnippetConfig> {
+    let mut snippet_configs: HashMap<_, _> = top_docs_collector_proto
+        .snippet_configs
+        .iter()
+        .map(|(field_name, fragment_size)| {
+            (
+                field_name.to_string(),
+                proto::SnippetConfig {
+                    fragment_size: Some(*fragment_size),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+    snippet_configs.extend(top_docs_collector_proto.snippet_field_configs.clone());
+    snippet_configs
+}
+
+/// Adds `RescoringTopDocs` collector to `multi_collector` and returns the handle of its fruit
+fn rescoring_top_docs_handle(
+    index_holder: &IndexHolder,
+    searcher: &Searcher,
+    top_docs_collector_proto: &proto::TopDocsCollector,
+    rescore_config: &proto::RescoreConfig,
+    multi_collector: &mut MultiCollector,
+    search_limits: &SearchLimits,
+) -> SummaResult<FruitHandle<Vec<(f64, tantivy::DocAddress)>>> {
+    if rescore_config.window_size == 0 {
+        return Err(ValidationError::InvalidRescore("`window_size` must be positive".to_string()).into());
+    }
+    if top_docs_collector_proto.collapse.is_some() {
+        return Err(ValidationError::InvalidRescore("collapsed documents cannot be rescored".to_string()).into());
+    }
+    let rescore_tweaker = match &rescore_config.scorer {
+        Some(proto::rescore_config::Scorer::EvalExpr(eval_expr)) => {
+            RescoreTweaker::EvalExpr(EvalScorerTweaker::new(EvalScorer::new(eval_expr, searcher.schema())?))
+        }
+        Some(proto::rescore_config::Scorer::Query(proto::Query { query: Some(query) })) => RescoreTweaker::Query(QueryScoreTweaker::new(
+            index_holder.query_parser().parse_query_with_limits(query.clone(), search_limits)?,
+            searcher.clone(),
+        )),
+        Some(proto::rescore_config::Scorer::Query(proto::Query { query: None })) | None => {
+            return Err(ValidationError::InvalidRescore("second phase scorer is not set".to_string()).into())
+        }
+    };
+    let window_size = rescore_config.window_size as usize;
+    let query_weight = rescore_config.query_weight.unwrap_or(1.0);
+    let rescore_query_weight = rescore_config.rescore_query_weight.unwrap_or(1.0);
+    let limit = (top_docs_collector_proto.limit + 1) as usize;
+    let offset = top_docs_collector_proto.offset as usize;
+    Ok(match &top_docs_collector_proto.scorer {
+        None | Some(proto::Scorer { scorer: None }) => {
+            let mut rescoring_top_docs_collector = collectors::RescoringTopDocs::new(
+                RelevanceScoreTweaker,
+                rescore_tweaker,
+                window_size,
+                query_weight,
+                rescore_query_weight,
+                limit,
+                offset,
+            );
+            if let Some(min_score) = top_docs_collector_proto.min_score {
+                rescoring_top_docs_collector = rescoring_top_docs_collector.with_min_score(min_score as Score);
+            }
+            multi_collector.add_collector(rescoring_top_docs_collector)
+        }
+        Some(proto::Scorer {
+            scorer: Some(proto::scorer::Scorer::EvalExpr(eval_expr)),
+        }) => {
+            let mut rescoring_top_docs_collector = collectors::RescoringTopDocs::new(
+                EvalScorerTweaker::new(EvalScorer::new(eval_expr, searcher.schema())?),
+                rescore_tweaker,
+                window_size,
+                query_weight,
+                rescore_query_weight,
+                limit,
+                offset,
+            );
+            if let Some(min_score) = top_docs_collector_proto.min_score {
+                rescoring_top_docs_collector = rescoring_top_docs_collector.with_min_score(min_score);
+            }
+            multi_collector.add_collector(rescoring_top_docs_collector)
+        }
+        Some(proto::Scorer {
+            scorer: Some(proto::scorer::Scorer::OrderBy(_)),
+        }) => return Err(ValidationError::InvalidRescore("documents ordered by a fast field cannot be rescored".to_string()).into()),
+    })
+}
+
+/// Scorer of any kind producing `f64` scores
+fn f64_score_tweaker(scorer: &Option<proto::Scorer>, schema: &Schema) -> SummaResult<FusionScoreTweaker> {
+    Ok(match scorer {
+        None | Some(proto::Scorer { scorer: None }) => FusionScoreTweaker::Relevance,
+        Some(proto::Scorer {
+            scorer: Some(proto::scorer::Scorer::EvalExpr(eval_expr)),
+        }) => FusionScoreTweaker::EvalExpr(EvalScorerTweaker::new(EvalScorer::new(eval_expr, schema)?)),
+        Some(proto::Scorer {
+            scorer: Some(proto::scorer::Scorer::OrderBy(field_name)),
+        }) => FusionScoreTweaker::OrderBy(FastFieldScoreTweaker::new(field_name, schema)?),
+    })
+}
+
+/// Adds `CollapsingTopDocs` collector to `multi_collector` and returns partially filled builder of its extractor
+fn collapsing_top_docs_builder<TScoreTweaker, TScore>(
+    score_tweaker: TScoreTweaker,
+    min_score: Option<TScore>,
+    top_docs_collector_proto: &proto::TopDocsCollector,
+    collapse_config: &proto::CollapseConfig,
+    multi_collector: &mut MultiCollector,
+) -> CollapsingTopDocsBuilder<TScore>
+where
+    TScoreTweaker: 'static + ScoreTweaker<TScore> + Send + Sync,
+    TScoreTweaker::Child: Send,
+    TScore: 'static + Copy + PartialOrd + Into<proto::Score> + Sync + Send,
+{
+    let mut collapsing_top_docs_collector = collectors::CollapsingTopDocs::new(
+        score_tweaker,
+        &collapse_config.field,
+        (top_docs_collector_proto.limit + 1) as usize,
+        top_docs_collector_proto.offset as usize,
+        collapse_config.inner_hits_limit as usize,
+    );
+    if let Some(min_score) = min_score {
+        collapsing_top_docs_collector = collapsing_top_docs_collector.with_min_score(min_score);
+    }
+    CollapsingTopDocsBuilder::default()
+        .handle(multi_collector.add_collector(collapsing_top_docs_collector))
+        .limit(top_docs_collector_proto.limit)
+        .offset(top_docs_collector_proto.offset)
+        .snippet_configs(merge_snippet_configs(top_docs_collector_proto))
+        .score_normalization(top_docs_collector_proto.score_normalization())
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned", build_fn(error = "BuilderError"))]
+pub struct CollapsingTopDocs<T: 'static + Copy + Into<proto::Score> + Sync + Send> {
+    searcher: Searcher,
+    index_alias: String,
+    handle: FruitHandle<collectors::CollapsedTopDocs<T>>,
+    limit: u32,
+    offset: u32,
+    snippet_configs: HashMap<String, proto::SnippetConfig>,
+    #[builder(default = "proto::ScoreNormalization::None")]
+    score_normalization: proto::ScoreNormalization,
+    query: Box<dyn Query>,
+    #[builder(default = "None")]
+    query_fields: Option<HashSet<Field>>,
+    multi_fields: HashSet<Field>,
+}
+
+impl<T: 'static + Copy + Into<proto::Score> + Sync + Send> FruitExtractor for CollapsingTopDocs<T> {
+    fn extract(self: Box<Self>, multi_fruit: &mut MultiFruit) -> SummaResult<IntermediateExtractionResult> {
+        let fruit = self.handle.extract(multi_fruit);
+        let length = fruit.groups.len();
+        let doc_addresses = fruit
+            .groups
+            .into_iter()
+            .take(std::cmp::min(self.limit as usize, length))
+            .map(|group| {
+                let mut hits = group
+                    .hits
+                    .into_iter()
+                    .map(|(score, doc_address)| ScoredDocAddress::new(doc_address, score.into()));
+                let mut best_hit = hits.next().expect("groups are never empty");
+                best_hit.inner_hits = hits.collect();
+                best_hit.group_size = Some(group.size);
+                best_hit
             })
             .collect();
         Ok(IntermediateExtractionResult::PreparedDocumentReferences(PreparedDocumentReferences {
@@ -234,8 +683,10 @@ impl<T: 'static + Copy + Into<proto::Score> + Sync + Send> FruitExtractor for To
             snippet_generator_config: Some(SnippetGeneratorConfig::new(self.searcher, self.query, self.snippet_configs)),
             scored_doc_addresses: doc_addresses,
             has_next: length > self.limit as usize,
+            groups_count: Some(fruit.groups_count),
             limit: self.limit,
             offset: self.offset,
+            score_normalization: self.score_normalization,
         }))
     }
 }
@@ -260,21 +711,84 @@ impl FruitExtractor for ReservoirSampling {
                 .handle
                 .extract(multi_fruit)
                 .into_iter()
-                .map(|doc_address| ScoredDocAddress {
-                    doc_address,
-                    score: Some(rng.gen::<f64>().into()),
-                })
+                .map(|doc_address| ScoredDocAddress::new(doc_address, rng.gen::<f64>().into()))
                 .collect(),
             index_alias: self.index_alias,
             has_next: false,
+            groups_count: None,
             limit: self.limit,
             extraction_tooling: ExtractionTooling::new(self.searcher, self.query_fields, self.multi_fields),
             snippet_generator_config: None,
             offset: 0,
+            score_normalization: proto::ScoreNormalization::None,
         }))
     }
 }
 
+#[derive(Builder)]
+#[builder(pattern = "owned", build_fn(error = "BuilderError"))]
+pub struct Fusion {
+    searcher: Searcher,
+    index_alias: String,
+    handle: FruitHandle<Vec<collectors::FusedHit>>,
+    source_names: Vec<String>,
+    limit: u32,
+    offset: u32,
+    #[builder(default = "None")]
+    query_fields: Option<HashSet<Field>>,
+    multi_fields: HashSet<Field>,
+}
+
+impl FruitExtractor for Fusion {
+    fn extract(self: Box<Self>, multi_fruit: &mut MultiFruit) -> SummaResult<IntermediateExtractionResult> {
+        let fused_hits = self.handle.extract(multi_fruit);
+        let length = fused_hits.len();
+        let source_names = &self.source_names;
+        let doc_addresses = fused_hits
+            .into_iter()
+            .take(self.limit as usize)
+            .map(|fused_hit| {
+                let mut scored_doc_address = ScoredDocAddress::new(fused_hit.doc_address, fused_hit.score.into());
+                scored_doc_address.source_ranks = source_names
+                    .iter()
+                    .zip(fused_hit.source_hits)
+                    .filter_map(|(source_name, source_hit)| {
+                        let (rank, score) = source_hit?;
+                        Some(proto::SourceRank {
+                            source: source_name.clone(),
+                            rank,
+                            score: Some(score.into()),
+                        })
+                    })
+                    .collect();
+                scored_doc_address
+            })
+            .collect();
+        Ok(IntermediateExtractionResult::PreparedDocumentReferences(PreparedDocumentReferences {
+            index_alias: self.index_alias,
+            extraction_tooling: ExtractionTooling::new(self.searcher, self.query_fields, self.multi_fields),
+            snippet_generator_config: None,
+            scored_doc_addresses: doc_addresses,
+            has_next: length > self.limit as usize,
+            groups_count: None,
+            limit: self.limit,
+            offset: self.offset,
+            score_normalization: proto::ScoreNormalization::None,
+        }))
+    }
+}
+
+pub struct Cardinality(pub FruitHandle<collectors::DistinctValues>);
+
+impl FruitExtractor for Cardinality {
+    fn extract(self: Box<Self>, multi_fruit: &mut MultiFruit) -> SummaResult<IntermediateExtractionResult> {
+        let (cardinality, is_exact) = self.0.extract(multi_fruit).cardinality();
+        Ok(IntermediateExtractionResult::Ready(ReadyCollectorOutput::Cardinality(
+            proto::CardinalityCollectorOutput { cardinality, is_exact },
+        )))
+    }
+}
+
 pub struct Count(pub FruitHandle<usize>);
 
 impl FruitExtractor for Count {
@@ -285,12 +799,25 @@ impl FruitExtractor for Count {
     }
 }
 
-pub struct Facet(pub FruitHandle<FacetCounts>);
+fn facet_node(node: collectors::FacetNode) -> proto::FacetNode {
+    proto::FacetNode {
+        facet: node.facet.to_string(),
+        count: node.count,
+        children: node.children.into_iter().map(facet_node).collect(),
+    }
+}
+
+pub struct Facet(pub FruitHandle<Vec<collectors::FacetNode>>);
 
 impl FruitExtractor for Facet {
     fn extract(self: Box<Self>, multi_fruit: &mut MultiFruit) -> SummaResult<IntermediateExtractionResult> {
+        let facet_nodes = self.0.extract(multi_fruit);
         Ok(IntermediateExtractionResult::Ready(ReadyCollectorOutput::Facet(proto::FacetCollectorOutput {
-            facet_counts: self.0.extract(multi_fruit).get("").map(|(facet, count)| (facet.to_string(), count)).collect(),
+            facet_counts: facet_nodes
+                .iter()
+                .flat_map(|facet_node| facet_node.children.iter().map(|child| (child.facet.to_string(), child.count)))
+                .collect(),
+            facet_nodes: facet_nodes.into_iter().map(facet_node).collect(),
         })))
     }
 }
@@ -306,3 +833,37 @@ impl FruitExtractor for Aggregation {
         )))
     }
 }
+
+pub struct TypedAggregation(pub FruitHandle<AggregationResults>);
+
+impl FruitExtractor for TypedAggregation {
+    fn extract(self: Box<Self>, multi_fruit: &mut MultiFruit) -> SummaResult<IntermediateExtractionResult> {
+        Ok(IntermediateExtractionResult::Ready(ReadyCollectorOutput::TypedAggregation(
+            proto::TypedAggregationCollectorOutput {
+                aggregation_results: Wrapper::from(self.0.extract(multi_fruit)).into_inner(),
+            },
+        )))
+    }
+}
+
+pub struct SignificantTerms(pub FruitHandle<Vec<collectors::SignificantTerm>>);
+
+impl FruitExtractor for SignificantTerms {
+    fn extract(self: Box<Self>, multi_fruit: &mut MultiFruit) -> SummaResult<IntermediateExtractionResult> {
+        Ok(IntermediateExtractionResult::Ready(ReadyCollectorOutput::SignificantTerms(
+            proto::SignificantTermsCollectorOutput {
+                significant_terms: self
+                    .0
+                    .extract(multi_fruit)
+                    .into_iter()
+                    .map(|significant_term| proto::SignificantTerm {
+                        term: significant_term.term,
+                        score: significant_term.score,
+                        doc_count: significant_term.doc_count,
+                        background_doc_count: significant_term.background_doc_count,
+                    })
+                    .collect(),
+            },
+        )))
+    }
+}
@@ -2,9 +2,11 @@ use std::collections::HashSet;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use futures::future::{join_all, try_join_all};
+use instant::Instant;
 use serde::Deserialize;
 use summa_proto::proto;
 use summa_proto::proto::IndexAttributes;
@@ -18,17 +20,24 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use super::SummaSegmentAttributes;
-use super::{build_fruit_extractor, default_tokenizers, FruitExtractor, ProtoQueryParser};
-use crate::components::collector_cache::CollectorCache;
+use super::{build_fruit_extractor, default_tokenizers, drill_sideways_query, query_to_tree, FruitExtractor, ProtoQueryParser, QueryProfile, SearchDeadline};
+use crate::components::collector_cache::{CollectorCache, QueryCachingKey};
+use crate::components::document_expiry::DocumentExpiry;
 use crate::components::fruit_extractors::IntermediateExtractionResult;
 use crate::components::segment_attributes::SegmentAttributesMergerImpl;
-use crate::components::{IndexWriterHolder, SummaDocument};
+use crate::components::{IndexWriterHolder, IndexingOutcome, SummaDocument};
+use crate::configs::core::SearchLimits;
 use crate::configs::ConfigProxy;
 use crate::directories::{CachingDirectory, ExternalRequest, ExternalRequestGenerator, FileStats, HotDirectory, NetworkDirectory, StaticDirectoryCache};
 use crate::errors::{SummaResult, ValidationError};
 use crate::proto_traits::Wrapper;
 use crate::Error;
 
+/// Query and collectors that are collected with it in a single search pass
+type SearchPass = (Box<dyn Query>, MultiCollector<'static>);
+/// Extractor of a collector and the index of the search pass collecting it
+type SearchPassExtractor = (usize, Box<dyn FruitExtractor>);
+
 pub struct IndexHolder {
     index_engine_config: Arc<dyn ConfigProxy<proto::IndexEngineConfig>>,
     index_name: String,
@@ -39,8 +48,10 @@ pub struct IndexHolder {
     index_reader: IndexReader,
     index_writer_holder: Option<Arc<RwLock<IndexWriterHolder>>>,
     query_parser: ProtoQueryParser,
+    document_expiry: Option<DocumentExpiry>,
     /// Counters
     collector_cache: parking_lot::Mutex<CollectorCache>,
+    expired_documents: AtomicU64,
 }
 
 impl Hash for IndexHolder {
@@ -151,6 +162,7 @@ impl IndexHolder {
         index_engine_config: Arc<dyn ConfigProxy<proto::IndexEngineConfig>>,
         merge_policy: Option<proto::MergePolicy>,
         query_parser_config: proto::QueryParserConfig,
+        search_limits: Option<proto::SearchLimits>,
     ) -> SummaResult<IndexHolder> {
         register_default_tokenizers(&index);
 
@@ -171,8 +183,13 @@ impl IndexHolder {
             })
             .transpose()?
             .unwrap_or_default();
+        let document_expiry = cached_index_attributes
+            .as_ref()
+            .and_then(|index_attributes| index_attributes.expiry.as_ref())
+            .map(|expiry| DocumentExpiry::new(&cached_schema, expiry))
+            .transpose()?;
 
-        let query_parser = ProtoQueryParser::for_index(&index, query_parser_config)?;
+        let query_parser = ProtoQueryParser::for_index(&index, query_parser_config)?.with_search_limits(SearchLimits(search_limits.unwrap_or_default()));
         let index_reader = index
             .reader_builder()
             .doc_store_cache_num_blocks(core_config.doc_store_cache_num_blocks)
@@ -203,7 +220,9 @@ impl IndexHolder {
             cached_multi_fields,
             index_reader,
             index_writer_holder,
+            document_expiry,
             collector_cache: parking_lot::Mutex::new(CollectorCache::new(&core_config.collector_cache)),
+            expired_documents: AtomicU64::new(0),
         })
     }
 
@@ -320,6 +339,11 @@ impl IndexHolder {
         &self.cached_multi_fields
     }
 
+    /// Parser of queries of the index
+    pub fn query_parser(&self) -> &ProtoQueryParser {
+        &self.query_parser
+    }
+
     /// Return internal Tantivy index
     pub fn real_directory(&self) -> &dyn Directory {
         self.index.directory().real_directory()
@@ -381,6 +405,8 @@ impl IndexHolder {
         query: &dyn Query,
         collector: &MultiCollector<'_>,
         is_fieldnorms_scoring_enabled: Option<bool>,
+        search_deadline: &SearchDeadline,
+        query_profile: &QueryProfile,
     ) -> tantivy::Result<MultiFruit> {
         let enabled_scoring = match (is_fieldnorms_scoring_enabled, collector.requires_scoring()) {
             (Some(true), true) | (None, true) => EnableScoring::enabled_from_searcher(searcher),
@@ -389,16 +415,27 @@ impl IndexHolder {
         };
         let segment_readers = searcher.segment_readers();
         trace!(index_name = ?self.index_name, action = "weight");
-        let weight = query.weight_async(enabled_scoring).await?;
+        let weight = search_deadline.wrap_weight(query.weight_async(enabled_scoring).await?);
         trace!(index_name = ?self.index_name, action = "collect_segment");
         let fruits = join_all(segment_readers.iter().enumerate().map(|(segment_ord, segment_reader)| {
             let weight_ref = weight.as_ref();
-            collector.collect_segment_async(weight_ref, segment_ord as u32, segment_reader)
+            async move {
+                if search_deadline.is_expired() {
+                    return Ok(None);
+                }
+                let started_at = Instant::now();
+                let fruit = collector.collect_segment_async(weight_ref, segment_ord as u32, segment_reader).await?;
+                query_profile.record_segment(segment_ord as u32, started_at.elapsed());
+                Ok(Some(fruit))
+            }
         }))
         .await
         .into_iter()
         .collect::<tantivy::Result<Vec<_>>>()?;
-        collector.merge_fruits(fruits)
+        if search_deadline.is_exceeded() {
+            warn!(index_name = ?self.index_name, action = "search_deadline_exceeded");
+        }
+        collector.merge_fruits(fruits.into_iter().flatten().collect())
     }
 
     pub fn search_in_segments(
@@ -407,17 +444,98 @@ impl IndexHolder {
         query: &dyn Query,
         collector: &MultiCollector<'_>,
         is_fieldnorms_scoring_enabled: Option<bool>,
+        search_deadline: &SearchDeadline,
+        query_profile: &QueryProfile,
     ) -> tantivy::Result<MultiFruit> {
         let enabled_scoring = match (is_fieldnorms_scoring_enabled, collector.requires_scoring()) {
             (Some(true), true) | (None, true) => EnableScoring::enabled_from_searcher(searcher),
             (Some(false), true) => EnableScoring::enabled_from_searcher_without_fieldnorms(searcher),
             (_, false) => EnableScoring::disabled_from_searcher(searcher),
         };
-        searcher.search_with_executor(query, collector, searcher.index().search_executor(), enabled_scoring)
+        let weight = search_deadline.wrap_weight(query.weight(enabled_scoring)?);
+        let fruits = searcher.index().search_executor().map(
+            |(segment_ord, segment_reader)| {
+                if search_deadline.is_expired() {
+                    return Ok(None);
+                }
+                let started_at = Instant::now();
+                let fruit = collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)?;
+                query_profile.record_segment(segment_ord as u32, started_at.elapsed());
+                Ok(Some(fruit))
+            },
+            searcher.segment_readers().iter().enumerate(),
+        )?;
+        if search_deadline.is_exceeded() {
+            warn!(index_name = ?self.index_name, action = "search_deadline_exceeded");
+        }
+        collector.merge_fruits(fruits.into_iter().flatten().collect())
+    }
+
+    /// Builds extractors for `collectors` and groups collectors by queries they have to be collected with
+    ///
+    /// All collectors share the parsed query except drill-sideways facet collectors getting their own search passes
+    fn build_search_passes(
+        &self,
+        index_alias: &str,
+        searcher: &Searcher,
+        parsed_query: &dyn Query,
+        collectors: &[proto::Collector],
+        search_limits: &SearchLimits,
+    ) -> SummaResult<(Vec<SearchPass>, Vec<SearchPassExtractor>)> {
+        let mut search_passes = vec![(parsed_query.box_clone(), MultiCollector::new())];
+        let extractors = collectors
+            .iter()
+            .map(|collector_proto| {
+                let search_pass = match drill_sideways_query(searcher, collector_proto, parsed_query)? {
+                    Some(drill_sideways_query) => {
+                        search_passes.push((drill_sideways_query, MultiCollector::new()));
+                        search_passes.len() - 1
+                    }
+                    None => 0,
+                };
+                let (query, multi_collector) = &mut search_passes[search_pass];
+                let extractor = build_fruit_extractor(
+                    self,
+                    index_alias,
+                    searcher.clone(),
+                    collector_proto.clone(),
+                    query.as_ref(),
+                    multi_collector,
+                    search_limits,
+                )?;
+                Ok((search_pass, extractor))
+            })
+            .collect::<SummaResult<_>>()?;
+        Ok((search_passes, extractors))
+    }
+
+    /// Parse `query` with the index query parser and return the resulting query tree
+    pub async fn parse_query(&self, query: proto::query::Query) -> SummaResult<proto::ParseQueryResponse> {
+        #[cfg(feature = "tokio-rt")]
+        let (parsed_query, warnings) = {
+            let query_parser = self.query_parser.clone();
+            tokio::task::spawn_blocking(move || query_parser.parse_query_with_warnings(query)).await??
+        };
+        #[cfg(not(feature = "tokio-rt"))]
+        let (parsed_query, warnings) = self.query_parser.parse_query_with_warnings(query)?;
+        Ok(proto::ParseQueryResponse {
+            root: Some(query_to_tree(&self.cached_schema, parsed_query.as_ref())),
+            warnings,
+        })
     }
 
     pub fn search(&self, index_alias: &str, query: proto::query::Query, collectors: Vec<proto::Collector>) -> SummaResult<Vec<IntermediateExtractionResult>> {
-        self.custom_search(index_alias, query, collectors, None, None, None)
+        self.custom_search(
+            index_alias,
+            query,
+            collectors,
+            None,
+            None,
+            None,
+            &SearchLimits::default(),
+            &SearchDeadline::default(),
+            &QueryProfile::default(),
+        )
     }
 
     #[cfg(feature = "tokio-rt")]
@@ -427,10 +545,22 @@ impl IndexHolder {
         query: proto::query::Query,
         collectors: Vec<proto::Collector>,
     ) -> SummaResult<Vec<IntermediateExtractionResult>> {
-        self.custom_search_async(index_alias, query, collectors, None, None, None).await
+        self.custom_search_async(
+            index_alias,
+            query,
+            collectors,
+            None,
+            None,
+            None,
+            &SearchLimits::default(),
+            &SearchDeadline::default(),
+            &QueryProfile::default(),
+        )
+        .await
     }
 
     /// Search `query` in the `IndexHolder` and collecting `Fruit` with a list of `collectors`
+    #[allow(clippy::too_many_arguments)]
     pub async fn custom_search_async(
         &self,
         index_alias: &str,
@@ -439,6 +569,9 @@ impl IndexHolder {
         is_fieldnorms_scoring_enabled: Option<bool>,
         load_cache: Option<bool>,
         store_cache: Option<bool>,
+        search_limits: &SearchLimits,
+        search_deadline: &SearchDeadline,
+        query_profile: &QueryProfile,
     ) -> SummaResult<Vec<IntermediateExtractionResult>> {
         let collectors_len = collectors.len();
         let mut missed_collector_indices = Vec::with_capacity(collectors_len);
@@ -448,16 +581,29 @@ impl IndexHolder {
         let load_cache = load_cache.unwrap_or(false);
         let store_cache = store_cache.unwrap_or(false);
 
+        let search_limits = self.query_parser.search_limits().stricter(search_limits);
+        search_limits.check_collectors(&collectors)?;
+
+        let searcher = self.index_reader().searcher();
+        let caching_key = QueryCachingKey::new(
+            &searcher,
+            &query,
+            self.query_parser.query_parser_config(),
+            is_fieldnorms_scoring_enabled,
+            &search_limits,
+        );
+
         info!(action = "parse_query", index_name = ?self.index_name, query = ?query);
+        let parse_started_at = Instant::now();
         #[cfg(feature = "tokio-rt")]
         let parsed_query = {
             let query_parser = self.query_parser.clone();
-            tokio::task::spawn_blocking(move || query_parser.parse_query(query)).await??
+            let search_limits = search_limits.clone();
+            tokio::task::spawn_blocking(move || query_parser.parse_query_with_limits(query, &search_limits)).await??
         };
         #[cfg(not(feature = "tokio-rt"))]
-        let parsed_query = self.query_parser.parse_query(query)?;
-
-        let caching_key = format!("{:?}|{:?}", parsed_query, is_fieldnorms_scoring_enabled);
+        let parsed_query = self.query_parser.parse_query_with_limits(query, &search_limits)?;
+        query_profile.record_parse(parse_started_at.elapsed());
 
         if load_cache {
             let mut cache = self.collector_cache.lock();
@@ -465,21 +611,23 @@ impl IndexHolder {
                 let is_caching_enabled = CollectorCache::is_caching_enabled(&collector);
                 if is_caching_enabled {
                     let adjusted_collector = CollectorCache::adjust_collector(&collector);
-                    info!(action = "querying_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?adjusted_collector);
+                    info!(action = "querying_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?adjusted_collector);
                     match cache.get(&caching_key, &adjusted_collector, &collector) {
                         Some(cached_value) => {
-                            info!(action = "match_collector_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?adjusted_collector);
+                            info!(action = "match_collector_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?adjusted_collector);
+                            query_profile.record_cache(true);
                             collector_outputs[i] = Some(cached_value)
                         }
                         None => {
-                            info!(action = "mismatch_collector_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?adjusted_collector);
+                            info!(action = "mismatch_collector_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?adjusted_collector);
+                            query_profile.record_cache(false);
                             adjusted_collectors.push(adjusted_collector);
                             original_collectors.push(collector);
                             missed_collector_indices.push(i)
                         }
                     }
                 } else {
-                    info!(action = "skip_querying_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?collector);
+                    info!(action = "skip_querying_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?collector);
                     adjusted_collectors.push(collector.clone());
                     original_collectors.push(collector);
                     missed_collector_indices.push(i)
@@ -503,43 +651,38 @@ impl IndexHolder {
             info!(action = "served_from_cache", index_name = ?self.index_name, query = ?parsed_query);
             return Ok(collector_outputs.into_iter().map(Option::unwrap).collect());
         }
-        let searcher = self.index_reader().searcher();
-        let mut multi_collector = MultiCollector::new();
-        let extractors: Vec<Box<dyn FruitExtractor>> = adjusted_collectors
-            .iter()
-            .map(|collector_proto| {
-                build_fruit_extractor(
-                    self,
-                    index_alias,
-                    searcher.clone(),
-                    collector_proto.clone(),
-                    &parsed_query,
-                    &mut multi_collector,
-                )
-            })
-            .collect::<SummaResult<_>>()?;
+        let (search_passes, extractors) = self.build_search_passes(index_alias, &searcher, parsed_query.as_ref(), &adjusted_collectors, &search_limits)?;
         info!(
             target: "query",
             index_name = ?self.index_name,
             parsed_query = ?parsed_query,
             is_fieldnorms_scoring_enabled = is_fieldnorms_scoring_enabled,
         );
-        let mut multi_fruit = self
-            .search_in_segments_async(&searcher, &parsed_query, &multi_collector, is_fieldnorms_scoring_enabled)
-            .await?;
+        let mut multi_fruits = try_join_all(search_passes.iter().map(|(query, multi_collector)| {
+            self.search_in_segments_async(
+                &searcher,
+                query.as_ref(),
+                multi_collector,
+                is_fieldnorms_scoring_enabled,
+                search_deadline,
+                query_profile,
+            )
+        }))
+        .await?;
         if load_cache || store_cache {
             let mut cache = self.collector_cache.lock();
-            for (((extractor, i), original_collector), adjusted_collector) in extractors
+            for ((((search_pass, extractor), i), original_collector), adjusted_collector) in extractors
                 .into_iter()
                 .zip(missed_collector_indices.into_iter())
                 .zip(original_collectors.into_iter())
                 .zip(adjusted_collectors.into_iter())
             {
-                let extracted_result = extractor.extract(&mut multi_fruit)?;
+                let extracted_result = extractor.extract(&mut multi_fruits[search_pass])?;
                 if CollectorCache::is_caching_enabled(&original_collector) {
                     let adjusted_extracted_result = CollectorCache::adjust_result(&extracted_result, &original_collector);
-                    if store_cache {
-                        info!(action = "storing_collector_to_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?adjusted_collector);
+                    // Partial results must not be served from cache later
+                    if store_cache && !search_deadline.is_exceeded() {
+                        info!(action = "storing_collector_to_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?adjusted_collector);
                         cache.put(&caching_key, &adjusted_collector, extracted_result);
                     };
                     collector_outputs[i] = Some(adjusted_extracted_result)
@@ -549,14 +692,15 @@ impl IndexHolder {
             }
             drop(cache);
         } else {
-            for (i, extractor) in extractors.into_iter().enumerate() {
-                collector_outputs[i] = Some(extractor.extract(&mut multi_fruit)?);
+            for (i, (search_pass, extractor)) in extractors.into_iter().enumerate() {
+                collector_outputs[i] = Some(extractor.extract(&mut multi_fruits[search_pass])?);
             }
         }
         Ok(collector_outputs.into_iter().map(Option::unwrap).collect())
     }
 
     /// Search `query` in the `IndexHolder` and collecting `Fruit` with a list of `collectors`
+    #[allow(clippy::too_many_arguments)]
     pub fn custom_search(
         &self,
         index_alias: &str,
@@ -565,6 +709,9 @@ impl IndexHolder {
         is_fieldnorms_scoring_enabled: Option<bool>,
         load_cache: Option<bool>,
         store_cache: Option<bool>,
+        search_limits: &SearchLimits,
+        search_deadline: &SearchDeadline,
+        query_profile: &QueryProfile,
     ) -> SummaResult<Vec<IntermediateExtractionResult>> {
         let collectors_len = collectors.len();
         let mut missed_collector_indices = Vec::with_capacity(collectors_len);
@@ -574,10 +721,22 @@ impl IndexHolder {
         let load_cache = load_cache.unwrap_or(false);
         let store_cache = store_cache.unwrap_or(false);
 
-        info!(action = "parse_query", index_name = ?self.index_name, query = ?query);
-        let parsed_query = self.query_parser.parse_query(query)?;
+        let search_limits = self.query_parser.search_limits().stricter(search_limits);
+        search_limits.check_collectors(&collectors)?;
+
+        let searcher = self.index_reader().searcher();
+        let caching_key = QueryCachingKey::new(
+            &searcher,
+            &query,
+            self.query_parser.query_parser_config(),
+            is_fieldnorms_scoring_enabled,
+            &search_limits,
+        );
 
-        let caching_key = format!("{:?}|{:?}", parsed_query, is_fieldnorms_scoring_enabled);
+        info!(action = "parse_query", index_name = ?self.index_name, query = ?query);
+        let parse_started_at = Instant::now();
+        let parsed_query = self.query_parser.parse_query_with_limits(query, &search_limits)?;
+        query_profile.record_parse(parse_started_at.elapsed());
 
         if load_cache {
             let mut cache = self.collector_cache.lock();
@@ -585,21 +744,23 @@ impl IndexHolder {
                 let is_caching_enabled = CollectorCache::is_caching_enabled(&collector);
                 if is_caching_enabled {
                     let adjusted_collector = CollectorCache::adjust_collector(&collector);
-                    info!(action = "querying_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?adjusted_collector);
+                    info!(action = "querying_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?adjusted_collector);
                     match cache.get(&caching_key, &adjusted_collector, &collector) {
                         Some(cached_value) => {
-                            info!(action = "match_collector_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?adjusted_collector);
+                            info!(action = "match_collector_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?adjusted_collector);
+                            query_profile.record_cache(true);
                             collector_outputs[i] = Some(cached_value)
                         }
                         None => {
-                            info!(action = "mismatch_collector_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?adjusted_collector);
+                            info!(action = "mismatch_collector_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?adjusted_collector);
+                            query_profile.record_cache(false);
                             adjusted_collectors.push(adjusted_collector);
                             original_collectors.push(collector);
                             missed_collector_indices.push(i)
                         }
                     }
                 } else {
-                    info!(action = "skip_querying_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?collector);
+                    info!(action = "skip_querying_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?collector);
                     adjusted_collectors.push(collector.clone());
                     original_collectors.push(collector);
                     missed_collector_indices.push(i)
@@ -623,41 +784,40 @@ impl IndexHolder {
             info!(action = "served_from_cache", index_name = ?self.index_name, query = ?parsed_query);
             return Ok(collector_outputs.into_iter().map(Option::unwrap).collect());
         }
-        let searcher = self.index_reader().searcher();
-        let mut multi_collector = MultiCollector::new();
-        let extractors: Vec<Box<dyn FruitExtractor>> = adjusted_collectors
-            .iter()
-            .map(|collector_proto| {
-                build_fruit_extractor(
-                    self,
-                    index_alias,
-                    searcher.clone(),
-                    collector_proto.clone(),
-                    &parsed_query,
-                    &mut multi_collector,
-                )
-            })
-            .collect::<SummaResult<_>>()?;
+        let (search_passes, extractors) = self.build_search_passes(index_alias, &searcher, parsed_query.as_ref(), &adjusted_collectors, &search_limits)?;
         info!(
             target: "query",
             index_name = ?self.index_name,
             parsed_query = ?parsed_query,
             is_fieldnorms_scoring_enabled = is_fieldnorms_scoring_enabled,
         );
-        let mut multi_fruit = self.search_in_segments(&searcher, &parsed_query, &multi_collector, is_fieldnorms_scoring_enabled)?;
+        let mut multi_fruits = search_passes
+            .iter()
+            .map(|(query, multi_collector)| {
+                self.search_in_segments(
+                    &searcher,
+                    query.as_ref(),
+                    multi_collector,
+                    is_fieldnorms_scoring_enabled,
+                    search_deadline,
+                    query_profile,
+                )
+            })
+            .collect::<tantivy::Result<Vec<_>>>()?;
         if load_cache || store_cache {
             let mut cache = self.collector_cache.lock();
-            for (((extractor, i), original_collector), adjusted_collector) in extractors
+            for ((((search_pass, extractor), i), original_collector), adjusted_collector) in extractors
                 .into_iter()
                 .zip(missed_collector_indices.into_iter())
                 .zip(original_collectors.into_iter())
                 .zip(adjusted_collectors.into_iter())
             {
-                let extracted_result = extractor.extract(&mut multi_fruit)?;
+                let extracted_result = extractor.extract(&mut multi_fruits[search_pass])?;
                 if CollectorCache::is_caching_enabled(&original_collector) {
                     let adjusted_extracted_result = CollectorCache::adjust_result(&extracted_result, &original_collector);
-                    if store_cache {
-                        info!(action = "storing_collector_to_cache", index_name = ?self.index_name, caching_key = caching_key, collector = ?adjusted_collector);
+                    // Partial results must not be served from cache later
+                    if store_cache && !search_deadline.is_exceeded() {
+                        info!(action = "storing_collector_to_cache", index_name = ?self.index_name, caching_key = ?caching_key, collector = ?adjusted_collector);
                         cache.put(&caching_key, &adjusted_collector, extracted_result);
                     };
                     collector_outputs[i] = Some(adjusted_extracted_result)
@@ -667,8 +827,8 @@ impl IndexHolder {
             }
             drop(cache);
         } else {
-            for (i, extractor) in extractors.into_iter().enumerate() {
-                collector_outputs[i] = Some(extractor.extract(&mut multi_fruit)?);
+            for (i, (search_pass, extractor)) in extractors.into_iter().enumerate() {
+                collector_outputs[i] = Some(extractor.extract(&mut multi_fruits[search_pass])?);
             }
         }
         Ok(collector_outputs.into_iter().map(Option::unwrap).collect())
@@ -690,27 +850,28 @@ impl IndexHolder {
     /// Index generic `SummaDocument`
     ///
     /// `IndexUpdater` bounds unbounded `SummaDocument` inside
-    pub async fn index_document(&self, document_bytes: &[u8], skip_updated_at_modification: bool) -> SummaResult<()> {
+    pub async fn index_document(&self, document_bytes: &[u8], skip_updated_at_modification: bool) -> SummaResult<IndexingOutcome> {
         let document = SummaDocument::parse_json_bytes(&self.index.schema(), document_bytes, skip_updated_at_modification)?;
         debug!(action = "acquiring_index_writer_for_read");
         self.index_writer_holder()?.read().await.index_document(document, self.conflict_strategy())
     }
 
-    /// Index multiple documents at a time
+    /// Index multiple documents at a time and return numbers of indexed, failed and skipped documents
     pub async fn index_bulk(
         &self,
         documents: &Vec<Vec<u8>>,
         conflict_strategy: Option<proto::ConflictStrategy>,
         skip_updated_at_modification: bool,
-    ) -> SummaResult<(u64, u64)> {
-        let (mut success_docs, mut failed_docs) = (0u64, 0u64);
+    ) -> SummaResult<(u64, u64, u64)> {
+        let (mut success_docs, mut failed_docs, mut skipped_docs) = (0u64, 0u64, 0u64);
         debug!(action = "acquiring_index_writer_for_read");
         let index_writer_holder = self.index_writer_holder()?.read().await;
         let conflict_strategy = conflict_strategy.unwrap_or_else(|| self.conflict_strategy());
         for document in documents {
             match SummaDocument::parse_json_bytes(&self.index.schema(), document, skip_updated_at_modification) {
                 Ok(document) => match index_writer_holder.index_document(document, conflict_strategy) {
-                    Ok(_) => success_docs += 1,
+                    Ok(IndexingOutcome::Indexed) => success_docs += 1,
+                    Ok(IndexingOutcome::Skipped) => skipped_docs += 1,
                     Err(error) => {
                         warn!(action = "error", error = ?error);
                         failed_docs += 1
@@ -722,7 +883,7 @@ impl IndexHolder {
                 }
             }
         }
-        Ok((success_docs, failed_docs))
+        Ok((success_docs, failed_docs, skipped_docs))
     }
 
     #[cfg(feature = "tokio-rt")]
@@ -820,6 +981,38 @@ impl IndexHolder {
     pub fn clear_collector_cache(&self) {
         self.collector_cache.lock().remove_expired()
     }
+
+    /// Numbers of hits and misses of the collector cache
+    pub fn collector_cache_hits_and_misses(&self) -> (u64, u64) {
+        self.collector_cache.lock().hits_and_misses()
+    }
+
+    /// Deletes documents expired according to `IndexAttributes.expiry` and returns their number
+    ///
+    /// Only committed documents are considered and deletions become visible after the next commit. Deletions are
+    /// counted by `expired_documents_count` only after `commit_expired_documents` is called for a successful commit
+    pub async fn delete_expired_documents(&self) -> SummaResult<u64> {
+        let Some(document_expiry) = &self.document_expiry else {
+            return Ok(0);
+        };
+        let expired_query = document_expiry.expired_query(crate::utils::current_time() as i64);
+        let expired_documents = expired_query.count(&self.index_reader.searcher())? as u64;
+        if expired_documents > 0 {
+            debug!(action = "acquiring_index_writer_for_read");
+            self.index_writer_holder()?.read().await.delete_by_query(expired_query)?;
+        }
+        Ok(expired_documents)
+    }
+
+    /// Counts expired documents that have been deleted by a successful commit
+    pub fn commit_expired_documents(&self, expired_documents: u64) {
+        self.expired_documents.fetch_add(expired_documents, Ordering::Relaxed);
+    }
+
+    /// Number of expired documents deleted and committed since opening the index
+    pub fn expired_documents_count(&self) -> u64 {
+        self.expired_documents.load(Ordering::Relaxed)
+    }
 }
 
 #[cfg(test)]
@@ -3,6 +3,7 @@ use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 
 use futures::future::join_all;
+use instant::Instant;
 use summa_proto::proto;
 use summa_proto::proto::collector_output::CollectorOutput;
 use summa_proto::proto::Score;
@@ -10,12 +11,12 @@ use tantivy::DocAddress;
 use tokio::sync::RwLock;
 use tracing::{debug, info, trace};
 
-use super::IndexHolder;
+use super::{IndexHolder, QueryProfile};
 use crate::components::custom_serializer::NamedFieldDocument;
-use crate::components::fruit_extractors::{IntermediateExtractionResult, ReadyCollectorOutput, ScoredDocAddress};
+use crate::components::fruit_extractors::{ExtractionTooling, IntermediateExtractionResult, ReadyCollectorOutput, ScoredDocAddress};
+use crate::components::snippet_generator::SnippetGenerator;
 use crate::configs::{ConfigProxy, DirectProxy};
 use crate::errors::{SummaResult, ValidationError};
-use crate::proto_traits::Wrapper;
 use crate::utils::sync::{Handler, OwningHandler};
 use crate::Error;
 
@@ -56,6 +57,81 @@ impl ScoredDocAddressRefWithAlias<'_> {
     }
 }
 
+/// Loads the document and builds its snippets
+async fn extract_scored_document(
+    extraction_tooling: &ExtractionTooling,
+    snippet_generator: &Option<Vec<(String, SnippetGenerator)>>,
+    scored_doc_address_ref: &ScoredDocAddressRefWithAlias<'_>,
+    position: u32,
+    query_profile: &QueryProfile,
+) -> SummaResult<proto::ScoredDocument> {
+    let doc_address = scored_doc_address_ref.doc_address();
+    let searcher = extraction_tooling.searcher.clone();
+    let fetch_started_at = Instant::now();
+    #[cfg(feature = "tokio-rt")]
+    let document = tokio::task::spawn_blocking(move || searcher.doc(doc_address)).await??;
+    #[cfg(not(feature = "tokio-rt"))]
+    let document = searcher.doc_async(doc_address).await?;
+    query_profile.record_fetch(fetch_started_at.elapsed());
+    let snippets_started_at = Instant::now();
+    let snippets = snippet_generator
+        .as_ref()
+        .map(|snippet_generator| {
+            snippet_generator
+                .iter()
+                .map(|(field_name, snippet_generator)| (field_name.to_string(), snippet_generator.snippet_from_doc(&document)))
+                .collect()
+        })
+        .unwrap_or_default();
+    query_profile.record_snippets(snippets_started_at.elapsed());
+    Ok(proto::ScoredDocument {
+        document: NamedFieldDocument::from_document(
+            extraction_tooling.searcher.schema(),
+            &extraction_tooling.query_fields,
+            &extraction_tooling.multi_fields,
+            &document,
+        )
+        .to_json_string(),
+        score: scored_doc_address_ref.score().clone(),
+        position,
+        snippets,
+        index_alias: scored_doc_address_ref.index_alias.to_string(),
+        inner_hits: vec![],
+        group_size: None,
+        normalized_score: None,
+        source_ranks: scored_doc_address_ref.scored_doc_address.source_ranks.clone(),
+    })
+}
+
+fn score_value(score: &Option<Score>) -> Option<f64> {
+    match score.as_ref()?.score.as_ref()? {
+        proto::score::Score::F64Score(score) => Some(*score),
+        proto::score::Score::U64Score(score) => Some(*score as f64),
+    }
+}
+
+/// Fills `normalized_score` of documents and their inner hits using bounds of scores of the returned page
+fn normalize_scores(scored_documents: &mut [proto::ScoredDocument], score_normalization: proto::ScoreNormalization) {
+    if score_normalization == proto::ScoreNormalization::None {
+        return;
+    }
+    let scores = scored_documents.iter().filter_map(|scored_document| score_value(&scored_document.score));
+    let (min, max) = scores.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), score| (min.min(score), max.max(score)));
+    let normalize = |score: f64| match score_normalization {
+        proto::ScoreNormalization::None => None,
+        proto::ScoreNormalization::Max => (max > 0.0).then_some(score / max),
+        proto::ScoreNormalization::MinMax if max > min => Some(((score - min) / (max - min)).max(0.0)),
+        // All documents of the page are scored equally
+        proto::ScoreNormalization::MinMax => Some(1.0),
+    };
+    for scored_document in scored_documents {
+        scored_document.normalized_score = score_value(&scored_document.score).and_then(&normalize);
+        for inner_hit in &mut scored_document.inner_hits {
+            inner_hit.normalized_score = score_value(&inner_hit.score).and_then(&normalize);
+        }
+    }
+}
+
 impl IndexRegistry {
     pub fn new(core_config: &Arc<dyn ConfigProxy<crate::configs::core::Config>>) -> IndexRegistry {
         IndexRegistry {
@@ -127,8 +203,12 @@ impl IndexRegistry {
         self.index_holders().write().await.remove(index_name);
     }
 
-    /// Merges several `IntermediateExtractionResult`
-    pub async fn finalize_extraction(&self, ie_results: Vec<IntermediateExtractionResult>) -> SummaResult<Vec<proto::CollectorOutput>> {
+    /// Merges several `IntermediateExtractionResult`, fetching documents and generating snippets are timed in `query_profile`
+    pub async fn finalize_extraction(
+        &self,
+        ie_results: Vec<IntermediateExtractionResult>,
+        query_profile: &QueryProfile,
+    ) -> SummaResult<Vec<proto::CollectorOutput>> {
         if ie_results.is_empty() {
             return Ok(vec![]);
         }
@@ -140,16 +220,27 @@ impl IndexRegistry {
                     IntermediateExtractionResult::Ready(ReadyCollectorOutput::Aggregation(aggregation_collector_output)) => {
                         CollectorOutput::Aggregation(aggregation_collector_output)
                     }
+                    IntermediateExtractionResult::Ready(ReadyCollectorOutput::TypedAggregation(typed_aggregation_collector_output)) => {
+                        CollectorOutput::TypedAggregation(typed_aggregation_collector_output)
+                    }
+                    IntermediateExtractionResult::Ready(ReadyCollectorOutput::Cardinality(cardinality_collector_output)) => {
+                        CollectorOutput::Cardinality(cardinality_collector_output)
+                    }
                     IntermediateExtractionResult::Ready(ReadyCollectorOutput::Count(count_collector_output)) => CollectorOutput::Count(count_collector_output),
                     IntermediateExtractionResult::Ready(ReadyCollectorOutput::Facet(facet_collector_output)) => CollectorOutput::Facet(facet_collector_output),
+                    IntermediateExtractionResult::Ready(ReadyCollectorOutput::SignificantTerms(significant_terms_collector_output)) => {
+                        CollectorOutput::SignificantTerms(significant_terms_collector_output)
+                    }
                     IntermediateExtractionResult::PreparedDocumentReferences(prepared_document_references) => {
                         trace!(action = "prepared_documents_finalization");
                         let extraction_tooling = &prepared_document_references.extraction_tooling;
+                        let snippets_started_at = Instant::now();
                         let snippet_generator = if let Some(snippet_generator_config) = prepared_document_references.snippet_generator_config {
-                            Some(snippet_generator_config.as_tantivy_async().await)
+                            Some(snippet_generator_config.as_generators_async().await?)
                         } else {
                             None
                         };
+                        query_profile.record_snippets(snippets_started_at.elapsed());
 
                         let scored_doc_address_refs =
                             prepared_document_references
@@ -159,51 +250,46 @@ impl IndexRegistry {
                                     index_alias: prepared_document_references.index_alias.as_str(),
                                     scored_doc_address,
                                 });
-                        let extraction_tooling_ref = &extraction_tooling;
                         let snippet_generator_ref = &snippet_generator;
 
-                        let scored_documents = join_all(scored_doc_address_refs.into_iter().enumerate().map(|(position, scored_doc_address_ref)| {
-                            let doc_address = scored_doc_address_ref.doc_address();
-                            let searcher = extraction_tooling_ref.searcher.clone();
-                            async move {
-                                #[cfg(feature = "tokio-rt")]
-                                let document = tokio::task::spawn_blocking(move || searcher.doc(doc_address)).await??;
-                                #[cfg(not(feature = "tokio-rt"))]
-                                let document = searcher.doc_async(doc_address).await?;
-                                Ok(proto::ScoredDocument {
-                                    document: NamedFieldDocument::from_document(
-                                        extraction_tooling.searcher.schema(),
-                                        &extraction_tooling.query_fields,
-                                        &extraction_tooling.multi_fields,
-                                        &document,
-                                    )
-                                    .to_json_string(),
-                                    score: scored_doc_address_ref.score().clone(),
-                                    position: position as u32,
-                                    snippets: snippet_generator_ref
-                                        .as_ref()
-                                        .map(|snippet_generator_ref| {
-                                            snippet_generator_ref
-                                                .iter()
-                                                .map(|(field_name, snippet_generator)| {
-                                                    (
-                                                        field_name.to_string(),
-                                                        Wrapper::from(snippet_generator.snippet_from_doc(&document)).into_inner(),
-                                                    )
-                                                })
-                                                .collect()
-                                        })
-                                        .unwrap_or_default(),
-                                    index_alias: scored_doc_address_ref.index_alias.to_string(),
-                                })
-                            }
-                        }))
+                        let mut scored_documents = join_all(scored_doc_address_refs.into_iter().enumerate().map(
+                            |(position, scored_doc_address_ref)| async move {
+                                let mut scored_document = extract_scored_document(
+                                    extraction_tooling,
+                                    snippet_generator_ref,
+                                    &scored_doc_address_ref,
+                                    position as u32,
+                                    query_profile,
+                                )
+                                .await?;
+                                for (inner_position, inner_hit) in scored_doc_address_ref.scored_doc_address.inner_hits.iter().enumerate() {
+                                    let inner_hit_ref = ScoredDocAddressRefWithAlias {
+                                        index_alias: scored_doc_address_ref.index_alias,
+                                        scored_doc_address: inner_hit,
+                                    };
+                                    scored_document.inner_hits.push(
+                                        extract_scored_document(
+                                            extraction_tooling,
+                                            snippet_generator_ref,
+                                            &inner_hit_ref,
+                                            inner_position as u32,
+                                            query_profile,
+                                        )
+                                        .await?,
+                                    );
+                                }
+                                scored_document.group_size = scored_doc_address_ref.scored_doc_address.group_size;
+                                Ok(scored_document)
+                            },
+                        ))
                         .await
                         .into_iter()
                         .collect::<SummaResult<Vec<_>>>()?;
+                        normalize_scores(&mut scored_documents, prepared_document_references.score_normalization);
                         CollectorOutput::Documents(proto::DocumentsCollectorOutput {
                             has_next: prepared_document_references.has_next,
                             scored_documents,
+                            groups_count: prepared_document_references.groups_count,
                         })
                     }
                 }),
@@ -2,9 +2,9 @@ use std::collections::hash_map::RandomState;
 use std::collections::HashSet;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
 
-use chrono::{DateTime, Datelike};
-use rand::RngCore;
+use parking_lot::Mutex;
 use summa_proto::proto;
 use tantivy::index::SegmentId;
 use tantivy::merge_policy::MergePolicy;
@@ -12,12 +12,18 @@ use tantivy::query::Query;
 use tantivy::schema::document::ReferenceValueLeaf;
 use tantivy::schema::document::{CompactDocObjectIter, CompactDocValue, ReferenceValue};
 use tantivy::schema::{Field, FieldType, OwnedValue, Value};
-use tantivy::{Directory, Document, Index, IndexWriter, Opstamp, SegmentMeta, SingleSegmentIndexWriter, TantivyDocument, Term};
-use tracing::info;
-
+use tantivy::{Directory, Document, Index, IndexWriter, Opstamp, SegmentMeta, SegmentReader, SingleSegmentIndexWriter, TantivyDocument, Term};
+use tokio::sync::mpsc::Sender;
+use tracing::{info, warn};
+
+use super::auto_id::AutoId;
+use super::document_versions::{DocumentVersions, IndexingOutcome};
+use super::ingest_pipeline::IngestPipeline;
+use super::vectors::{hnsw_graph_path, read_vector, HnswGraph, DEFAULT_EF_CONSTRUCTION, DEFAULT_MAX_CONNECTIONS};
 use super::SummaSegmentAttributes;
 use crate::configs::core::WriterThreads;
 use crate::errors::{SummaResult, ValidationError};
+use crate::scorers::ComputedField;
 use crate::Error;
 
 fn extract_flatten<'a, T: AsRef<str>>(v: CompactDocValue<'a>, parts: &[T], buffer: &mut Vec<OwnedValue>) {
@@ -60,13 +66,6 @@ fn extract_flatten_from_map<'a, T: AsRef<str>>(m: CompactDocObjectIter<'a>, part
     }
 }
 
-#[inline]
-fn generate_id() -> String {
-    let mut data = [0u8; 16];
-    rand::thread_rng().fill_bytes(&mut data);
-    base36::encode(&data)
-}
-
 /// Wrap `tantivy::SingleSegmentIndexWriter` and allows to recreate it
 pub struct SingleIndexWriter {
     pub index_writer: RwLock<SingleSegmentIndexWriter>,
@@ -172,6 +171,78 @@ impl IndexWriterImpl {
     }
 }
 
+/// Bytes fast field storing vectors of fixed dimensions
+pub(super) struct VectorFieldConfig {
+    field: Field,
+    field_name: String,
+    dimensions: usize,
+    metric: proto::VectorMetric,
+    hnsw: Option<proto::HnswConfig>,
+}
+
+impl VectorFieldConfig {
+    fn build_hnsw_graph(&self, segment_reader: &SegmentReader, hnsw: &proto::HnswConfig) -> SummaResult<HnswGraph> {
+        let mut vectors = vec![];
+        if let Some(column) = segment_reader.fast_fields().bytes(&self.field_name)? {
+            let mut buffer = vec![];
+            for doc in segment_reader.doc_ids_alive() {
+                match read_vector(&column, doc, &mut buffer)? {
+                    Some(vector) if vector.len() == self.dimensions => vectors.push((doc, vector)),
+                    _ => {}
+                }
+            }
+        }
+        let max_connections = match hnsw.max_connections {
+            0 => DEFAULT_MAX_CONNECTIONS,
+            max_connections => max_connections as usize,
+        };
+        let ef_construction = match hnsw.ef_construction {
+            0 => DEFAULT_EF_CONSTRUCTION,
+            ef_construction => ef_construction as usize,
+        };
+        Ok(HnswGraph::build(vectors, self.metric, max_connections, ef_construction))
+    }
+}
+
+/// Builds HNSW graphs of vector fields for searchable segments that have no graphs yet and removes graphs of segments
+/// that are not searchable anymore
+///
+/// Graphs are stored next to segment files and are never changed, as well as segments themselves. They are written
+/// to the underlying directory because Tantivy garbage collects every managed file that is not a part of segments.
+/// `graph_segments` keeps segments that were searchable during the previous building and it is locked during the whole building
+fn build_hnsw_graphs(index: &Index, vector_fields: &[VectorFieldConfig], graph_segments: &Mutex<HashSet<SegmentId>>) -> SummaResult<()> {
+    let mut graph_segments = graph_segments.lock();
+    let directory = index
+        .directory()
+        .underlying_directory()
+        .expect("managed directory should contain nested directory");
+    let segments = index.searchable_segments()?;
+    for segment in &segments {
+        for vector_field in vector_fields {
+            let Some(hnsw) = &vector_field.hnsw else {
+                continue;
+            };
+            let path = hnsw_graph_path(segment.id(), &vector_field.field_name);
+            if directory.exists(&path).map_err(|e| Error::Tantivy(e.into()))? {
+                continue;
+            }
+            let graph = vector_field.build_hnsw_graph(&SegmentReader::open(segment)?, hnsw)?;
+            directory.atomic_write(&path, &graph.to_bytes())?;
+            info!(action = "built_hnsw_graph", segment_id = ?segment.id(), field = ?vector_field.field_name);
+        }
+    }
+    let segment_ids = segments.iter().map(|segment| segment.id()).collect::<HashSet<_>>();
+    for segment_id in graph_segments.difference(&segment_ids) {
+        for vector_field in vector_fields.iter().filter(|vector_field| vector_field.hnsw.is_some()) {
+            if let Err(error) = directory.delete(&hnsw_graph_path(*segment_id, &vector_field.field_name)) {
+                info!(action = "skip_removing_hnsw_graph", segment_id = ?segment_id, error = ?error);
+            }
+        }
+    }
+    *graph_segments = segment_ids;
+    Ok(())
+}
+
 /// Managing write operations to index
 pub struct IndexWriterHolder {
     index_writer: IndexWriterImpl,
@@ -179,9 +250,15 @@ pub struct IndexWriterHolder {
     unique_fields: Vec<Field>,
     writer_threads: WriterThreads,
     writer_heap_size_bytes: usize,
-    auto_id_field: Option<Field>,
-    extra_year_field: Option<(Field, Field)>,
+    auto_id: Option<AutoId>,
+    ingest_pipeline: IngestPipeline,
+    computed_fields: Vec<ComputedField>,
     mapped_fields: Vec<((Field, Vec<String>), Field)>,
+    vector_fields: Arc<Vec<VectorFieldConfig>>,
+    document_versions: Option<DocumentVersions>,
+    mirror: Mutex<Option<Sender<TantivyDocument>>>,
+    hnsw_graphs_builder: Option<JoinHandle<()>>,
+    hnsw_graph_segments: Arc<Mutex<HashSet<SegmentId>>>,
 }
 
 impl IndexWriterHolder {
@@ -190,30 +267,36 @@ impl IndexWriterHolder {
     /// `IndexWriterHolder` maintains invariant that the only document with the particular primary key exists in the index.
     /// It is reached by deletion of every document with the same primary key as indexing one.
     /// The type of primary key is restricted to I64 but it is subjected to be changed in the future.
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         index_writer: IndexWriterImpl,
         merge_policy: Arc<dyn MergePolicy>,
         unique_fields: Vec<Field>,
-        auto_id_field: Option<Field>,
+        auto_id: Option<AutoId>,
+        ingest_pipeline: IngestPipeline,
+        computed_fields: Vec<ComputedField>,
         mapped_fields: Vec<((Field, Vec<String>), Field)>,
+        vector_fields: Vec<VectorFieldConfig>,
+        document_versions: Option<DocumentVersions>,
         writer_threads: WriterThreads,
         writer_heap_size_bytes: usize,
     ) -> SummaResult<IndexWriterHolder> {
-        let schema = index_writer.index().schema();
-        let extra_year_field = if let (Ok(extra_field), Ok(issued_at_field)) = (schema.get_field("extra"), schema.get_field("issued_at")) {
-            Some((extra_field, issued_at_field))
-        } else {
-            None
-        };
+        let hnsw_graph_segments = Arc::new(Mutex::new(index_writer.index().searchable_segment_ids()?.into_iter().collect()));
         Ok(IndexWriterHolder {
             index_writer,
             merge_policy,
             unique_fields,
-            auto_id_field,
+            auto_id,
             writer_threads,
             writer_heap_size_bytes,
-            extra_year_field,
+            ingest_pipeline,
+            computed_fields,
             mapped_fields,
+            vector_fields: Arc::new(vector_fields),
+            document_versions,
+            mirror: Mutex::new(None),
+            hnsw_graphs_builder: None,
+            hnsw_graph_segments,
         })
     }
 
@@ -264,33 +347,86 @@ impl IndexWriterHolder {
             })
             .transpose()?
             .unwrap_or_default();
-        let auto_id_field = metas
+        let auto_id = metas
             .index_attributes()?
-            .and_then(|attributes: proto::IndexAttributes| {
-                attributes.auto_id_field.map(|auto_id_field| {
-                    schema
-                        .get_field(&auto_id_field)
-                        .or_else(|_| Err(ValidationError::MissingField(auto_id_field.to_string())))
-                })
+            .map(|attributes: proto::IndexAttributes| AutoId::new(&schema, &attributes))
+            .transpose()?
+            .flatten();
+        let vector_fields = metas
+            .index_attributes()?
+            .map(|attributes: proto::IndexAttributes| {
+                attributes
+                    .vector_fields
+                    .iter()
+                    .map(|vector_field| {
+                        let field = schema
+                            .get_field(&vector_field.field)
+                            .map_err(|_| ValidationError::MissingField(vector_field.field.to_string()))?;
+                        let field_entry = schema.get_field_entry(field);
+                        if !matches!(field_entry.field_type(), FieldType::Bytes(_)) || !field_entry.is_fast() {
+                            return Err(ValidationError::InvalidSchema(format!(
+                                "vector field `{}` must be fast bytes field",
+                                vector_field.field
+                            )));
+                        }
+                        if vector_field.dimensions == 0 {
+                            return Err(ValidationError::InvalidSchema(format!(
+                                "vector field `{}` must have dimensions",
+                                vector_field.field
+                            )));
+                        }
+                        Ok(VectorFieldConfig {
+                            field,
+                            field_name: vector_field.field.clone(),
+                            dimensions: vector_field.dimensions as usize,
+                            metric: vector_field.metric(),
+                            hnsw: vector_field.hnsw.clone(),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let ingest_pipeline = IngestPipeline::new(
+            &schema,
+            &metas
+                .index_attributes()?
+                .map(|attributes: proto::IndexAttributes| attributes.ingest_pipeline)
+                .unwrap_or_default(),
+        )?;
+        let computed_fields = metas
+            .index_attributes()?
+            .map(|attributes: proto::IndexAttributes| {
+                attributes
+                    .computed_fields
+                    .iter()
+                    .map(|computed_field| ComputedField::new(&schema, &computed_field.field, &computed_field.eval_expr))
+                    .collect::<SummaResult<Vec<_>>>()
             })
-            .transpose()?;
+            .transpose()?
+            .unwrap_or_default();
+        let document_versions = metas
+            .index_attributes()?
+            .map(|attributes: proto::IndexAttributes| DocumentVersions::new(index, &attributes))
+            .transpose()?
+            .flatten();
         IndexWriterHolder::new(
             index_writer,
             merge_policy,
             unique_fields,
-            auto_id_field,
+            auto_id,
+            ingest_pipeline,
+            computed_fields,
             mapped_fields,
+            vector_fields,
+            document_versions,
             writer_threads,
             writer_heap_size_bytes,
         )
     }
 
-    /// Delete index by its unique fields
-    pub(super) fn resolve_conflicts(&self, document: &TantivyDocument, conflict_strategy: proto::ConflictStrategy) -> SummaResult<Option<u64>> {
-        if self.unique_fields.is_empty() || matches!(conflict_strategy, proto::ConflictStrategy::DoNothing) {
-            return Ok(None);
-        }
-
+    /// Terms of unique fields of the document
+    fn unique_terms(&self, document: &TantivyDocument) -> SummaResult<Vec<Term>> {
         let unique_terms: Vec<Term> = self
             .unique_fields
             .iter()
@@ -321,7 +457,16 @@ impl IndexWriterHolder {
                 document.to_named_doc(&self.index_writer.index().schema()),
             )))?
         }
+        Ok(unique_terms)
+    }
+
+    /// Delete index by its unique fields
+    pub(super) fn resolve_conflicts(&self, document: &TantivyDocument, conflict_strategy: proto::ConflictStrategy) -> SummaResult<Option<u64>> {
+        if self.unique_fields.is_empty() || matches!(conflict_strategy, proto::ConflictStrategy::DoNothing) {
+            return Ok(None);
+        }
 
+        let unique_terms = self.unique_terms(document)?;
         let mut last_opstamp = None;
         for term in unique_terms {
             last_opstamp = Some(self.delete_by_term(term))
@@ -347,15 +492,7 @@ impl IndexWriterHolder {
 
     #[inline]
     fn process_dynamic_fields(&self, document: &mut TantivyDocument) -> SummaResult<()> {
-        if let Some((extra_field, issued_at_field)) = self.extra_year_field {
-            if let Some(issued_at_value) = document.get_first(issued_at_field) {
-                if let Some(issued_at_value) = issued_at_value.as_i64() {
-                    if let Some(correct_timestamp) = DateTime::from_timestamp(issued_at_value, 0) {
-                        document.add_text(extra_field, correct_timestamp.year().to_string())
-                    }
-                }
-            }
-        }
+        self.ingest_pipeline.process(document)?;
         let mut buffer = vec![];
         for ((source_field, source_full_path), target_field) in &self.mapped_fields {
             for value in document.get_all(*source_field) {
@@ -370,35 +507,142 @@ impl IndexWriterHolder {
             }
             buffer.clear();
         }
+        self.compute_fields(document)
+    }
+
+    /// Replaces values of computed fields, so updated documents always get values computed from their new fields
+    fn compute_fields(&self, document: &mut TantivyDocument) -> SummaResult<()> {
+        if self.computed_fields.is_empty() {
+            return Ok(());
+        }
+        let computed_values = self
+            .computed_fields
+            .iter()
+            .map(|computed_field| Ok((computed_field.field(), computed_field.compute(document)?)))
+            .collect::<SummaResult<Vec<_>>>()?;
+        let mut computed_document = TantivyDocument::default();
+        for (field, values) in document.get_sorted_field_values() {
+            if computed_values.iter().all(|(computed_field, _)| *computed_field != field) {
+                for value in values {
+                    computed_document.add_field_value(field, &OwnedValue::from(value.as_value()))
+                }
+            }
+        }
+        for (field, value) in computed_values {
+            if let Some(value) = value {
+                computed_document.add_field_value(field, &value)
+            }
+        }
+        *document = computed_document;
         Ok(())
     }
     #[inline]
     fn setup_id_field(&self, document: &mut TantivyDocument) -> SummaResult<()> {
-        if let Some(auto_id_field) = &self.auto_id_field {
-            let schema = self.index_writer.index().schema();
-            match schema.get_field_entry(*auto_id_field).field_type() {
-                FieldType::Str(_) => match document.get_first(*auto_id_field) {
-                    Some(_) => {}
-                    None => document.add_text(*auto_id_field, generate_id()),
-                },
-                _ => unreachable!(),
+        match &self.auto_id {
+            Some(auto_id) => auto_id.setup(document),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn validate_vector_fields(&self, document: &TantivyDocument) -> SummaResult<()> {
+        for vector_field in self.vector_fields.iter() {
+            for value in document.get_all(vector_field.field) {
+                if value.as_bytes().map(|bytes| bytes.len()) != Some(4 * vector_field.dimensions) {
+                    return Err(
+                        ValidationError::InvalidVector(format!("`{}` must have {} dimensions", vector_field.field_name, vector_field.dimensions)).into(),
+                    );
+                }
             }
         }
         Ok(())
     }
 
+    fn build_hnsw_graphs(&self) -> SummaResult<()> {
+        build_hnsw_graphs(self.index(), &self.vector_fields, &self.hnsw_graph_segments)
+    }
+
+    /// Starts building HNSW graphs of committed segments in a background thread
+    ///
+    /// Segments are searched by the exact scan until their graphs are built. If the previous building is still running
+    /// then new segments are left for the next commit
+    fn spawn_hnsw_graphs_building(&mut self) {
+        if self.vector_fields.iter().all(|vector_field| vector_field.hnsw.is_none())
+            || self.hnsw_graphs_builder.as_ref().is_some_and(|builder| !builder.is_finished())
+        {
+            return;
+        }
+        let index = self.index().clone();
+        let vector_fields = self.vector_fields.clone();
+        let hnsw_graph_segments = self.hnsw_graph_segments.clone();
+        self.hnsw_graphs_builder = Some(std::thread::spawn(move || {
+            if let Err(error) = build_hnsw_graphs(&index, &vector_fields, &hnsw_graph_segments) {
+                warn!(action = "failed_building_hnsw_graphs", error = ?error);
+            }
+        }));
+    }
+
+    fn wait_hnsw_graphs_building(&mut self) {
+        if let Some(builder) = self.hnsw_graphs_builder.take() {
+            if builder.join().is_err() {
+                warn!(action = "hnsw_graphs_builder_panicked");
+            }
+        }
+    }
+
     /// Put document to the index. Before comes searchable it must be committed
-    pub fn index_document(&self, mut document: TantivyDocument, conflict_strategy: proto::ConflictStrategy) -> SummaResult<()> {
+    pub fn index_document(&self, mut document: TantivyDocument, conflict_strategy: proto::ConflictStrategy) -> SummaResult<IndexingOutcome> {
         self.process_dynamic_fields(&mut document)?;
+        self.validate_vector_fields(&document)?;
         self.setup_id_field(&mut document)?;
+        match &self.document_versions {
+            Some(document_versions) => {
+                let version = document_versions.version(&document)?;
+                let unique_terms = self.unique_terms(&document)?;
+                document_versions.write_if_newer(version, &unique_terms, || self.write_document(document, conflict_strategy))
+            }
+            None => {
+                self.write_document(document, conflict_strategy)?;
+                Ok(IndexingOutcome::Indexed)
+            }
+        }
+    }
+
+    fn write_document(&self, document: TantivyDocument, conflict_strategy: proto::ConflictStrategy) -> SummaResult<()> {
         self.resolve_conflicts(&document, conflict_strategy)?;
+        self.mirror_document(&document);
         self.index_writer.add_document(document)?;
         Ok(())
     }
 
+    /// Sends a copy of the document to the mirror. Mirroring stops if the channel is full, so writes are never blocked by a lagging receiver
+    fn mirror_document(&self, document: &TantivyDocument) {
+        let mut mirror = self.mirror.lock();
+        if let Some(sender) = mirror.as_ref() {
+            if let Err(error) = sender.try_send(document.clone()) {
+                warn!(action = "mirror_closed", error = %error);
+                *mirror = None;
+            }
+        }
+    }
+
+    /// Sets the channel receiving copies of all documents indexed through `IndexWriterHolder::index_document`
+    ///
+    /// Used by reindexing for catching up writes that land in the source index during copying. Passing `None` stops mirroring
+    /// and closes the previous channel.
+    pub fn set_mirror(&mut self, mirror: Option<Sender<TantivyDocument>>) {
+        *self.mirror.get_mut() = mirror;
+    }
+
+    /// Returns `false` if mirroring has been stopped because of the full channel or has never been started
+    pub fn is_mirrored(&self) -> bool {
+        self.mirror.lock().is_some()
+    }
+
     /// Merge segments into one.
     ///
     /// Also cleans deleted documents and do recompression. Possible to pass the only segment in `segment_ids` to do recompression or clean up.
+    /// HNSW graphs of merged segments are replaced by the graph of the new segment.
     /// It is heavy operation that also blocks on `.await` so should be spawned if non-blocking behaviour is required
     pub fn merge(&self, segment_ids: &[SegmentId], segment_attributes: Option<SummaSegmentAttributes>) -> SummaResult<Option<SegmentMeta>> {
         info!(action = "merge_segments", segment_ids = ?segment_ids);
@@ -407,6 +651,7 @@ impl IndexWriterHolder {
             segment_attributes.map(|segment_attributes| serde_json::to_value(segment_attributes).expect("cannot serialize")),
         )?;
         info!(action = "merged_segments", segment_ids = ?segment_ids, merged_segment_meta = ?segment_meta);
+        self.build_hnsw_graphs()?;
         Ok(segment_meta)
     }
 
@@ -415,11 +660,22 @@ impl IndexWriterHolder {
     /// Committing makes indexed documents visible
     /// It is heavy operation that also blocks on `.await` so should be spawned if non-blocking behaviour is required
     pub fn commit(&mut self) -> SummaResult<Opstamp> {
-        self.index_writer.commit()
+        let opstamp = self.index_writer.commit()?;
+        self.reload_document_versions()?;
+        self.spawn_hnsw_graphs_building();
+        Ok(opstamp)
     }
 
     pub fn rollback(&mut self) -> SummaResult<()> {
-        self.index_writer.rollback()
+        self.index_writer.rollback()?;
+        self.reload_document_versions()
+    }
+
+    fn reload_document_versions(&self) -> SummaResult<()> {
+        match &self.document_versions {
+            Some(document_versions) => document_versions.reload(),
+            None => Ok(()),
+        }
     }
 
     pub fn vacuum(&self, segment_attributes: Option<SummaSegmentAttributes>, excluded_segments: Vec<String>) -> SummaResult<()> {
@@ -475,6 +731,9 @@ impl IndexWriterHolder {
     pub fn commit_and_prepare(&mut self, with_hotcache: bool) -> SummaResult<Opstamp> {
         let opstamp = self.commit()?;
         self.wait_merging_threads();
+        // Prepared index must have graphs for all segments including ones produced by background merges
+        self.wait_hnsw_graphs_building();
+        self.build_hnsw_graphs()?;
 
         if with_hotcache {
             let directory = self.index().directory();
@@ -489,3 +748,67 @@ impl IndexWriterHolder {
         Ok(opstamp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use summa_proto::proto;
+    use tantivy::schema::{SchemaBuilder, FAST};
+    use tantivy::{doc, Directory, IndexBuilder};
+
+    use crate::components::test_utils::create_test_index_writer_holder;
+    use crate::components::vectors::{encode_vector, hnsw_graph_path};
+
+    #[test]
+    fn test_hnsw_graphs_lifecycle() {
+        let mut schema_builder = SchemaBuilder::new();
+        let embedding = schema_builder.add_bytes_field("embedding", FAST);
+        let index = IndexBuilder::new()
+            .schema(schema_builder.build())
+            .index_attributes(proto::IndexAttributes {
+                vector_fields: vec![proto::VectorField {
+                    field: "embedding".to_string(),
+                    dimensions: 2,
+                    metric: proto::VectorMetric::Cosine.into(),
+                    hnsw: Some(proto::HnswConfig::default()),
+                }],
+                ..Default::default()
+            })
+            .create_in_ram()
+            .unwrap();
+        let mut index_writer_holder = create_test_index_writer_holder(&index);
+        let conflict_strategy = proto::ConflictStrategy::OverwriteAlways;
+        let has_graph = |segment_id| index.directory().exists(&hnsw_graph_path(segment_id, "embedding")).unwrap();
+
+        for vector in [[1.0, 0.0], [0.0, 1.0]] {
+            index_writer_holder
+                .index_document(doc!(embedding => encode_vector(&vector)), conflict_strategy)
+                .unwrap();
+        }
+        index_writer_holder.commit_and_prepare(false).unwrap();
+        let first_segment_ids = index.searchable_segment_ids().unwrap();
+        assert!(first_segment_ids.iter().all(|segment_id| has_graph(*segment_id)));
+
+        index_writer_holder
+            .index_document(doc!(embedding => encode_vector(&[1.0, 1.0])), conflict_strategy)
+            .unwrap();
+        index_writer_holder.commit_and_prepare(false).unwrap();
+        // Repeats garbage collection of the commit, as missing graphs are rebuilt by `commit_and_prepare`
+        let living_files = index
+            .searchable_segment_metas()
+            .unwrap()
+            .iter()
+            .flat_map(|segment_meta| segment_meta.list_files())
+            .chain([PathBuf::from("meta.json")])
+            .collect();
+        index.clone().directory_mut().garbage_collect(|| living_files).unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        assert_eq!(segment_ids.len(), 2);
+        assert!(segment_ids.iter().all(|segment_id| has_graph(*segment_id)));
+
+        let merged_segment_id = index_writer_holder.merge(&segment_ids, None).unwrap().unwrap().id();
+        assert!(has_graph(merged_segment_id));
+        assert!(segment_ids.iter().all(|segment_id| !has_graph(*segment_id)));
+    }
+}
@@ -0,0 +1,506 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use summa_proto::proto;
+use tantivy::schema::{Field, OwnedValue, Schema, Type, Value};
+use tantivy::{DateTime, Document, TantivyDocument};
+
+use super::summa_document::{value_from_json, ValueParsingError};
+use super::DocumentParsingError;
+use crate::errors::{SummaResult, ValidationError};
+
+enum Processor {
+    Rename {
+        source_field: Field,
+        target_field: Field,
+    },
+    Copy {
+        source_field: Field,
+        target_field: Field,
+    },
+    Drop {
+        fields: Vec<Field>,
+    },
+    Lowercase {
+        field: Field,
+    },
+    Trim {
+        field: Field,
+    },
+    RegexExtract {
+        source_field: Field,
+        target_field: Field,
+        regex: Regex,
+        group: usize,
+    },
+    Split {
+        field: Field,
+        separator: String,
+    },
+    DateParse {
+        source_field: Field,
+        source_field_name: String,
+        target_field: Field,
+        target_type: Type,
+        format: String,
+    },
+    HtmlStrip {
+        field: Field,
+    },
+    DefaultValue {
+        field: Field,
+        value: OwnedValue,
+    },
+    SetYear {
+        source_field: Field,
+        target_field: Field,
+        target_type: Type,
+    },
+}
+
+/// Ordered processors transforming documents before indexing
+///
+/// Processors are validated against the schema once, so processing a document fails only on values that cannot be parsed.
+#[derive(Default)]
+pub struct IngestPipeline {
+    processors: Vec<Processor>,
+}
+
+impl IngestPipeline {
+    /// Creates `IngestPipeline` from `IndexAttributes.ingest_pipeline`
+    ///
+    /// Empty `processors` keep the behaviour of indices created before ingest pipelines: the year of `issued_at`
+    /// is appended to `extra` if the schema has both fields.
+    pub fn new(schema: &Schema, processors: &[proto::IngestProcessor]) -> SummaResult<IngestPipeline> {
+        if processors.is_empty() {
+            return Ok(IngestPipeline::legacy(schema));
+        }
+        Ok(IngestPipeline {
+            processors: processors
+                .iter()
+                .map(|processor| Processor::from_proto(schema, processor))
+                .collect::<SummaResult<_>>()?,
+        })
+    }
+
+    fn legacy(schema: &Schema) -> IngestPipeline {
+        let processors = match (schema.get_field("extra"), schema.get_field("issued_at")) {
+            (Ok(extra_field), Ok(issued_at_field)) => vec![Processor::SetYear {
+                source_field: issued_at_field,
+                target_field: extra_field,
+                target_type: Type::Str,
+            }],
+            _ => vec![],
+        };
+        IngestPipeline { processors }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    /// Applies all processors to `document` in order
+    pub fn process(&self, document: &mut TantivyDocument) -> SummaResult<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let mut field_values: BTreeMap<Field, Vec<OwnedValue>> = document
+            .get_sorted_field_values()
+            .into_iter()
+            .map(|(field, values)| (field, values.into_iter().map(|value| OwnedValue::from(value.as_value())).collect()))
+            .collect();
+        for processor in &self.processors {
+            processor.apply(&mut field_values)?;
+        }
+        let mut processed_document = TantivyDocument::default();
+        for (field, values) in &field_values {
+            for value in values {
+                processed_document.add_field_value(*field, value)
+            }
+        }
+        *document = processed_document;
+        Ok(())
+    }
+}
+
+impl Processor {
+    fn from_proto(schema: &Schema, processor: &proto::IngestProcessor) -> SummaResult<Processor> {
+        use proto::ingest_processor::Processor as ProtoProcessor;
+        Ok(match &processor.processor {
+            Some(ProtoProcessor::Rename(proto::RenameProcessor { source_field, target_field })) => {
+                let (source_field, target_field) = get_same_typed_fields(schema, source_field, target_field)?;
+                Processor::Rename { source_field, target_field }
+            }
+            Some(ProtoProcessor::Copy(proto::CopyProcessor { source_field, target_field })) => {
+                let (source_field, target_field) = get_same_typed_fields(schema, source_field, target_field)?;
+                Processor::Copy { source_field, target_field }
+            }
+            Some(ProtoProcessor::Drop(proto::DropProcessor { fields })) => Processor::Drop {
+                fields: fields.iter().map(|field| get_field(schema, field, &[])).collect::<SummaResult<_>>()?,
+            },
+            Some(ProtoProcessor::Lowercase(proto::LowercaseProcessor { field })) => Processor::Lowercase {
+                field: get_field(schema, field, &[Type::Str])?,
+            },
+            Some(ProtoProcessor::Trim(proto::TrimProcessor { field })) => Processor::Trim {
+                field: get_field(schema, field, &[Type::Str])?,
+            },
+            Some(ProtoProcessor::RegexExtract(proto::RegexExtractProcessor {
+                source_field,
+                target_field,
+                pattern,
+                group,
+            })) => {
+                let regex = Regex::new(pattern).map_err(|error| ValidationError::InvalidIngestProcessor(format!("invalid pattern `{pattern}`: {error}")))?;
+                let group = *group as usize;
+                if group >= regex.captures_len() {
+                    return Err(ValidationError::InvalidIngestProcessor(format!("pattern `{pattern}` has no group {group}")).into());
+                }
+                Processor::RegexExtract {
+                    source_field: get_field(schema, source_field, &[Type::Str])?,
+                    target_field: get_field(schema, target_field, &[Type::Str])?,
+                    regex,
+                    group,
+                }
+            }
+            Some(ProtoProcessor::Split(proto::SplitProcessor { field, separator })) => {
+                if separator.is_empty() {
+                    return Err(ValidationError::EmptyArgument("separator".to_owned()).into());
+                }
+                Processor::Split {
+                    field: get_field(schema, field, &[Type::Str])?,
+                    separator: separator.clone(),
+                }
+            }
+            Some(ProtoProcessor::DateParse(proto::DateParseProcessor {
+                source_field,
+                target_field,
+                format,
+            })) => {
+                let target_field_name = target_field;
+                let target_field = get_field(schema, target_field_name, &[Type::Date, Type::I64])?;
+                Processor::DateParse {
+                    source_field: get_field(schema, source_field, &[Type::Str])?,
+                    source_field_name: source_field.clone(),
+                    target_field,
+                    target_type: schema.get_field_entry(target_field).field_type().value_type(),
+                    format: format.clone(),
+                }
+            }
+            Some(ProtoProcessor::HtmlStrip(proto::HtmlStripProcessor { field })) => Processor::HtmlStrip {
+                field: get_field(schema, field, &[Type::Str])?,
+            },
+            Some(ProtoProcessor::DefaultValue(proto::DefaultValueProcessor { field, value })) => {
+                let field_name = field;
+                let field = get_field(schema, field_name, &[])?;
+                let json_value: JsonValue =
+                    serde_json::from_str(value).map_err(|error| ValidationError::InvalidIngestProcessor(format!("`{value}` is not a valid JSON: {error}")))?;
+                let value = value_from_json(schema.get_field_entry(field).field_type(), json_value)
+                    .map_err(|error| ValidationError::InvalidIngestProcessor(format!("invalid default value for `{field_name}`: {error}")))?;
+                Processor::DefaultValue { field, value }
+            }
+            Some(ProtoProcessor::SetYear(proto::SetYearProcessor { source_field, target_field })) => {
+                let target_field = get_field(schema, target_field, &[Type::Str, Type::I64, Type::U64])?;
+                Processor::SetYear {
+                    source_field: get_field(schema, source_field, &[Type::I64, Type::Date])?,
+                    target_field,
+                    target_type: schema.get_field_entry(target_field).field_type().value_type(),
+                }
+            }
+            None => return Err(ValidationError::InvalidIngestProcessor("processor is not set".to_owned()).into()),
+        })
+    }
+
+    fn apply(&self, field_values: &mut BTreeMap<Field, Vec<OwnedValue>>) -> SummaResult<()> {
+        match self {
+            Processor::Rename { source_field, target_field } => {
+                if let Some(values) = field_values.remove(source_field) {
+                    append(field_values, *target_field, values)
+                }
+            }
+            Processor::Copy { source_field, target_field } => {
+                if let Some(values) = field_values.get(source_field).cloned() {
+                    append(field_values, *target_field, values)
+                }
+            }
+            Processor::Drop { fields } => {
+                for field in fields {
+                    field_values.remove(field);
+                }
+            }
+            Processor::Lowercase { field } => map_texts(field_values, *field, |text| text.to_lowercase()),
+            Processor::Trim { field } => map_texts(field_values, *field, |text| text.trim().to_owned()),
+            Processor::RegexExtract {
+                source_field,
+                target_field,
+                regex,
+                group,
+            } => {
+                let extracted = texts(field_values, *source_field)
+                    .filter_map(|text| regex.captures(text).and_then(|captures| captures.get(*group)))
+                    .map(|extracted| OwnedValue::Str(extracted.as_str().to_owned()))
+                    .collect();
+                append(field_values, *target_field, extracted)
+            }
+            Processor::Split { field, separator } => {
+                if let Some(values) = field_values.get_mut(field) {
+                    *values = std::mem::take(values)
+                        .into_iter()
+                        .flat_map(|value| match value {
+                            OwnedValue::Str(text) => text
+                                .split(separator.as_str())
+                                .filter(|part| !part.is_empty())
+                                .map(|part| OwnedValue::Str(part.to_owned()))
+                                .collect(),
+                            value => vec![value],
+                        })
+                        .collect()
+                }
+            }
+            Processor::DateParse {
+                source_field,
+                source_field_name,
+                target_field,
+                target_type,
+                format,
+            } => {
+                let parsed = texts(field_values, *source_field)
+                    .map(|text| {
+                        parse_timestamp(text, format)
+                            .map(|timestamp| match target_type {
+                                Type::Date => OwnedValue::Date(DateTime::from_timestamp_secs(timestamp)),
+                                _ => OwnedValue::I64(timestamp),
+                            })
+                            .ok_or_else(|| {
+                                DocumentParsingError::ValueError(
+                                    source_field_name.clone(),
+                                    ValueParsingError::ParseError {
+                                        error: format!("does not match format `{format}`"),
+                                        json: JsonValue::String(text.to_owned()),
+                                    },
+                                )
+                            })
+                    })
+                    .collect::<Result<_, _>>()?;
+                append(field_values, *target_field, parsed)
+            }
+            Processor::HtmlStrip { field } => map_texts(field_values, *field, strip_html),
+            Processor::DefaultValue { field, value } => {
+                let values = field_values.entry(*field).or_default();
+                if values.is_empty() {
+                    values.push(value.clone())
+                }
+            }
+            Processor::SetYear {
+                source_field,
+                target_field,
+                target_type,
+            } => {
+                let year = field_values
+                    .get(source_field)
+                    .and_then(|values| values.first())
+                    .and_then(|value| match value {
+                        OwnedValue::I64(timestamp) => Some(*timestamp),
+                        OwnedValue::Date(date) => Some(date.into_timestamp_secs()),
+                        _ => None,
+                    })
+                    .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+                    .map(|date| date.year());
+                let year_value = year.and_then(|year| match target_type {
+                    Type::Str => Some(OwnedValue::Str(year.to_string())),
+                    Type::I64 => Some(OwnedValue::I64(i64::from(year))),
+                    _ => u64::try_from(year).ok().map(OwnedValue::U64),
+                });
+                if let Some(year_value) = year_value {
+                    append(field_values, *target_field, vec![year_value])
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn get_field(schema: &Schema, field_name: &str, allowed_types: &[Type]) -> SummaResult<Field> {
+    let field = schema.get_field(field_name).map_err(|_| ValidationError::MissingField(field_name.to_owned()))?;
+    let value_type = schema.get_field_entry(field).field_type().value_type();
+    if !allowed_types.is_empty() && !allowed_types.contains(&value_type) {
+        return Err(ValidationError::InvalidIngestProcessor(format!("`{field_name}` has type {value_type:?}, expected one of {allowed_types:?}")).into());
+    }
+    Ok(field)
+}
+
+fn get_same_typed_fields(schema: &Schema, source_field_name: &str, target_field_name: &str) -> SummaResult<(Field, Field)> {
+    let source_field = get_field(schema, source_field_name, &[])?;
+    let source_type = schema.get_field_entry(source_field).field_type().value_type();
+    Ok((source_field, get_field(schema, target_field_name, &[source_type])?))
+}
+
+fn append(field_values: &mut BTreeMap<Field, Vec<OwnedValue>>, field: Field, values: Vec<OwnedValue>) {
+    if !values.is_empty() {
+        field_values.entry(field).or_default().extend(values)
+    }
+}
+
+fn texts(field_values: &BTreeMap<Field, Vec<OwnedValue>>, field: Field) -> impl Iterator<Item = &str> {
+    field_values.get(&field).into_iter().flatten().filter_map(|value| match value {
+        OwnedValue::Str(text) => Some(text.as_str()),
+        _ => None,
+    })
+}
+
+fn map_texts(field_values: &mut BTreeMap<Field, Vec<OwnedValue>>, field: Field, f: impl Fn(&str) -> String) {
+    for value in field_values.get_mut(&field).into_iter().flatten() {
+        if let OwnedValue::Str(text) = value {
+            *text = f(text)
+        }
+    }
+}
+
+/// Parses `text` as a date with time zone, a date with time in UTC or a date at midnight in UTC
+fn parse_timestamp(text: &str, format: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_str(text, format)
+        .map(|date| date.timestamp())
+        .ok()
+        .or_else(|| NaiveDateTime::parse_from_str(text, format).ok().map(|date| date.and_utc().timestamp()))
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, format)
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc().timestamp())
+        })
+}
+
+fn strip_html(text: &str) -> String {
+    thread_local! {
+        static TAG: Regex = Regex::new(r"(?s)<[^>]*>").expect("cannot compile regex");
+    }
+    TAG.with(|tag| tag.replace_all(text, ""))
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use summa_proto::proto;
+    use tantivy::schema::{Field, OwnedValue, SchemaBuilder, INDEXED, STORED, STRING, TEXT};
+    use tantivy::{doc, TantivyDocument};
+
+    use super::IngestPipeline;
+
+    fn values(document: &TantivyDocument, field: Field) -> Vec<OwnedValue> {
+        document.get_all(field).map(OwnedValue::from).collect()
+    }
+
+    #[test]
+    fn test_ingest_pipeline() {
+        let mut schema_builder = SchemaBuilder::new();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let raw_title = schema_builder.add_text_field("raw_title", TEXT | STORED);
+        let tags = schema_builder.add_text_field("tags", STRING | STORED);
+        let doi = schema_builder.add_text_field("doi", STRING | STORED);
+        let published = schema_builder.add_text_field("published", STRING);
+        let issued_at = schema_builder.add_i64_field("issued_at", INDEXED | STORED);
+        let extra = schema_builder.add_text_field("extra", TEXT | STORED);
+        let language = schema_builder.add_text_field("language", STRING | STORED);
+        let schema = schema_builder.build();
+
+        let processor = |processor| proto::IngestProcessor { processor: Some(processor) };
+        let ingest_pipeline = IngestPipeline::new(
+            &schema,
+            &[
+                processor(proto::ingest_processor::Processor::Rename(proto::RenameProcessor {
+                    source_field: "raw_title".to_string(),
+                    target_field: "title".to_string(),
+                })),
+                processor(proto::ingest_processor::Processor::HtmlStrip(proto::HtmlStripProcessor {
+                    field: "title".to_string(),
+                })),
+                processor(proto::ingest_processor::Processor::Trim(proto::TrimProcessor { field: "title".to_string() })),
+                processor(proto::ingest_processor::Processor::Split(proto::SplitProcessor {
+                    field: "tags".to_string(),
+                    separator: ",".to_string(),
+                })),
+                processor(proto::ingest_processor::Processor::Lowercase(proto::LowercaseProcessor {
+                    field: "tags".to_string(),
+                })),
+                processor(proto::ingest_processor::Processor::RegexExtract(proto::RegexExtractProcessor {
+                    source_field: "title".to_string(),
+                    target_field: "doi".to_string(),
+                    pattern: r"doi:(\S+)".to_string(),
+                    group: 1,
+                })),
+                processor(proto::ingest_processor::Processor::DateParse(proto::DateParseProcessor {
+                    source_field: "published".to_string(),
+                    target_field: "issued_at".to_string(),
+                    format: "%Y-%m-%d".to_string(),
+                })),
+                processor(proto::ingest_processor::Processor::Drop(proto::DropProcessor {
+                    fields: vec!["published".to_string()],
+                })),
+                processor(proto::ingest_processor::Processor::SetYear(proto::SetYearProcessor {
+                    source_field: "issued_at".to_string(),
+                    target_field: "extra".to_string(),
+                })),
+                processor(proto::ingest_processor::Processor::DefaultValue(proto::DefaultValueProcessor {
+                    field: "language".to_string(),
+                    value: "\"en\"".to_string(),
+                })),
+            ],
+        )
+        .unwrap();
+
+        let mut document = doc!(
+            raw_title => " <b>Paper</b> doi:10.1000/182 &amp; more ",
+            tags => "Physics,,Math",
+            published => "2021-03-04",
+        );
+        ingest_pipeline.process(&mut document).unwrap();
+        assert_eq!(values(&document, title), vec![OwnedValue::Str("Paper doi:10.1000/182 & more".to_string())]);
+        assert!(values(&document, raw_title).is_empty());
+        assert_eq!(
+            values(&document, tags),
+            vec![OwnedValue::Str("physics".to_string()), OwnedValue::Str("math".to_string())]
+        );
+        assert_eq!(values(&document, doi), vec![OwnedValue::Str("10.1000/182".to_string())]);
+        assert!(values(&document, published).is_empty());
+        assert_eq!(values(&document, issued_at), vec![OwnedValue::I64(1614816000)]);
+        assert_eq!(values(&document, extra), vec![OwnedValue::Str("2021".to_string())]);
+        assert_eq!(values(&document, language), vec![OwnedValue::Str("en".to_string())]);
+
+        let mut document = doc!(published => "04.03.2021");
+        assert!(ingest_pipeline.process(&mut document).is_err());
+    }
+
+    #[test]
+    fn test_invalid_ingest_pipeline() {
+        let mut schema_builder = SchemaBuilder::new();
+        schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_i64_field("issued_at", INDEXED | STORED);
+        let schema = schema_builder.build();
+
+        assert!(IngestPipeline::new(
+            &schema,
+            &[proto::IngestProcessor {
+                processor: Some(proto::ingest_processor::Processor::Lowercase(proto::LowercaseProcessor {
+                    field: "issued_at".to_string()
+                })),
+            }]
+        )
+        .is_err());
+        assert!(IngestPipeline::new(
+            &schema,
+            &[proto::IngestProcessor {
+                processor: Some(proto::ingest_processor::Processor::Rename(proto::RenameProcessor {
+                    source_field: "title".to_string(),
+                    target_field: "issued_at".to_string(),
+                })),
+            }]
+        )
+        .is_err());
+    }
+}
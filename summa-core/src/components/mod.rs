@@ -1,38 +1,53 @@
+mod auto_id;
 mod collector_cache;
 mod custom_serializer;
 mod default_tokenizers;
+mod document_expiry;
+mod document_versions;
 mod fruit_extractors;
 mod index_holder;
 mod index_registry;
 mod index_writer_holder;
+mod ingest_pipeline;
 pub mod merge_policies;
 pub mod queries;
 mod query_parser;
+mod query_profile;
+mod search_deadline;
 mod segment_attributes;
 mod snippet_generator;
 mod summa_document;
 pub mod tokenizers;
+pub mod vectors;
 
 pub use custom_serializer::NamedFieldDocument;
 pub use default_tokenizers::{default_tokenizers, STOP_WORDS};
-pub use fruit_extractors::{build_fruit_extractor, FruitExtractor, IntermediateExtractionResult};
+pub use document_versions::IndexingOutcome;
+pub use fruit_extractors::{build_fruit_extractor, drill_sideways_query, FruitExtractor, IntermediateExtractionResult};
 pub use index_holder::{cleanup_index, IndexHolder};
 pub use index_registry::IndexRegistry;
 pub use index_writer_holder::IndexWriterHolder;
-pub use query_parser::{MorphologyManager, ProtoQueryParser, QueryParser, QueryParserError};
+pub use ingest_pipeline::IngestPipeline;
+pub use query_parser::{query_to_tree, MorphologyManager, ProtoQueryParser, QueryParser, QueryParserError};
+pub use query_profile::QueryProfile;
+pub use search_deadline::{CancelOnDrop, SearchDeadline};
 pub use segment_attributes::SummaSegmentAttributes;
 pub use summa_document::{DocumentParsingError, SummaDocument};
 
 pub mod test_utils {
     use std::default::Default;
     use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
 
     use itertools::Itertools;
     use rand::rngs::SmallRng;
     use rand::{Rng, SeedableRng};
     use serde_json::json;
     use tantivy::schema::{IndexRecordOption, JsonObjectOptions, Schema, TextFieldIndexing, TextOptions, FAST, INDEXED, STORED};
-    use tantivy::{doc, Document};
+    use tantivy::{doc, Document, Index, TantivyDocument};
+
+    use crate::components::IndexWriterHolder;
+    use crate::configs::core::WriterThreads;
 
     pub fn create_test_schema() -> Schema {
         let mut schema_builder = Schema::builder();
@@ -91,6 +106,25 @@ pub mod test_utils {
         schema_builder.build()
     }
 
+    /// Creates an in-memory index where every item of `segments` is committed as a separate segment
+    pub fn create_test_index(schema: Schema, segments: impl IntoIterator<Item = impl IntoIterator<Item = TantivyDocument>>) -> Index {
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).expect("cannot create index writer");
+        for segment in segments {
+            for document in segment {
+                index_writer.add_document(document).expect("cannot add document");
+            }
+            index_writer.commit().expect("cannot commit");
+        }
+        index
+    }
+
+    /// Creates a single-threaded `IndexWriterHolder` that never merges segments
+    pub fn create_test_index_writer_holder(index: &Index) -> IndexWriterHolder {
+        IndexWriterHolder::create(index, WriterThreads::N(1), 30 * 1024 * 1024, Arc::new(tantivy::merge_policy::NoMergePolicy))
+            .expect("cannot create index writer")
+    }
+
     #[inline]
     fn generate_term(rng: &mut SmallRng, prefix: &str, power: usize) -> String {
         if power > 0 {
@@ -64,6 +64,15 @@ impl ExistsQuery {
             full_path: full_path.to_string(),
         }
     }
+
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Path inside JSON field, empty for other fields
+    pub fn full_path(&self) -> &str {
+        &self.full_path
+    }
 }
 
 #[async_trait]
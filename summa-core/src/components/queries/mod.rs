@@ -1,3 +1,5 @@
 mod exists_query;
+mod vector_query;
 
 pub use exists_query::ExistsQuery;
+pub use vector_query::VectorQuery;
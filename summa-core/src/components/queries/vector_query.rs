@@ -0,0 +1,336 @@
+use std::fmt::{Debug, Formatter};
+use std::io;
+
+use summa_proto::proto;
+use tantivy::columnar::{BytesColumn, ColumnType};
+use tantivy::directory::error::OpenReadError;
+use tantivy::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::{Directory, DocId, DocSet, Index, Result, Score, SegmentReader, TantivyError, TERMINATED};
+use tantivy_common::BitSet;
+use tracing::warn;
+
+use crate::components::vectors::{hnsw_graph_path, read_vector, similarity, HnswGraph, DEFAULT_EF_SEARCH};
+
+/// A Vector Query matches `k` documents of every segment which vectors are the most similar to the query vector
+///
+/// Vectors are read from the bytes fast field and documents are scored by their similarity to the query vector.
+/// Segments having HNSW graphs built for the same metric are searched through graphs, other segments are scanned exhaustively.
+/// The filter is applied before selecting neighbours, so filtered out documents never take places of the nearest ones.
+pub struct VectorQuery {
+    index: Index,
+    field_name: String,
+    vector: Vec<f32>,
+    k: usize,
+    metric: proto::VectorMetric,
+    ef_search: usize,
+    exact: bool,
+    filter: Option<Box<dyn Query>>,
+}
+
+impl VectorQuery {
+    /// Creates a new VectorQuery over the bytes fast field of the index
+    pub fn new(index: Index, field_name: &str, vector: Vec<f32>, k: usize, metric: proto::VectorMetric) -> Self {
+        VectorQuery {
+            index,
+            field_name: field_name.to_string(),
+            vector,
+            k,
+            metric,
+            ef_search: DEFAULT_EF_SEARCH,
+            exact: false,
+            filter: None,
+        }
+    }
+
+    /// Sets the size of the candidate list of HNSW search
+    pub fn with_ef_search(mut self, ef_search: usize) -> Self {
+        self.ef_search = ef_search;
+        self
+    }
+
+    /// Disables HNSW graphs and scans all vectors
+    pub fn with_exact(mut self, exact: bool) -> Self {
+        self.exact = exact;
+        self
+    }
+
+    /// Restricts neighbours to documents matching `filter`
+    pub fn with_filter(mut self, filter: Box<dyn Query>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    pub fn filter(&self) -> Option<&dyn Query> {
+        self.filter.as_deref()
+    }
+}
+
+impl Clone for VectorQuery {
+    fn clone(&self) -> Self {
+        VectorQuery {
+            index: self.index.clone(),
+            field_name: self.field_name.clone(),
+            vector: self.vector.clone(),
+            k: self.k,
+            metric: self.metric,
+            ef_search: self.ef_search,
+            exact: self.exact,
+            filter: self.filter.as_ref().map(|filter| filter.box_clone()),
+        }
+    }
+}
+
+impl Debug for VectorQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VectorQuery")
+            .field("field_name", &self.field_name)
+            .field("vector", &self.vector)
+            .field("k", &self.k)
+            .field("metric", &self.metric)
+            .field("ef_search", &self.ef_search)
+            .field("exact", &self.exact)
+            .field("filter", &self.filter)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Query for VectorQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> Result<Box<dyn Weight>> {
+        let filter = self
+            .filter
+            .as_ref()
+            .map(|filter| filter.weight(EnableScoring::disabled_from_schema(enable_scoring.schema())))
+            .transpose()?;
+        Ok(Box::new(VectorWeight { query: self.clone(), filter }))
+    }
+
+    async fn weight_async(&self, enable_scoring: EnableScoring<'_>) -> Result<Box<dyn Weight>> {
+        let filter = match &self.filter {
+            Some(filter) => Some(filter.weight_async(EnableScoring::disabled_from_schema(enable_scoring.schema())).await?),
+            None => None,
+        };
+        Ok(Box::new(VectorWeight { query: self.clone(), filter }))
+    }
+}
+
+/// Weight associated with the `VectorQuery` query.
+pub struct VectorWeight {
+    query: VectorQuery,
+    filter: Option<Box<dyn Weight>>,
+}
+
+impl VectorWeight {
+    fn graph_from_file(&self, reader: &SegmentReader, file: std::result::Result<Vec<u8>, OpenReadError>) -> Result<Option<HnswGraph>> {
+        match file {
+            Ok(bytes) => {
+                let graph = HnswGraph::from_bytes(&bytes)?;
+                Ok((graph.metric() == self.query.metric).then_some(graph))
+            }
+            Err(OpenReadError::FileDoesNotExist(_)) => Ok(None),
+            Err(error) => {
+                warn!(action = "skip_hnsw_graph", segment_id = ?reader.segment_id(), error = ?error);
+                Ok(None)
+            }
+        }
+    }
+
+    fn load_graph(&self, reader: &SegmentReader) -> Result<Option<HnswGraph>> {
+        if self.query.exact {
+            return Ok(None);
+        }
+        let path = hnsw_graph_path(reader.segment_id(), &self.query.field_name);
+        self.graph_from_file(reader, self.query.index.directory().atomic_read(&path))
+    }
+
+    async fn load_graph_async(&self, reader: &SegmentReader) -> Result<Option<HnswGraph>> {
+        if self.query.exact {
+            return Ok(None);
+        }
+        let path = hnsw_graph_path(reader.segment_id(), &self.query.field_name);
+        self.graph_from_file(reader, self.query.index.directory().atomic_read_async(&path).await)
+    }
+
+    /// Returns nearest documents of the segment in the order of increasing doc ids
+    fn nearest(
+        &self,
+        reader: &SegmentReader,
+        column: Option<BytesColumn>,
+        graph: Option<HnswGraph>,
+        filter: Option<Box<dyn Scorer>>,
+    ) -> Result<Vec<(DocId, Score)>> {
+        let Some(column) = column else {
+            return Ok(vec![]);
+        };
+        let allowed_docs = filter.map(|mut filter| {
+            let mut allowed_docs = BitSet::with_max_value(reader.max_doc());
+            let mut doc = filter.doc();
+            while doc != TERMINATED {
+                allowed_docs.insert(doc);
+                doc = filter.advance();
+            }
+            allowed_docs
+        });
+        let alive_bitset = reader.alive_bitset();
+        let is_accepted = |doc: DocId| {
+            if let Some(alive_bitset) = alive_bitset {
+                if !alive_bitset.is_alive(doc) {
+                    return false;
+                }
+            }
+            match &allowed_docs {
+                Some(allowed_docs) => allowed_docs.contains(doc),
+                None => true,
+            }
+        };
+
+        let mut buffer = vec![];
+        let mut similarity_to = |doc: DocId| {
+            Ok::<_, io::Error>(match read_vector(&column, doc, &mut buffer)? {
+                Some(vector) if vector.len() == self.query.vector.len() => similarity(self.query.metric, &self.query.vector, &vector),
+                _ => f32::NEG_INFINITY,
+            })
+        };
+
+        if let Some(graph) = graph {
+            let mut nearest = graph.search(self.query.k, self.query.ef_search, is_accepted, &mut similarity_to)?;
+            // Graph search visits limited number of candidates, so filtered searches falling short of `k` are repeated with exact scan
+            if nearest.len() >= self.query.k || (allowed_docs.is_none() && alive_bitset.is_none()) {
+                nearest.sort_by_key(|(doc, _)| *doc);
+                return Ok(nearest);
+            }
+        }
+
+        let mut nearest = vec![];
+        for doc in 0..reader.max_doc() {
+            if is_accepted(doc) {
+                let score = similarity_to(doc)?;
+                if score > f32::NEG_INFINITY {
+                    nearest.push((doc, score));
+                }
+            }
+        }
+        nearest.sort_by(|left, right| right.1.total_cmp(&left.1).then_with(|| left.0.cmp(&right.0)));
+        nearest.truncate(self.query.k);
+        nearest.sort_by_key(|(doc, _)| *doc);
+        Ok(nearest)
+    }
+}
+
+#[async_trait]
+impl Weight for VectorWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> Result<Box<dyn Scorer>> {
+        let column = reader.fast_fields().bytes(&self.query.field_name)?;
+        let filter = self.filter.as_ref().map(|filter| filter.scorer(reader, 1.0)).transpose()?;
+        let graph = self.load_graph(reader)?;
+        Ok(Box::new(VectorScorer::new(self.nearest(reader, column, graph, filter)?, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!("Document #({}) does not match", doc)));
+        }
+        Ok(Explanation::new("VectorQuery", scorer.score()))
+    }
+
+    async fn scorer_async(&self, reader: &SegmentReader, boost: Score) -> Result<Box<dyn Scorer>> {
+        let column: Option<BytesColumn> = match reader
+            .fast_fields()
+            .dynamic_column_handle_async(&self.query.field_name, ColumnType::Bytes)
+            .await?
+        {
+            Some(dynamic_column_handle) => dynamic_column_handle.open_async().await?.into(),
+            None => None,
+        };
+        let filter = match &self.filter {
+            Some(filter) => Some(filter.scorer_async(reader, 1.0).await?),
+            None => None,
+        };
+        let graph = self.load_graph_async(reader).await?;
+        Ok(Box::new(VectorScorer::new(self.nearest(reader, column, graph, filter)?, boost)))
+    }
+}
+
+/// Iterates over precomputed nearest documents sorted by doc ids
+struct VectorScorer {
+    docs: Vec<(DocId, Score)>,
+    cursor: usize,
+}
+
+impl VectorScorer {
+    fn new(docs: Vec<(DocId, Score)>, boost: Score) -> Self {
+        VectorScorer {
+            docs: docs.into_iter().map(|(doc, score)| (doc, score * boost)).collect(),
+            cursor: 0,
+        }
+    }
+}
+
+impl DocSet for VectorScorer {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.docs.len() {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs.get(self.cursor).map(|(doc, _)| *doc).unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u64 {
+        self.docs.len() as u64
+    }
+}
+
+impl Scorer for VectorScorer {
+    fn score(&mut self) -> Score {
+        self.docs.get(self.cursor).map(|(_, score)| *score).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use summa_proto::proto;
+    use tantivy::collector::TopDocs;
+    use tantivy::query::{BooleanQuery, Occur, Query, TermQuery};
+    use tantivy::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use tantivy::{doc, Term};
+
+    use super::VectorQuery;
+    use crate::components::test_utils::create_test_index;
+    use crate::components::vectors::encode_vector;
+
+    #[test]
+    fn test_vector_query() {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let embedding = schema_builder.add_bytes_field("embedding", FAST);
+        let index = create_test_index(
+            schema_builder.build(),
+            [[("a", [1.0, 0.0]), ("b", [0.9, 0.1]), ("a", [0.0, 1.0]), ("b", [-1.0, 0.0])]
+                .map(|(tag_value, vector)| doc!(tag => tag_value, embedding => encode_vector(&vector)))],
+        );
+        let searcher = index.reader().unwrap().searcher();
+
+        let query = VectorQuery::new(index.clone(), "embedding", vec![1.0, 0.0], 2, proto::VectorMetric::Cosine);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        assert_eq!(top_docs.iter().map(|(_, doc_address)| doc_address.doc_id).collect::<Vec<_>>(), vec![0, 1]);
+        assert!((top_docs[0].0 - 1.0).abs() < 1e-6);
+
+        let filter: Box<dyn Query> = Box::new(TermQuery::new(Term::from_field_text(tag, "b"), IndexRecordOption::Basic));
+        let query = VectorQuery::new(index.clone(), "embedding", vec![1.0, 0.0], 2, proto::VectorMetric::Cosine).with_filter(filter.box_clone());
+        let top_docs = searcher
+            .search(
+                &BooleanQuery::new(vec![(Occur::Must, Box::new(query)), (Occur::Must, filter)]),
+                &TopDocs::with_limit(10),
+            )
+            .unwrap();
+        assert_eq!(top_docs.iter().map(|(_, doc_address)| doc_address.doc_id).collect::<Vec<_>>(), vec![1, 3]);
+    }
+}
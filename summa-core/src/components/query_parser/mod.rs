@@ -1,10 +1,12 @@
 mod morphology;
 mod proto_query_parser;
+mod query_tree;
 mod summa_ql;
 mod term_field_mappers;
 pub(crate) mod utils;
 
 pub use proto_query_parser::ProtoQueryParser;
+pub use query_tree::query_to_tree;
 
 pub use self::morphology::MorphologyManager;
 pub use self::summa_ql::{QueryParser, QueryParserError};
@@ -1,28 +1,35 @@
 use std::ops::Bound;
 use std::ops::Bound::Unbounded;
 use std::str::FromStr;
+use std::sync::Arc;
 
 #[cfg(feature = "metrics")]
 use opentelemetry::metrics::Counter;
 #[cfg(feature = "metrics")]
 use opentelemetry::{global, KeyValue};
+use regex::RegexBuilder;
 use summa_proto::proto;
 use tantivy::query::{
-    AllQuery, BooleanQuery, BoostQuery, DisjunctionMaxQuery, EmptyQuery, MoreLikeThisQuery, Occur, PhraseQuery, Query, RangeQuery, RegexQuery, TermQuery,
+    AllQuery, BooleanQuery, BoostQuery, DisjunctionMaxQuery, EmptyQuery, MoreLikeThisQuery, Occur, PhraseQuery, Query, QueryClone, RangeQuery, RegexQuery,
+    TermQuery,
 };
 use tantivy::schema::{Field, FieldEntry, FieldType, IndexRecordOption, OwnedValue, Schema};
 use tantivy::{Document, Index, Score, TantivyDocument, Term};
 use tracing::info;
 
-use crate::components::queries::ExistsQuery;
+use crate::components::queries::{ExistsQuery, VectorQuery};
 use crate::components::query_parser::morphology::MorphologyManager;
+use crate::components::query_parser::term_field_mappers::TermFieldMappersManager;
 use crate::components::query_parser::utils::cast_field_to_typed_term;
 use crate::components::query_parser::{QueryParser, QueryParserError};
-use crate::configs::core::QueryParserConfig;
+use crate::configs::core::{QueryParserConfig, SearchLimits};
 use crate::errors::{Error, SummaResult, ValidationError};
 #[cfg(feature = "metrics")]
 use crate::metrics::ToLabel;
 
+/// Number of terms selected by `MoreLikeThisQuery` if it is not set in the query
+const DEFAULT_MORE_LIKE_THIS_TERMS: usize = 25;
+
 /// Responsible for casting `crate::proto::Query` message to `tantivy::query::Query`
 #[derive(Clone)]
 pub struct ProtoQueryParser {
@@ -34,7 +41,9 @@ pub struct ProtoQueryParser {
     #[cfg(feature = "metrics")]
     subquery_counter: Counter<u64>,
     query_parser_config: QueryParserConfig,
+    term_field_mappers_manager: Arc<TermFieldMappersManager>,
     morphology_manager: MorphologyManager,
+    search_limits: SearchLimits,
 }
 
 pub enum QueryParserDefaultMode {
@@ -67,6 +76,33 @@ fn cast_value_to_bound_term(field: &Field, full_path: &str, field_type: &FieldTy
     })
 }
 
+/// Restricts neighbours of vector subqueries to documents passing `must` and `must_not` clauses of the same boolean query
+///
+/// Otherwise clauses would be applied after selecting nearest documents and filter out some of them.
+fn push_filters_to_vector_queries(subqueries: &mut [(Occur, Box<dyn Query>)]) {
+    let is_vector_query = |query: &dyn Query| query.downcast_ref::<VectorQuery>().is_some();
+    if !subqueries.iter().any(|(_, query)| is_vector_query(query.as_ref())) {
+        return;
+    }
+    let mut filters: Vec<(Occur, Box<dyn Query>)> = subqueries
+        .iter()
+        .filter(|(occur, query)| *occur != Occur::Should && !is_vector_query(query.as_ref()))
+        .map(|(occur, query)| (*occur, query.box_clone()))
+        .collect();
+    if filters.is_empty() {
+        return;
+    }
+    if filters.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+        filters.push((Occur::Must, Box::new(AllQuery)));
+    }
+    let filter = BooleanQuery::new(filters);
+    for (_, query) in subqueries.iter_mut() {
+        if let Some(vector_query) = query.as_ref().downcast_ref::<VectorQuery>() {
+            *query = Box::new(vector_query.clone().with_filter(filter.box_clone()));
+        }
+    }
+}
+
 impl ProtoQueryParser {
     pub fn for_index(index: &Index, query_parser_config: proto::QueryParserConfig) -> SummaResult<ProtoQueryParser> {
         #[cfg(feature = "metrics")]
@@ -77,6 +113,12 @@ impl ProtoQueryParser {
             .with_description("Sub-queries counter")
             .init();
 
+        let term_field_mappers_manager = Arc::new(TermFieldMappersManager::new(
+            &index.schema(),
+            index.tokenizers(),
+            &query_parser_config.term_field_mapper_configs,
+        )?);
+
         Ok(ProtoQueryParser {
             index: index.clone(),
             cached_schema: index.schema(),
@@ -85,10 +127,26 @@ impl ProtoQueryParser {
             #[cfg(feature = "metrics")]
             subquery_counter,
             query_parser_config: QueryParserConfig(query_parser_config),
+            term_field_mappers_manager,
             morphology_manager: MorphologyManager::default(),
+            search_limits: SearchLimits::default(),
         })
     }
 
+    /// Sets limits checked while parsing queries by `parse_query` and `parse_query_with_warnings`
+    pub fn with_search_limits(mut self, search_limits: SearchLimits) -> ProtoQueryParser {
+        self.search_limits = search_limits;
+        self
+    }
+
+    pub fn search_limits(&self) -> &SearchLimits {
+        &self.search_limits
+    }
+
+    pub fn query_parser_config(&self) -> &proto::QueryParserConfig {
+        &self.query_parser_config.0
+    }
+
     pub fn resolve_field_name<'a>(&'a self, field_name: &'a str) -> &str {
         self.query_parser_config
             .0
@@ -109,7 +167,7 @@ impl ProtoQueryParser {
         }
     }
 
-    fn parse_subquery(&self, query: proto::query::Query) -> SummaResult<Box<dyn Query>> {
+    fn parse_subquery(&self, query: proto::query::Query, search_limits: &SearchLimits, warnings: &mut Vec<proto::QueryWarning>) -> SummaResult<Box<dyn Query>> {
         #[cfg(feature = "metrics")]
         self.subquery_counter.add(1, &[KeyValue::new("query", query.to_label())]);
         Ok(match query {
@@ -124,16 +182,17 @@ impl ProtoQueryParser {
                             proto::Occur::Must => Occur::Must,
                             proto::Occur::MustNot => Occur::MustNot,
                         },
-                        self.parse_subquery(subquery.query.and_then(|query| query.query).ok_or(Error::EmptyQuery)?)?,
+                        self.parse_subquery(subquery.query.and_then(|query| query.query).ok_or(Error::EmptyQuery)?, search_limits, warnings)?,
                     ))
                 }
+                push_filters_to_vector_queries(&mut subqueries);
                 Box::new(BooleanQuery::new(subqueries))
             }
             proto::query::Query::DisjunctionMax(disjunction_max_proto) => Box::new(DisjunctionMaxQuery::with_tie_breaker(
                 disjunction_max_proto
                     .disjuncts
                     .into_iter()
-                    .map(|disjunct| self.parse_subquery(disjunct.query.ok_or(Error::EmptyQuery)?))
+                    .map(|disjunct| self.parse_subquery(disjunct.query.ok_or(Error::EmptyQuery)?, search_limits, warnings))
                     .collect::<SummaResult<Vec<_>>>()?,
                 match disjunction_max_proto.tie_breaker.as_str() {
                     "" => 0.0,
@@ -145,8 +204,21 @@ impl ProtoQueryParser {
                 if let Some(query_parser_config) = match_query_proto.query_parser_config {
                     new_query_parser_config.merge(QueryParserConfig(query_parser_config));
                 }
-                let nested_query_parser = QueryParser::for_index(&self.index, new_query_parser_config.clone(), &self.morphology_manager)?;
-                match nested_query_parser.parse_query(&match_query_proto.value) {
+                if let Some(exact_matches_promoter) = &new_query_parser_config.0.exact_matches_promoter {
+                    SearchLimits::check("phrase_slop", exact_matches_promoter.slop, search_limits.0.max_phrase_slop)?;
+                }
+                let nested_query_parser = if new_query_parser_config.0.term_field_mapper_configs == self.query_parser_config.0.term_field_mapper_configs {
+                    QueryParser::with_term_field_mappers_manager(
+                        self.cached_schema.clone(),
+                        new_query_parser_config.clone(),
+                        &self.morphology_manager,
+                        self.index.tokenizers(),
+                        self.term_field_mappers_manager.clone(),
+                    )?
+                } else {
+                    QueryParser::for_index(&self.index, new_query_parser_config.clone(), &self.morphology_manager)?
+                };
+                match nested_query_parser.parse_query_with_warnings(&match_query_proto.value, warnings) {
                     Ok(parsed_query) => {
                         info!(query = ?match_query_proto.value, parsed_match_query = ?parsed_query, query_parser_config = ?new_query_parser_config);
                         Ok(parsed_query)
@@ -163,14 +235,26 @@ impl ProtoQueryParser {
                 Box::new(RangeQuery::new(left, right))
             }
             proto::query::Query::Boost(boost_query_proto) => Box::new(BoostQuery::new(
-                self.parse_subquery(boost_query_proto.query.and_then(|query| query.query).ok_or(Error::EmptyQuery)?)?,
+                self.parse_subquery(
+                    boost_query_proto.query.and_then(|query| query.query).ok_or(Error::EmptyQuery)?,
+                    search_limits,
+                    warnings,
+                )?,
                 f32::from_str(&boost_query_proto.score).map_err(|_e| Error::InvalidSyntax(format!("cannot parse {} as f32", boost_query_proto.score)))?,
             )),
             proto::query::Query::Regex(regex_query_proto) => {
                 let (field, _, _) = self.field_and_field_entry(&regex_query_proto.field)?;
+                if let Some(max_regex_size_bytes) = search_limits.0.max_regex_size_bytes {
+                    // Automaton of `RegexQuery` grows along with the compiled program of the same regular expression
+                    if let Err(regex::Error::CompiledTooBig(_)) = RegexBuilder::new(&regex_query_proto.value).size_limit(max_regex_size_bytes as usize).build()
+                    {
+                        return Err(ValidationError::LimitExceeded("regex_size_bytes".to_string(), max_regex_size_bytes).into());
+                    }
+                }
                 Box::new(RegexQuery::from_pattern(&regex_query_proto.value, field)?)
             }
             proto::query::Query::Phrase(phrase_query_proto) => {
+                SearchLimits::check("phrase_slop", phrase_query_proto.slop, search_limits.0.max_phrase_slop)?;
                 let (field, full_path, field_entry) = self.field_and_field_entry(&phrase_query_proto.field)?;
                 let mut tokenizer = self.index.tokenizer_for_field(field)?;
 
@@ -183,6 +267,11 @@ impl ProtoQueryParser {
                     ))
                 }
                 if terms.is_empty() {
+                    warnings.push(proto::QueryWarning {
+                        kind: proto::QueryWarningKind::DroppedTerm.into(),
+                        field: Some(phrase_query_proto.field.to_string()),
+                        term: Some(phrase_query_proto.value.to_string()),
+                    });
                     Box::new(EmptyQuery)
                 } else if terms.len() == 1 {
                     Box::new(TermQuery::new(
@@ -220,7 +309,10 @@ impl ProtoQueryParser {
                     query_builder = query_builder.with_min_term_frequency(min_term_frequency as usize);
                 }
                 if let Some(max_query_terms) = more_like_this_query_proto.max_query_terms {
+                    SearchLimits::check("more_like_this_terms", max_query_terms, search_limits.0.max_more_like_this_terms.map(u64::from))?;
                     query_builder = query_builder.with_max_query_terms(max_query_terms as usize);
+                } else if let Some(max_more_like_this_terms) = search_limits.0.max_more_like_this_terms {
+                    query_builder = query_builder.with_max_query_terms((max_more_like_this_terms as usize).min(DEFAULT_MORE_LIKE_THIS_TERMS));
                 }
                 if let Some(min_word_length) = more_like_this_query_proto.min_word_length {
                     query_builder = query_builder.with_min_word_length(min_word_length as usize);
@@ -243,12 +335,137 @@ impl ProtoQueryParser {
                 }
                 Box::new(ExistsQuery::new(field, full_path))
             }
+            proto::query::Query::Vector(vector_query_proto) => {
+                let (_, _, field_entry) = self.field_and_field_entry(&vector_query_proto.field)?;
+                if !matches!(field_entry.field_type(), FieldType::Bytes(_)) {
+                    return Err(Error::InvalidFieldType(field_entry.name().to_string(), field_entry.field_type().clone()));
+                }
+                if !field_entry.is_fast() {
+                    return Err(ValidationError::RequiredFastField(field_entry.name().to_string()).into());
+                }
+                if vector_query_proto.vector.is_empty() {
+                    return Err(ValidationError::EmptyArgument("vector".to_string()).into());
+                }
+                if vector_query_proto.k == 0 {
+                    return Err(Error::InvalidSyntax("`k` of vector query must be positive".to_string()));
+                }
+                let metric = vector_query_proto.metric();
+                let mut vector_query = VectorQuery::new(
+                    self.index.clone(),
+                    field_entry.name(),
+                    vector_query_proto.vector,
+                    vector_query_proto.k as usize,
+                    metric,
+                )
+                .with_exact(vector_query_proto.exact);
+                if let Some(ef_search) = vector_query_proto.ef_search {
+                    vector_query = vector_query.with_ef_search(ef_search as usize);
+                }
+                Box::new(vector_query)
+            }
         })
     }
 
+    /// Counts terms of the parsed query, terms of queries like `RegexQuery` are expanded only during search and are not counted
+    fn check_query_terms(parsed_query: &dyn Query, search_limits: &SearchLimits) -> SummaResult<()> {
+        if let Some(max_query_terms) = search_limits.0.max_query_terms {
+            let mut query_terms = 0u64;
+            parsed_query.query_terms(&mut |_, _| query_terms += 1);
+            SearchLimits::check("query_terms", query_terms, Some(u64::from(max_query_terms)))?;
+        }
+        Ok(())
+    }
+
     pub fn parse_query(&self, query: proto::query::Query) -> SummaResult<Box<dyn Query>> {
+        self.parse_query_with_limits(query, &self.search_limits)
+    }
+
+    /// Parses query checking `search_limits` instead of limits of the parser
+    pub fn parse_query_with_limits(&self, query: proto::query::Query, search_limits: &SearchLimits) -> SummaResult<Box<dyn Query>> {
         #[cfg(feature = "metrics")]
         self.query_counter.add(1, &[KeyValue::new("query", query.to_label())]);
-        self.parse_subquery(query)
+        let parsed_query = self.parse_subquery(query, search_limits, &mut vec![])?;
+        ProtoQueryParser::check_query_terms(parsed_query.as_ref(), search_limits)?;
+        Ok(parsed_query)
+    }
+
+    /// Parses query and collects warnings about parts of the query that were dropped or truncated
+    pub fn parse_query_with_warnings(&self, query: proto::query::Query) -> SummaResult<(Box<dyn Query>, Vec<proto::QueryWarning>)> {
+        let mut warnings = vec![];
+        let parsed_query = self.parse_subquery(query, &self.search_limits, &mut warnings)?;
+        ProtoQueryParser::check_query_terms(parsed_query.as_ref(), &self.search_limits)?;
+        Ok((parsed_query, warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use summa_proto::proto;
+    use tantivy::schema::{Schema, TEXT};
+    use tantivy::Index;
+
+    use super::ProtoQueryParser;
+    use crate::configs::core::SearchLimits;
+
+    #[test]
+    fn test_search_limits() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let query_parser = ProtoQueryParser::for_index(
+            &index,
+            proto::QueryParserConfig {
+                default_fields: vec!["title".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .with_search_limits(SearchLimits(proto::SearchLimits {
+            max_regex_size_bytes: Some(100_000),
+            max_phrase_slop: Some(2),
+            max_query_terms: Some(3),
+            ..Default::default()
+        }));
+
+        let phrase = |slop| {
+            proto::query::Query::Phrase(proto::PhraseQuery {
+                field: "title".to_string(),
+                value: "alpha beta".to_string(),
+                slop,
+            })
+        };
+        assert!(query_parser.parse_query(phrase(2)).is_ok());
+        assert!(query_parser.parse_query(phrase(3)).is_err());
+        assert!(query_parser.parse_query_with_limits(phrase(3), &SearchLimits::default()).is_ok());
+
+        let match_query = |value: &str| {
+            proto::query::Query::Match(proto::MatchQuery {
+                value: value.to_string(),
+                ..Default::default()
+            })
+        };
+        assert!(query_parser.parse_query(match_query("alpha beta gamma")).is_ok());
+        assert!(query_parser.parse_query(match_query("alpha beta gamma delta")).is_err());
+
+        let regex = |value: &str| {
+            proto::query::Query::Regex(proto::RegexQuery {
+                field: "title".to_string(),
+                value: value.to_string(),
+            })
+        };
+        assert!(query_parser.parse_query(regex("alpha.*")).is_ok());
+        assert!(query_parser.parse_query(regex("[a-z]{10000}")).is_err());
+
+        let search_limits = SearchLimits(proto::SearchLimits {
+            max_collectors: Some(1),
+            max_limit: Some(100),
+            ..Default::default()
+        });
+        let top_docs = |limit| proto::Collector {
+            collector: Some(proto::collector::Collector::TopDocs(proto::TopDocsCollector { limit, ..Default::default() })),
+        };
+        assert!(search_limits.check_collectors(&[top_docs(100)]).is_ok());
+        assert!(search_limits.check_collectors(&[top_docs(101)]).is_err());
+        assert!(search_limits.check_collectors(&[top_docs(10), top_docs(10)]).is_err());
     }
 }
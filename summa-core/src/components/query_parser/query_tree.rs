@@ -0,0 +1,206 @@
+//! Structured representation of parsed `tantivy` queries
+
+use summa_proto::proto;
+use tantivy::json_utils::json_path_sep_to_dot;
+use tantivy::query::{
+    AllQuery, BooleanQuery, BoostQuery, DisjunctionMaxQuery, EmptyQuery, MoreLikeThisQuery, Occur, PhraseQuery, Query, RangeQuery, RegexQuery, TermQuery,
+};
+use tantivy::schema::{Schema, Type, ValueBytes};
+use tantivy::Term;
+use tantivy_common::json_path_writer::JSON_END_OF_PATH;
+
+use crate::components::queries::{ExistsQuery, VectorQuery};
+
+fn new_node(node_type: &str) -> proto::ParsedQueryNode {
+    proto::ParsedQueryNode {
+        node_type: node_type.to_string(),
+        ..Default::default()
+    }
+}
+
+fn occur_to_proto(occur: Occur) -> proto::Occur {
+    match occur {
+        Occur::Should => proto::Occur::Should,
+        Occur::Must => proto::Occur::Must,
+        Occur::MustNot => proto::Occur::MustNot,
+    }
+}
+
+/// Full name of the field of the term including path inside JSON field
+fn term_field_name(schema: &Schema, term: &Term) -> String {
+    let mut full_path = term.get_full_path(schema);
+    json_path_sep_to_dot(&mut full_path);
+    full_path
+}
+
+fn term_text(term: &Term) -> String {
+    let value = term.value();
+    // Values of JSON terms follow their paths
+    let value = match value.typ() {
+        Type::Json => {
+            let bytes = &value.as_serialized()[1..];
+            match bytes.iter().position(|byte| *byte == JSON_END_OF_PATH) {
+                Some(end_of_path) => ValueBytes::wrap(&bytes[end_of_path + 1..]),
+                None => value,
+            }
+        }
+        _ => value,
+    };
+    value
+        .as_str()
+        .map(str::to_string)
+        .or_else(|| value.as_u64().map(|value| value.to_string()))
+        .or_else(|| value.as_i64().map(|value| value.to_string()))
+        .or_else(|| value.as_f64().map(|value| value.to_string()))
+        .or_else(|| value.as_bool().map(|value| value.to_string()))
+        .or_else(|| value.as_date().map(|value| value.into_timestamp_secs().to_string()))
+        .or_else(|| value.as_facet().map(|value| value.to_string()))
+        .or_else(|| value.as_ip_addr().map(|value| value.to_string()))
+        .unwrap_or_else(|| value.as_serialized().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Node of the query which structure is not exposed by `tantivy`, i.e. boosts, slops, bounds and nested queries
+///
+/// The node keeps terms of the query and its debug representation as the description
+fn opaque_node(schema: &Schema, node_type: &str, query: &dyn Query) -> proto::ParsedQueryNode {
+    let mut node = new_node(node_type);
+    query.query_terms(&mut |term, _| {
+        node.field.get_or_insert_with(|| term_field_name(schema, term));
+        node.terms.push(term_text(term));
+    });
+    node.description = Some(format!("{query:?}"));
+    node
+}
+
+/// Converts parsed query into the tree of `ParsedQueryNode`
+///
+/// Queries of unknown types are returned as `unknown` nodes containing their terms
+pub fn query_to_tree(schema: &Schema, query: &dyn Query) -> proto::ParsedQueryNode {
+    let any_query = query.as_any();
+    if let Some(boolean_query) = any_query.downcast_ref::<BooleanQuery>() {
+        proto::ParsedQueryNode {
+            children: boolean_query
+                .clauses()
+                .iter()
+                .map(|(occur, subquery)| proto::ParsedQueryNode {
+                    occur: Some(occur_to_proto(*occur) as i32),
+                    ..query_to_tree(schema, subquery.as_ref())
+                })
+                .collect(),
+            ..new_node("boolean")
+        }
+    } else if let Some(term_query) = any_query.downcast_ref::<TermQuery>() {
+        proto::ParsedQueryNode {
+            field: Some(term_field_name(schema, term_query.term())),
+            terms: vec![term_text(term_query.term())],
+            ..new_node("term")
+        }
+    } else if any_query.is::<PhraseQuery>() {
+        opaque_node(schema, "phrase", query)
+    } else if any_query.is::<BoostQuery>() {
+        opaque_node(schema, "boost", query)
+    } else if any_query.is::<DisjunctionMaxQuery>() {
+        opaque_node(schema, "disjunction_max", query)
+    } else if let Some(range_query) = any_query.downcast_ref::<RangeQuery>() {
+        proto::ParsedQueryNode {
+            field: Some(schema.get_field_name(range_query.field()).to_string()),
+            ..opaque_node(schema, "range", query)
+        }
+    } else if any_query.is::<RegexQuery>() {
+        opaque_node(schema, "regex", query)
+    } else if let Some(exists_query) = any_query.downcast_ref::<ExistsQuery>() {
+        let field_name = schema.get_field_name(exists_query.field());
+        proto::ParsedQueryNode {
+            field: Some(match exists_query.full_path() {
+                "" => field_name.to_string(),
+                full_path => format!("{field_name}.{full_path}"),
+            }),
+            ..new_node("exists")
+        }
+    } else if let Some(vector_query) = any_query.downcast_ref::<VectorQuery>() {
+        proto::ParsedQueryNode {
+            field: Some(vector_query.field_name().to_string()),
+            children: vector_query.filter().map(|filter| query_to_tree(schema, filter)).into_iter().collect(),
+            ..new_node("vector")
+        }
+    } else if any_query.is::<AllQuery>() {
+        new_node("all")
+    } else if any_query.is::<EmptyQuery>() {
+        new_node("empty")
+    } else if any_query.is::<MoreLikeThisQuery>() {
+        new_node("more_like_this")
+    } else {
+        opaque_node(schema, "unknown", query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use summa_proto::proto;
+    use tantivy::query::{BooleanQuery, BoostQuery, DisjunctionMaxQuery, Occur, PhraseQuery, RangeQuery, TermQuery};
+    use tantivy::schema::{IndexRecordOption, Schema, INDEXED, TEXT};
+    use tantivy::Term;
+
+    use super::*;
+
+    #[test]
+    fn test_query_to_tree() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let year = schema_builder.add_u64_field("year", INDEXED);
+        let schema = schema_builder.build();
+
+        let term_query = |field, text| Box::new(TermQuery::new(Term::from_field_text(field, text), IndexRecordOption::WithFreqs)) as Box<dyn Query>;
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(BoostQuery::new(term_query(title, "search"), 2.0)) as Box<dyn Query>),
+            (
+                Occur::Should,
+                Box::new(DisjunctionMaxQuery::with_tie_breaker(
+                    vec![term_query(title, "\"quoted\""), term_query(body, "engine")],
+                    0.5,
+                )),
+            ),
+            (
+                Occur::MustNot,
+                Box::new(PhraseQuery::new_with_offset_and_slop(
+                    vec![(0, Term::from_field_text(body, "search")), (1, Term::from_field_text(body, "engine"))],
+                    2,
+                )),
+            ),
+            (
+                Occur::Must,
+                Box::new(RangeQuery::new(Bound::Included(Term::from_field_u64(year, 2000)), Bound::Unbounded)),
+            ),
+        ]);
+        let tree = query_to_tree(&schema, &query);
+        assert_eq!(tree.node_type, "boolean");
+        assert_eq!(tree.children.len(), 4);
+
+        let boost = &tree.children[0];
+        assert_eq!(boost.occur, Some(proto::Occur::Must as i32));
+        assert_eq!(boost.node_type, "boost");
+        assert_eq!(boost.field.as_deref(), Some("title"));
+        assert_eq!(boost.terms, vec!["search".to_string()]);
+        assert!(boost.description.as_deref().unwrap().ends_with("boost=2)"));
+
+        let disjunction_max = &tree.children[1];
+        assert_eq!(disjunction_max.node_type, "disjunction_max");
+        assert_eq!(disjunction_max.terms, vec!["\"quoted\"".to_string(), "engine".to_string()]);
+        assert!(disjunction_max.description.as_deref().unwrap().contains("tie_breaker: 0.5"));
+
+        let phrase = &tree.children[2];
+        assert_eq!(phrase.node_type, "phrase");
+        assert_eq!(phrase.occur, Some(proto::Occur::MustNot as i32));
+        assert_eq!(phrase.field.as_deref(), Some("body"));
+        assert_eq!(phrase.terms, vec!["search".to_string(), "engine".to_string()]);
+        assert!(phrase.description.as_deref().unwrap().contains("slop: 2"));
+
+        let range = &tree.children[3];
+        assert_eq!(range.node_type, "range");
+        assert_eq!(range.field.as_deref(), Some("year"));
+        assert!(range.terms.is_empty());
+    }
+}
@@ -2,12 +2,14 @@ use std::collections::Bound;
 use std::ops::Bound::{Included, Unbounded};
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
 use pest::iterators::{Pair, Pairs};
 use pest::Parser;
 use pest_derive::Parser;
+use summa_proto::proto;
 use tantivy::query::{BooleanQuery, BoostQuery, DisjunctionMaxQuery, EmptyQuery, PhraseQuery, Query, QueryClone, RangeQuery, RegexQuery, TermQuery};
 use tantivy::schema::{Facet, FacetParseError, Field, FieldEntry, FieldType, IndexRecordOption, Schema, TextFieldIndexing, Type};
 use tantivy::tokenizer::{TextAnalyzer, TokenizerManager};
@@ -32,7 +34,7 @@ pub struct QueryParser {
     schema: Schema,
     tokenizer_manager: TokenizerManager,
     morphology_manager: MorphologyManager,
-    term_field_mappers_manager: TermFieldMappersManager,
+    term_field_mappers_manager: Arc<TermFieldMappersManager>,
     query_parser_config: QueryParserConfig,
 }
 
@@ -189,10 +191,26 @@ impl QueryParser {
         query_parser_config: QueryParserConfig,
         morphology_manager: &MorphologyManager,
         tokenizer_manager: &TokenizerManager,
+    ) -> SummaResult<QueryParser> {
+        let term_field_mappers_manager = Arc::new(TermFieldMappersManager::new(
+            &schema,
+            tokenizer_manager,
+            &query_parser_config.0.term_field_mapper_configs,
+        )?);
+        QueryParser::with_term_field_mappers_manager(schema, query_parser_config, morphology_manager, tokenizer_manager, term_field_mappers_manager)
+    }
+
+    /// Creates parser reusing term field mappers compiled for the same `term_field_mapper_configs`
+    pub(crate) fn with_term_field_mappers_manager(
+        schema: Schema,
+        query_parser_config: QueryParserConfig,
+        morphology_manager: &MorphologyManager,
+        tokenizer_manager: &TokenizerManager,
+        term_field_mappers_manager: Arc<TermFieldMappersManager>,
     ) -> SummaResult<QueryParser> {
         validators::parse_fields(&schema, &query_parser_config.0.default_fields, &[])?;
         Ok(QueryParser {
-            term_field_mappers_manager: TermFieldMappersManager::new(&schema, tokenizer_manager),
+            term_field_mappers_manager,
             morphology_manager: morphology_manager.clone(),
             tokenizer_manager: tokenizer_manager.clone(),
             query_parser_config,
@@ -213,6 +231,24 @@ impl QueryParser {
             .unwrap_or(field_name)
     }
 
+    /// Checks if `field_name` or, if `match_root` is set, its first path segment is listed in `excluded_fields`
+    fn is_excluded_field(&self, field_name: &str, match_root: bool, warnings: &mut Vec<proto::QueryWarning>) -> bool {
+        let is_excluded = self
+            .query_parser_config
+            .0
+            .excluded_fields
+            .iter()
+            .any(|x| x == field_name || match_root && Some(x.as_str()) == field_name.split('.').next());
+        if is_excluded {
+            warnings.push(proto::QueryWarning {
+                kind: proto::QueryWarningKind::ExcludedField.into(),
+                field: Some(field_name.to_string()),
+                term: None,
+            });
+        }
+        is_excluded
+    }
+
     fn get_text_analyzer(&self, field_entry: &FieldEntry, option: &TextFieldIndexing) -> Result<TextAnalyzer, QueryParserError> {
         self.tokenizer_manager
             .get(option.tokenizer())
@@ -222,7 +258,7 @@ impl QueryParser {
             })
     }
 
-    fn default_field_queries(&self, term: Pair<Rule>, boost: Option<f32>) -> Result<Box<dyn Query>, QueryParserError> {
+    fn default_field_queries(&self, term: Pair<Rule>, boost: Option<f32>, warnings: &mut Vec<proto::QueryWarning>) -> Result<Box<dyn Query>, QueryParserError> {
         let (occur, term) = match term.as_rule() {
             Rule::field_name => (Occur::Should, term),
             _ => {
@@ -240,7 +276,7 @@ impl QueryParser {
             .iter()
             .map(|field| {
                 let (field, full_path) = self.schema.find_field(field).expect("inconsistent state");
-                self.parse_pre_term(&field, full_path, term.clone(), boost, true)
+                self.parse_pre_term(&field, full_path, term.clone(), boost, true, warnings)
             })
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -309,6 +345,7 @@ impl QueryParser {
         pre_term: Pair<Rule>,
         boost: Option<f32>,
         ignore_phrase_for_non_position_field: bool,
+        warnings: &mut Vec<proto::QueryWarning>,
     ) -> Result<Vec<Box<dyn Query>>, QueryParserError> {
         let field_entry = self.schema.get_field_entry(*field);
         let field_type = field_entry.field_type();
@@ -424,6 +461,13 @@ impl QueryParser {
                             };
                             queries.push(boost_query(query, boost))
                         });
+                        if queries.is_empty() {
+                            warnings.push(proto::QueryWarning {
+                                kind: proto::QueryWarningKind::DroppedTerm.into(),
+                                field: Some(field_entry.name().to_string()),
+                                term: Some(pre_term.as_str().to_string()),
+                            });
+                        }
                         Ok(queries)
                     }
                     Rule::phrase => {
@@ -441,6 +485,13 @@ impl QueryParser {
                             })
                             .unwrap_or(0);
                         let terms = self.parse_words(*field, full_path, indexing, words.as_str())?;
+                        if terms.is_empty() {
+                            warnings.push(proto::QueryWarning {
+                                kind: proto::QueryWarningKind::DroppedTerm.into(),
+                                field: Some(field_entry.name().to_string()),
+                                term: Some(words.as_str().to_string()),
+                            });
+                        }
                         if terms.len() <= 1 {
                             return Ok(terms
                                 .into_iter()
@@ -454,6 +505,11 @@ impl QueryParser {
                             let query = Box::new(PhraseQuery::new_with_offset_and_slop(terms, slop)) as Box<dyn Query>;
                             Ok(vec![boost_query(query, boost)])
                         } else if ignore_phrase_for_non_position_field {
+                            warnings.push(proto::QueryWarning {
+                                kind: proto::QueryWarningKind::DroppedPhrase.into(),
+                                field: Some(field_entry.name().to_string()),
+                                term: Some(words.as_str().to_string()),
+                            });
                             Ok(vec![])
                         } else {
                             Err(QueryParserError::FieldDoesNotHavePositionsIndexed(field_entry.name().to_string()))
@@ -483,12 +539,19 @@ impl QueryParser {
         }
     }
 
-    fn parse_term(&self, term: Pair<Rule>, field: &Field, full_path: &str, boost: Option<f32>) -> Result<Box<dyn Query>, QueryParserError> {
+    fn parse_term(
+        &self,
+        term: Pair<Rule>,
+        field: &Field,
+        full_path: &str,
+        boost: Option<f32>,
+        warnings: &mut Vec<proto::QueryWarning>,
+    ) -> Result<Box<dyn Query>, QueryParserError> {
         let term = term.into_inner().next().expect("grammar failure");
         let occur = self.parse_occur(&term);
         let pre_term = term.into_inner().next().expect("grammar failure");
         Ok(Box::new(BooleanQuery::new(
-            self.parse_pre_term(field, full_path, pre_term, boost, false)?
+            self.parse_pre_term(field, full_path, pre_term, boost, false, warnings)?
                 .into_iter()
                 .map(|q| (occur, q))
                 .collect(),
@@ -562,10 +625,10 @@ impl QueryParser {
         })
     }
 
-    fn extract_top_level_phrase(&self, pairs: Pairs<Rule>) -> Option<String> {
+    fn extract_top_level_phrase(&self, pairs: &[Pair<Rule>]) -> Option<String> {
         let mut terms = vec![];
         for pair in pairs {
-            let mut statement_pairs = pair.into_inner();
+            let mut statement_pairs = pair.clone().into_inner();
             let search_group_or_term = statement_pairs.next().expect("grammar failure");
             let boost = statement_pairs.next().map(|boost| f32::from_str(boost.as_str()).expect("grammar failure"));
             match (search_group_or_term.as_rule(), boost) {
@@ -583,7 +646,7 @@ impl QueryParser {
         (!terms.is_empty()).then(|| terms.join(" "))
     }
 
-    fn parse_statement(&self, pair: Pair<Rule>) -> Result<Box<dyn Query>, QueryParserError> {
+    fn parse_statement(&self, pair: Pair<Rule>, warnings: &mut Vec<proto::QueryWarning>) -> Result<Box<dyn Query>, QueryParserError> {
         let mut statement_pairs = pair.into_inner();
         let isbn_doi_or_search_group_or_grouping_or_term = statement_pairs.next().expect("grammar failure");
         let statement_boost = statement_pairs.next().map(|boost| f32::from_str(boost.as_str()).expect("grammar failure"));
@@ -601,16 +664,16 @@ impl QueryParser {
                         match self.schema.find_field(resolved_field_name) {
                             Some((field, full_path)) => {
                                 for term in grouping.into_inner() {
-                                    intermediate_results.push(self.parse_term(term, &field, full_path, statement_boost)?);
+                                    intermediate_results.push(self.parse_term(term, &field, full_path, statement_boost, warnings)?);
                                 }
                             }
                             None => {
-                                if self.query_parser_config.0.excluded_fields.iter().any(|x| x == field_name.as_str()) {
+                                if self.is_excluded_field(field_name.as_str(), false, warnings) {
                                     return Ok(Box::new(EmptyQuery {}));
                                 }
-                                intermediate_results.push(self.default_field_queries(field_name, statement_boost)?);
+                                intermediate_results.push(self.default_field_queries(field_name, statement_boost, warnings)?);
                                 for term in grouping.into_inner() {
-                                    intermediate_results.push(self.default_field_queries(term, statement_boost)?)
+                                    intermediate_results.push(self.default_field_queries(term, statement_boost, warnings)?)
                                 }
                             }
                         }
@@ -624,20 +687,14 @@ impl QueryParser {
                     Rule::term => {
                         let resolved_field_name = self.resolve_field_name(field_name.as_str());
                         match self.schema.find_field(resolved_field_name) {
-                            Some((field, full_path)) => self.parse_term(grouping_or_term, &field, full_path, statement_boost),
+                            Some((field, full_path)) => self.parse_term(grouping_or_term, &field, full_path, statement_boost, warnings),
                             None => {
-                                if self
-                                    .query_parser_config
-                                    .0
-                                    .excluded_fields
-                                    .iter()
-                                    .any(|x| x == field_name.as_str() || Some(x.as_str()) == field_name.as_str().split('.').next())
-                                {
+                                if self.is_excluded_field(field_name.as_str(), true, warnings) {
                                     Ok(Box::new(EmptyQuery {}) as Box<dyn Query>)
                                 } else {
                                     Ok(Box::new(BooleanQuery::new(vec![
-                                        (Occur::Should, self.default_field_queries(field_name, statement_boost)?),
-                                        (Occur::Should, self.default_field_queries(grouping_or_term, statement_boost)?),
+                                        (Occur::Should, self.default_field_queries(field_name, statement_boost, warnings)?),
+                                        (Occur::Should, self.default_field_queries(grouping_or_term, statement_boost, warnings)?),
                                     ])) as Box<dyn Query>)
                                 }
                             }
@@ -648,16 +705,10 @@ impl QueryParser {
                         match self.schema.find_field(resolved_field_name) {
                             Some((field, full_path)) => Ok(Box::new(ExistsQuery::new(field, full_path)) as Box<dyn Query>),
                             None => {
-                                if self
-                                    .query_parser_config
-                                    .0
-                                    .excluded_fields
-                                    .iter()
-                                    .any(|x| x == field_name.as_str() || Some(x.as_str()) == field_name.as_str().split('.').next())
-                                {
+                                if self.is_excluded_field(field_name.as_str(), true, warnings) {
                                     Ok(Box::new(EmptyQuery {}) as Box<dyn Query>)
                                 } else {
-                                    Ok(self.default_field_queries(field_name, statement_boost)?)
+                                    Ok(self.default_field_queries(field_name, statement_boost, warnings)?)
                                 }
                             }
                         }
@@ -679,6 +730,13 @@ impl QueryParser {
                     }
                 }
 
+                if queries.is_empty() {
+                    warnings.push(proto::QueryWarning {
+                        kind: proto::QueryWarningKind::UnmappedTerm.into(),
+                        field: None,
+                        term: Some(isbn_doi_or_search_group_or_grouping_or_term.as_str().to_string()),
+                    });
+                }
                 Ok(Box::new(BooleanQuery::new(queries)) as Box<dyn Query>)
             }
             Rule::isbn => {
@@ -695,15 +753,22 @@ impl QueryParser {
                     }
                 }
 
+                if queries.is_empty() {
+                    warnings.push(proto::QueryWarning {
+                        kind: proto::QueryWarningKind::UnmappedTerm.into(),
+                        field: None,
+                        term: Some(isbn_doi_or_search_group_or_grouping_or_term.as_str().to_string()),
+                    });
+                }
                 Ok(Box::new(BooleanQuery::new(queries)) as Box<dyn Query>)
             }
-            Rule::term => self.default_field_queries(isbn_doi_or_search_group_or_grouping_or_term, statement_boost),
+            Rule::term => self.default_field_queries(isbn_doi_or_search_group_or_grouping_or_term, statement_boost, warnings),
             Rule::grouping => {
                 let grouping = isbn_doi_or_search_group_or_grouping_or_term.into_inner().next().expect("grammar failure");
                 let occur = self.parse_occur(&grouping);
                 let mut intermediate_results = vec![];
                 for term in grouping.into_inner() {
-                    intermediate_results.push(self.default_field_queries(term, statement_boost)?)
+                    intermediate_results.push(self.default_field_queries(term, statement_boost, warnings)?)
                 }
                 let group_query = Box::new(BooleanQuery::new(intermediate_results.into_iter().map(|q| (Occur::Should, q)).collect())) as Box<dyn Query>;
                 match occur {
@@ -717,15 +782,56 @@ impl QueryParser {
         Ok(statement_result)
     }
 
-    fn parse_statements(&self, pairs: Pairs<Rule>) -> Result<Box<dyn Query>, QueryParserError> {
-        let mut subqueries = Subqueries::new();
+    /// Maps statements matched by user-defined term field mappers and parses the rest of `mapped_statement`s as regular statements
+    ///
+    /// Mapped words may contain characters that are statement separators for the grammar (i.e. `arXiv:2101.00001`),
+    /// so they are matched against mappers as a whole
+    fn map_statements<'a>(&self, pairs: Pairs<'a, Rule>) -> Result<(Subqueries, Vec<Pair<'a, Rule>>), QueryParserError> {
+        let mut mapped_subqueries = Subqueries::new();
+        let mut statements = vec![];
+        for pair in pairs {
+            if pair.as_rule() != Rule::mapped_statement {
+                statements.push(pair);
+                continue;
+            }
+            let statement = pair.as_str();
+            let mut statement_pairs = pair.into_inner();
+            let mapped_term = statement_pairs.next().expect("grammar failure");
+            let statement_boost = statement_pairs.next().map(|boost| f32::from_str(boost.as_str()).expect("grammar failure"));
+            let occur = match mapped_term.as_str().chars().next() {
+                Some('+') => Occur::Must,
+                Some('-') => Occur::MustNot,
+                _ => Occur::Should,
+            };
+            let mapped_word = mapped_term.into_inner().next().expect("grammar failure");
+            match self.term_field_mappers_manager.map_by_regex_mappers(mapped_word.as_str()) {
+                Some(query) => {
+                    let query = boost_query(query, statement_boost);
+                    mapped_subqueries.push(match occur {
+                        Occur::Should => (Occur::Should, query),
+                        occur => (Occur::Should, Box::new(BooleanQuery::new(vec![(occur, query)])) as Box<dyn Query>),
+                    })
+                }
+                None => statements.extend(SummaQlParser::parse(Rule::main, statement).map_err(Box::new)?),
+            }
+        }
+        Ok((mapped_subqueries, statements))
+    }
 
-        for pair in pairs.clone() {
-            let parsed_queries = self.parse_statement(pair)?;
+    fn parse_statements(
+        &self,
+        pairs: Vec<Pair<Rule>>,
+        mapped_subqueries: Subqueries,
+        warnings: &mut Vec<proto::QueryWarning>,
+    ) -> Result<Box<dyn Query>, QueryParserError> {
+        let mut subqueries = mapped_subqueries;
+
+        for pair in pairs.iter().cloned() {
+            let parsed_queries = self.parse_statement(pair, warnings)?;
             subqueries.push((Occur::Should, parsed_queries));
         }
 
-        if let Some(top_level_phrase) = self.extract_top_level_phrase(pairs) {
+        if let Some(top_level_phrase) = self.extract_top_level_phrase(&pairs) {
             if let Some(exact_matches_promoter) = &self.query_parser_config.0.exact_matches_promoter {
                 let fields = if exact_matches_promoter.fields.is_empty() {
                     self.query_parser_config.0.default_fields.iter()
@@ -770,12 +876,33 @@ impl QueryParser {
                 )
             }
         }
-        Ok(Box::new(BooleanQuery::new(subqueries.into_iter().take(self.query_parser_config.term_limit()).collect())) as Box<dyn Query>)
+        let term_limit = self.query_parser_config.term_limit();
+        if subqueries.len() > term_limit {
+            warnings.push(proto::QueryWarning {
+                kind: proto::QueryWarningKind::TermLimitTruncation.into(),
+                field: None,
+                term: None,
+            });
+        }
+        Ok(Box::new(BooleanQuery::new(subqueries.into_iter().take(term_limit).collect())) as Box<dyn Query>)
     }
 
     pub fn parse_query(&self, query: &str) -> Result<Box<dyn Query>, QueryParserError> {
-        let pairs = SummaQlParser::parse(Rule::main, query).map_err(Box::new)?;
-        Ok(reduce_empty_queries(reduce_should_clause(self.parse_statements(pairs)?)))
+        self.parse_query_with_warnings(query, &mut vec![])
+    }
+
+    /// Parses query and collects warnings about dropped or truncated parts into `warnings`
+    pub fn parse_query_with_warnings(&self, query: &str, warnings: &mut Vec<proto::QueryWarning>) -> Result<Box<dyn Query>, QueryParserError> {
+        let (mapped_subqueries, pairs) = if self.term_field_mappers_manager.has_regex_mappers() {
+            self.map_statements(SummaQlParser::parse(Rule::mapped_main, query).map_err(Box::new)?)?
+        } else {
+            (Subqueries::new(), SummaQlParser::parse(Rule::main, query).map_err(Box::new)?.collect())
+        };
+        Ok(reduce_empty_queries(reduce_should_clause(self.parse_statements(
+            pairs,
+            mapped_subqueries,
+            warnings,
+        )?)))
     }
 }
 
@@ -871,18 +998,21 @@ mod tests {
             "doi".to_string(),
             proto::TermFieldMapperConfig {
                 fields: vec!["doi".to_string()],
+                ..Default::default()
             },
         );
         query_parser.query_parser_config.0.term_field_mapper_configs.insert(
             "doi_isbn".to_string(),
             proto::TermFieldMapperConfig {
                 fields: vec!["metadata.isbns".to_string()],
+                ..Default::default()
             },
         );
         query_parser.query_parser_config.0.term_field_mapper_configs.insert(
             "isbn".to_string(),
             proto::TermFieldMapperConfig {
                 fields: vec!["metadata.isbns".to_string()],
+                ..Default::default()
             },
         );
         assert_eq!(
@@ -1199,4 +1329,88 @@ mod tests {
         let query = query_parser.parse_query("(test1 test2) -(test3) +(test4 test5)");
         assert_eq!(format!("{:?}", query), "Ok(BooleanQuery { subqueries: [(Should, TermQuery(Term(field=0, type=Str, \"test1\"))), (Should, TermQuery(Term(field=0, type=Str, \"test2\"))), (MustNot, TermQuery(Term(field=0, type=Str, \"test3\"))), (Must, BooleanQuery { subqueries: [(Should, TermQuery(Term(field=0, type=Str, \"test4\"))), (Should, TermQuery(Term(field=0, type=Str, \"test5\")))] })] })");
     }
+
+    #[test]
+    pub fn test_warnings() {
+        let mut query_parser = create_query_parser();
+        query_parser.query_parser_config.0.excluded_fields = vec!["secret".to_string()];
+        query_parser.query_parser_config.0.term_limit = 2;
+        let mut warnings = vec![];
+        query_parser
+            .parse_query_with_warnings("secret:test search engine query", &mut warnings)
+            .expect("cannot parse");
+        assert_eq!(
+            warnings,
+            vec![
+                proto::QueryWarning {
+                    kind: proto::QueryWarningKind::ExcludedField.into(),
+                    field: Some("secret".to_string()),
+                    term: None,
+                },
+                proto::QueryWarning {
+                    kind: proto::QueryWarningKind::TermLimitTruncation.into(),
+                    field: None,
+                    term: None,
+                }
+            ]
+        );
+        let mut warnings = vec![];
+        query_parser.parse_query_with_warnings("search", &mut warnings).expect("cannot parse");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    pub fn test_regex_term_field_mappers() {
+        let tokenizer_manager = TokenizerManager::default();
+        let morphology_manager = MorphologyManager::default();
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("id", STRING);
+        let schema = schema_builder.build();
+        let mut term_field_mapper_configs = HashMap::new();
+        term_field_mapper_configs.insert(
+            "arxiv".to_string(),
+            proto::TermFieldMapperConfig {
+                fields: vec!["id".to_string()],
+                regex: Some(proto::RegexTermFieldMapper {
+                    pattern: r"(?i)arxiv:(?P<id>\d{4}\.\d{4,5})(v\d+)?".to_string(),
+                    template: "arxiv:${id}".to_string(),
+                }),
+            },
+        );
+        let query_parser_config = QueryParserConfig(proto::QueryParserConfig {
+            default_fields: vec!["title".to_string()],
+            term_field_mapper_configs,
+            ..Default::default()
+        });
+        let query_parser =
+            QueryParser::new(schema.clone(), query_parser_config.clone(), &morphology_manager, &tokenizer_manager).expect("cannot create parser");
+        assert_eq!(
+            format!("{:?}", query_parser.parse_query("arXiv:2101.00001v2")),
+            "Ok(TermQuery(Term(field=1, type=Str, \"arxiv:2101.00001\")))"
+        );
+        assert_eq!(
+            format!("{:?}", query_parser.parse_query("arxiv")),
+            "Ok(TermQuery(Term(field=0, type=Str, \"arxiv\")))"
+        );
+        let query = query_parser.parse_query("deep arxiv:2101.00001");
+        assert_eq!(
+            format!("{:?}", query),
+            "Ok(BooleanQuery { subqueries: [(Should, TermQuery(Term(field=1, type=Str, \"arxiv:2101.00001\"))), (Should, TermQuery(Term(field=0, type=Str, \"deep\")))], minimum_number_should_match: 1 })"
+        );
+
+        let query = format!("{:?}", query_parser.parse_query("-arXiv:2101.00001 deep"));
+        assert!(query.contains("(MustNot, TermQuery(Term(field=1, type=Str, \"arxiv:2101.00001\")))"));
+        let query = format!("{:?}", query_parser.parse_query("\"deep arXiv:2101.00001\""));
+        assert!(!query.contains("field=1"));
+
+        let mut invalid_query_parser_config = query_parser_config;
+        invalid_query_parser_config
+            .0
+            .term_field_mapper_configs
+            .get_mut("arxiv")
+            .expect("no mapper")
+            .fields = vec!["unknown".to_string()];
+        assert!(QueryParser::new(schema, invalid_query_parser_config, &morphology_manager, &tokenizer_manager).is_err());
+    }
 }
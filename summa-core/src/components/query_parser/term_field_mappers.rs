@@ -1,14 +1,17 @@
 use std::collections::HashMap;
 
 use regex::Regex;
+use summa_proto::proto;
 use tantivy::query::{BooleanQuery, Occur, Query};
 use tantivy::schema::{Field, FieldType, Schema};
 use tantivy::tokenizer::TokenizerManager;
 use tantivy::Term;
 
 use crate::components::query_parser::utils::cast_field_to_term;
+use crate::errors::{Error, SummaResult};
+use crate::validators;
 
-pub trait TermFieldMapper {
+pub trait TermFieldMapper: Send + Sync {
     fn map(&self, value: &str, fields: &[String]) -> Option<Box<dyn Query>>;
 }
 
@@ -118,12 +121,66 @@ impl TermFieldMapper for IsbnMapper {
     }
 }
 
+/// User-defined mapper matching the whole value against the regex and looking up normalized value in the fields
+pub struct RegexMapper {
+    schema: Schema,
+    tokenizer_manager: TokenizerManager,
+    regex: Regex,
+    template: String,
+    fields: Vec<String>,
+}
+
+impl RegexMapper {
+    pub fn new(
+        schema: Schema,
+        tokenizer_manager: TokenizerManager,
+        regex_term_field_mapper: &proto::RegexTermFieldMapper,
+        fields: Vec<String>,
+    ) -> SummaResult<Self> {
+        let regex = Regex::new(&format!("^(?:{})$", regex_term_field_mapper.pattern))
+            .map_err(|e| Error::InvalidSyntax(format!("invalid term field mapper pattern {}: {e}", regex_term_field_mapper.pattern)))?;
+        let template = match regex_term_field_mapper.template.as_str() {
+            "" => "$0".to_string(),
+            template => template.to_string(),
+        };
+        Ok(RegexMapper {
+            schema,
+            tokenizer_manager,
+            regex,
+            template,
+            fields,
+        })
+    }
+}
+
+impl TermFieldMapper for RegexMapper {
+    fn map(&self, value: &str, fields: &[String]) -> Option<Box<dyn Query>> {
+        let captures = self.regex.captures(value)?;
+        let mut normalized_value = String::new();
+        captures.expand(&self.template, &mut normalized_value);
+        let terms = fields
+            .iter()
+            .flat_map(|field_name| {
+                let (field, full_path) = self.schema.find_field(field_name).expect("inconsistent state");
+                tokenize_value(&self.schema, &field, full_path, &normalized_value, &self.tokenizer_manager)
+            })
+            .collect();
+        Some(Box::new(BooleanQuery::new_multiterms_query(terms)) as Box<dyn Query>)
+    }
+}
+
+/// Built-in and user-defined term field mappers, user-defined ones are compiled once per query parser config
 pub struct TermFieldMappersManager {
     term_field_mappers: HashMap<String, Box<dyn TermFieldMapper>>,
+    regex_mappers: Vec<(String, RegexMapper)>,
 }
 
 impl TermFieldMappersManager {
-    pub fn new(schema: &Schema, tokenizer_manager: &TokenizerManager) -> Self {
+    pub fn new(
+        schema: &Schema,
+        tokenizer_manager: &TokenizerManager,
+        term_field_mapper_configs: &HashMap<String, proto::TermFieldMapperConfig>,
+    ) -> SummaResult<Self> {
         let mut term_field_mappers = HashMap::new();
         term_field_mappers.insert(
             "doi".to_string(),
@@ -137,10 +194,43 @@ impl TermFieldMappersManager {
             "isbn".to_string(),
             Box::new(IsbnMapper::new(schema.clone(), tokenizer_manager.clone())) as Box<dyn TermFieldMapper>,
         );
-        TermFieldMappersManager { term_field_mappers }
+        let mut regex_mappers = vec![];
+        for (name, term_field_mapper_config) in term_field_mapper_configs {
+            if let Some(regex_term_field_mapper) = &term_field_mapper_config.regex {
+                validators::parse_fields(schema, &term_field_mapper_config.fields, &[])?;
+                regex_mappers.push((
+                    name.to_string(),
+                    RegexMapper::new(
+                        schema.clone(),
+                        tokenizer_manager.clone(),
+                        regex_term_field_mapper,
+                        term_field_mapper_config.fields.clone(),
+                    )?,
+                ));
+            }
+        }
+        regex_mappers.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(TermFieldMappersManager {
+            term_field_mappers,
+            regex_mappers,
+        })
     }
 
     pub fn get(&self, name: &str) -> Option<&Box<dyn TermFieldMapper>> {
         self.term_field_mappers.get(name)
     }
+
+    pub fn has_regex_mappers(&self) -> bool {
+        !self.regex_mappers.is_empty()
+    }
+
+    /// Applies all user-defined mappers to the value and returns the union of produced queries
+    pub fn map_by_regex_mappers(&self, value: &str) -> Option<Box<dyn Query>> {
+        let queries: Vec<_> = self
+            .regex_mappers
+            .iter()
+            .filter_map(|(_, regex_mapper)| Some((Occur::Should, regex_mapper.map(value, &regex_mapper.fields)?)))
+            .collect();
+        (!queries.is_empty()).then(|| Box::new(BooleanQuery::new(queries)) as Box<dyn Query>)
+    }
 }
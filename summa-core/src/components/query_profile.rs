@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use instant::{Duration, Instant};
+use parking_lot::Mutex;
+use summa_proto::proto;
+use tracing::info;
+
+#[derive(Debug, Default)]
+struct QueryProfileInner {
+    parse: Duration,
+    segments: Vec<(u32, Duration)>,
+    fetch: Duration,
+    snippets: Duration,
+    cache_hits: usize,
+    cache_misses: usize,
+}
+
+/// Timings of search phases written to the slow query log
+///
+/// Collecting segments is timed for every segment. Fetching documents and generating snippets run concurrently for
+/// all documents, so their timings are summed over documents and may exceed the elapsed time of the search.
+#[derive(Clone, Debug)]
+pub struct QueryProfile {
+    started_at: Instant,
+    inner: Arc<Mutex<QueryProfileInner>>,
+}
+
+impl Default for QueryProfile {
+    fn default() -> Self {
+        QueryProfile {
+            started_at: Instant::now(),
+            inner: Arc::default(),
+        }
+    }
+}
+
+impl QueryProfile {
+    pub fn record_parse(&self, elapsed: Duration) {
+        self.inner.lock().parse += elapsed
+    }
+
+    pub fn record_segment(&self, segment_ord: u32, elapsed: Duration) {
+        self.inner.lock().segments.push((segment_ord, elapsed))
+    }
+
+    pub fn record_fetch(&self, elapsed: Duration) {
+        self.inner.lock().fetch += elapsed
+    }
+
+    pub fn record_snippets(&self, elapsed: Duration) {
+        self.inner.lock().snippets += elapsed
+    }
+
+    pub fn record_cache(&self, is_hit: bool) {
+        let mut inner = self.inner.lock();
+        if is_hit {
+            inner.cache_hits += 1
+        } else {
+            inner.cache_misses += 1
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Writes the search to the slow query log with `slow_query` target if it took longer than `threshold`
+    pub fn log_if_slow(
+        &self,
+        index_name: &str,
+        search_request: &proto::SearchRequest,
+        collector_outputs: &[proto::CollectorOutput],
+        timed_out: bool,
+        threshold: Duration,
+    ) {
+        let elapsed = self.elapsed();
+        if elapsed < threshold {
+            return;
+        }
+        let inner = self.inner.lock();
+        let segments = inner
+            .segments
+            .iter()
+            .map(|(segment_ord, elapsed)| format!("{segment_ord}:{}", elapsed.as_millis()))
+            .collect::<Vec<_>>();
+        let (documents, count) = result_counts(collector_outputs);
+        info!(
+            target: "slow_query",
+            index_name = index_name,
+            index_alias = search_request.index_alias,
+            elapsed_ms = elapsed.as_millis() as u64,
+            parse_ms = inner.parse.as_millis() as u64,
+            collect_ms = ?segments,
+            fetch_ms = inner.fetch.as_millis() as u64,
+            snippets_ms = inner.snippets.as_millis() as u64,
+            cache_hits = inner.cache_hits,
+            cache_misses = inner.cache_misses,
+            timed_out = timed_out,
+            documents = documents,
+            count = ?count,
+            query = ?search_request.query,
+            collectors = ?search_request.collectors,
+        );
+    }
+}
+
+/// Number of returned documents and the value of the count collector
+fn result_counts(collector_outputs: &[proto::CollectorOutput]) -> (usize, Option<u32>) {
+    let mut documents = 0;
+    let mut count = None;
+    for collector_output in collector_outputs {
+        match &collector_output.collector_output {
+            Some(proto::collector_output::CollectorOutput::Documents(documents_output)) => documents += documents_output.scored_documents.len(),
+            Some(proto::collector_output::CollectorOutput::Count(count_output)) => count = Some(count_output.count),
+            _ => {}
+        }
+    }
+    (documents, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use instant::Duration;
+
+    use super::QueryProfile;
+
+    #[test]
+    fn test_query_profile() {
+        let query_profile = QueryProfile::default();
+        query_profile.clone().record_segment(0, Duration::from_millis(5));
+        query_profile.record_segment(1, Duration::from_millis(7));
+        query_profile.record_fetch(Duration::from_millis(2));
+        query_profile.record_fetch(Duration::from_millis(3));
+        query_profile.record_cache(true);
+        query_profile.record_cache(false);
+        query_profile.record_cache(false);
+
+        let inner = query_profile.inner.lock();
+        assert_eq!(inner.segments, vec![(0, Duration::from_millis(5)), (1, Duration::from_millis(7))]);
+        assert_eq!(inner.fetch, Duration::from_millis(5));
+        assert_eq!((inner.cache_hits, inner.cache_misses), (1, 2));
+    }
+}
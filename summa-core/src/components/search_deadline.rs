@@ -0,0 +1,218 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use instant::{Duration, Instant};
+use tantivy::query::{Explanation, Scorer, Weight};
+use tantivy::{DocId, DocSet, Result, Score, SegmentReader, TERMINATED};
+
+/// Number of documents scored between checks of the deadline
+const CHECK_INTERVAL: u32 = 4096;
+
+#[derive(Debug, Default)]
+struct SearchDeadlineInner {
+    expires_at: Option<Instant>,
+    is_cancellable: AtomicBool,
+    is_cancelled: AtomicBool,
+    is_exceeded: AtomicBool,
+}
+
+/// Bounds the time of the search and allows to cancel it from outside
+///
+/// Expired or cancelled search stops before collecting the next segment and, if the timeout is set or the search is cancellable,
+/// while scoring documents of the current segment. Collectors are merged from documents collected so far and `is_exceeded` reports that results are partial.
+#[derive(Clone, Debug, Default)]
+pub struct SearchDeadline {
+    inner: Arc<SearchDeadlineInner>,
+}
+
+impl SearchDeadline {
+    pub fn new(timeout: Option<Duration>) -> SearchDeadline {
+        SearchDeadline {
+            inner: Arc::new(SearchDeadlineInner {
+                expires_at: timeout.map(|timeout| Instant::now() + timeout),
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn from_timeout_ms(timeout_ms: Option<u32>) -> SearchDeadline {
+        SearchDeadline::new(timeout_ms.map(|timeout_ms| Duration::from_millis(u64::from(timeout_ms))))
+    }
+
+    /// Stops the search at the next check
+    pub fn cancel(&self) {
+        self.inner.is_cancelled.store(true, Ordering::Relaxed)
+    }
+
+    /// Returns guard cancelling the search on drop, i.e. when the future of the request is dropped after client disconnect
+    ///
+    /// Weights wrapped after creating the guard are stopped while scoring documents of the current segment
+    pub fn cancel_on_drop(&self) -> CancelOnDrop {
+        self.inner.is_cancellable.store(true, Ordering::Relaxed);
+        CancelOnDrop { search_deadline: self.clone() }
+    }
+
+    /// Checks if the search must be stopped and marks its results as partial in this case
+    pub fn is_expired(&self) -> bool {
+        if self.inner.is_exceeded.load(Ordering::Relaxed) {
+            return true;
+        }
+        let is_expired = self.inner.is_cancelled.load(Ordering::Relaxed) || self.inner.expires_at.is_some_and(|expires_at| Instant::now() >= expires_at);
+        if is_expired {
+            self.inner.is_exceeded.store(true, Ordering::Relaxed);
+        }
+        is_expired
+    }
+
+    /// Returns `true` if the search has been stopped before all documents were collected
+    pub fn is_exceeded(&self) -> bool {
+        self.inner.is_exceeded.load(Ordering::Relaxed)
+    }
+
+    /// Wraps `weight` for stopping scorers after expiration
+    ///
+    /// Wrapping disables specialized iteration of weights like block-max pruning, so it is done only if the timeout is set
+    /// or the search may be cancelled through `cancel_on_drop`
+    pub fn wrap_weight(&self, weight: Box<dyn Weight>) -> Box<dyn Weight> {
+        if self.inner.expires_at.is_none() && !self.inner.is_cancellable.load(Ordering::Relaxed) {
+            return weight;
+        }
+        Box::new(DeadlineWeight {
+            weight,
+            search_deadline: self.clone(),
+        })
+    }
+}
+
+/// Cancels the search on drop
+pub struct CancelOnDrop {
+    search_deadline: SearchDeadline,
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.search_deadline.cancel()
+    }
+}
+
+struct DeadlineWeight {
+    weight: Box<dyn Weight>,
+    search_deadline: SearchDeadline,
+}
+
+#[async_trait]
+impl Weight for DeadlineWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> Result<Box<dyn Scorer>> {
+        Ok(Box::new(DeadlineScorer::new(self.weight.scorer(reader, boost)?, self.search_deadline.clone())))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        self.weight.explain(reader, doc)
+    }
+
+    async fn scorer_async(&self, reader: &SegmentReader, boost: Score) -> Result<Box<dyn Scorer>> {
+        Ok(Box::new(DeadlineScorer::new(
+            self.weight.scorer_async(reader, boost).await?,
+            self.search_deadline.clone(),
+        )))
+    }
+}
+
+/// Terminates the wrapped scorer once the deadline is expired
+struct DeadlineScorer {
+    scorer: Box<dyn Scorer>,
+    search_deadline: SearchDeadline,
+    until_check: u32,
+    is_terminated: bool,
+}
+
+impl DeadlineScorer {
+    fn new(scorer: Box<dyn Scorer>, search_deadline: SearchDeadline) -> DeadlineScorer {
+        DeadlineScorer {
+            is_terminated: search_deadline.is_expired(),
+            scorer,
+            search_deadline,
+            until_check: CHECK_INTERVAL,
+        }
+    }
+
+    #[inline]
+    fn check(&mut self) -> bool {
+        if self.until_check == 0 {
+            self.until_check = CHECK_INTERVAL;
+            self.is_terminated = self.is_terminated || self.search_deadline.is_expired();
+        } else {
+            self.until_check -= 1;
+        }
+        self.is_terminated
+    }
+}
+
+impl DocSet for DeadlineScorer {
+    fn advance(&mut self) -> DocId {
+        if self.check() {
+            return TERMINATED;
+        }
+        self.scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.check() {
+            return TERMINATED;
+        }
+        self.scorer.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        if self.is_terminated {
+            return TERMINATED;
+        }
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u64 {
+        self.scorer.size_hint()
+    }
+}
+
+impl Scorer for DeadlineScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use instant::Duration;
+    use tantivy::doc;
+    use tantivy::query::{AllQuery, EnableScoring, Query};
+    use tantivy::schema::{Schema, FAST};
+
+    use super::SearchDeadline;
+    use crate::components::test_utils::create_test_index;
+
+    #[test]
+    fn test_search_deadline() {
+        let mut schema_builder = Schema::builder();
+        let rank = schema_builder.add_u64_field("rank", FAST);
+        let index = create_test_index(schema_builder.build(), [(0..10_000u64).map(|i| doc!(rank => i))]);
+        let searcher = index.reader().unwrap().searcher();
+        let segment_reader = searcher.segment_reader(0);
+
+        let search_deadline = SearchDeadline::new(Some(Duration::from_secs(60)));
+        let weight = search_deadline.wrap_weight(AllQuery.weight(EnableScoring::disabled_from_searcher(&searcher)).unwrap());
+        assert_eq!(weight.count(segment_reader).unwrap(), 10_000);
+        assert!(!search_deadline.is_exceeded());
+
+        search_deadline.cancel();
+        assert_eq!(weight.count(segment_reader).unwrap(), 0);
+        assert!(search_deadline.is_exceeded());
+
+        let search_deadline = SearchDeadline::default();
+        let cancel_on_drop = search_deadline.cancel_on_drop();
+        let weight = search_deadline.wrap_weight(AllQuery.weight(EnableScoring::disabled_from_searcher(&searcher)).unwrap());
+        drop(cancel_on_drop);
+        assert!(search_deadline.is_expired());
+        assert_eq!(weight.count(segment_reader).unwrap(), 0);
+    }
+}
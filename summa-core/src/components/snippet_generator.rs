@@ -1,13 +1,28 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Range;
 
 use futures::future::join_all;
+use summa_proto::proto;
 use tantivy::query::{Query, QueryClone};
-use tantivy::Searcher;
+use tantivy::schema::{Field, OwnedValue, Value, ValueBytes};
+use tantivy::snippet::collapse_overlapped_ranges;
+use tantivy::tokenizer::{TextAnalyzer, TokenStream};
+use tantivy::{Score, Searcher, TantivyDocument, Term};
+use tantivy_common::json_path_writer::{JSON_END_OF_PATH, JSON_PATH_SEGMENT_SEP_STR};
+
+use crate::errors::SummaResult;
+use crate::Error;
+
+const DEFAULT_FRAGMENT_SIZE: usize = 150;
+const DEFAULT_PRE_TAG: &str = "<b>";
+const DEFAULT_POST_TAG: &str = "</b>";
 
 pub struct SnippetGeneratorConfig {
     searcher: Searcher,
     query: Box<dyn Query>,
-    snippet_configs: HashMap<String, u32>,
+    snippet_configs: HashMap<String, proto::SnippetConfig>,
 }
 
 impl Clone for SnippetGeneratorConfig {
@@ -21,7 +36,7 @@ impl Clone for SnippetGeneratorConfig {
 }
 
 impl SnippetGeneratorConfig {
-    pub fn new(searcher: Searcher, query: Box<dyn Query>, snippet_configs: HashMap<String, u32>) -> SnippetGeneratorConfig {
+    pub fn new(searcher: Searcher, query: Box<dyn Query>, snippet_configs: HashMap<String, proto::SnippetConfig>) -> SnippetGeneratorConfig {
         SnippetGeneratorConfig {
             searcher,
             query,
@@ -29,30 +44,408 @@ impl SnippetGeneratorConfig {
         }
     }
 
-    pub fn as_tantivy(&self) -> Vec<(String, tantivy::snippet::SnippetGenerator)> {
-        self.snippet_configs
-            .iter()
-            .filter_map(|(field_name, max_num_chars)| {
-                self.searcher.schema().get_field(field_name).ok().map(|snippet_field| {
-                    let mut snippet_generator =
-                        tantivy::snippet::SnippetGenerator::create(&self.searcher, &*self.query, snippet_field).expect("Snippet generator cannot be created");
-                    snippet_generator.set_max_num_chars(*max_num_chars as usize);
-                    (field_name.to_string(), snippet_generator)
-                })
-            })
+    /// Collects texts of the query terms related to the field, `json_path` is empty for non-JSON fields
+    fn query_terms(&self, field: Field, json_path: &str) -> Vec<(Term, String)> {
+        let mut terms = BTreeSet::new();
+        self.query.query_terms(&mut |term, _| {
+            if term.field() == field {
+                terms.insert(term.clone());
+            }
+        });
+        terms
+            .into_iter()
+            .filter_map(|term| term_text(&term, json_path).map(|text| (term, text)))
             .collect()
     }
 
-    pub async fn as_tantivy_async(&self) -> Vec<(String, tantivy::snippet::SnippetGenerator)> {
-        let futures = self.snippet_configs.iter().filter_map(|(field_name, max_num_chars)| {
-            self.searcher.schema().get_field(field_name).ok().map(|snippet_field| async move {
-                let mut snippet_generator = tantivy::snippet::SnippetGenerator::create_async(&self.searcher, &self.query, snippet_field)
-                    .await
-                    .expect("Snippet generator cannot be created");
-                snippet_generator.set_max_num_chars(*max_num_chars as usize);
-                (field_name.to_string(), snippet_generator)
+    pub fn as_generators(&self) -> SummaResult<Vec<(String, SnippetGenerator)>> {
+        let mut snippet_generators = vec![];
+        for (field_name, snippet_config) in &self.snippet_configs {
+            let Some((field, json_path)) = self.searcher.schema().find_field(field_name) else {
+                continue;
+            };
+            let Ok(tokenizer) = self.searcher.index().tokenizer_for_field(field) else {
+                continue;
+            };
+            let mut terms_text = BTreeMap::new();
+            for (term, text) in self.query_terms(field, json_path) {
+                let doc_freq = self.searcher.doc_freq(&term)?;
+                if doc_freq > 0 {
+                    terms_text.insert(text, 1.0 / (1.0 + doc_freq as Score));
+                }
+            }
+            snippet_generators.push((
+                field_name.to_string(),
+                SnippetGenerator::new(field, json_path, terms_text, tokenizer, snippet_config.clone()),
+            ));
+        }
+        Ok(snippet_generators)
+    }
+
+    pub async fn as_generators_async(&self) -> SummaResult<Vec<(String, SnippetGenerator)>> {
+        let futures = self.snippet_configs.iter().filter_map(|(field_name, snippet_config)| {
+            let (field, json_path) = self.searcher.schema().find_field(field_name)?;
+            let tokenizer = self.searcher.index().tokenizer_for_field(field).ok()?;
+            Some(async move {
+                let mut terms_text = BTreeMap::new();
+                for (term, text) in self.query_terms(field, json_path) {
+                    let doc_freq = self.searcher.doc_freq_async(&term).await?;
+                    if doc_freq > 0 {
+                        terms_text.insert(text, 1.0 / (1.0 + doc_freq as Score));
+                    }
+                }
+                Ok::<_, Error>((
+                    field_name.to_string(),
+                    SnippetGenerator::new(field, json_path, terms_text, tokenizer, snippet_config.clone()),
+                ))
             })
         });
-        join_all(futures).await
+        join_all(futures).await.into_iter().collect()
+    }
+}
+
+/// Returns text of the term if it is a string belonging to `json_path`
+fn term_text(term: &Term, json_path: &str) -> Option<String> {
+    match term.get_json_path() {
+        None => term.value().as_str().map(str::to_string),
+        Some(term_json_path) => {
+            if !json_path.is_empty() && term_json_path.replace(JSON_PATH_SEGMENT_SEP_STR, ".") != json_path {
+                return None;
+            }
+            let value_bytes = term.serialized_value_bytes();
+            let end_of_path = value_bytes.iter().position(|byte| *byte == JSON_END_OF_PATH)?;
+            ValueBytes::wrap(&value_bytes[end_of_path + 1..]).as_str().map(str::to_string)
+        }
+    }
+}
+
+/// Collects string values found at `json_path`, arrays are flattened and the whole object is taken if `json_path` is empty
+fn collect_texts(value: &OwnedValue, json_path: &[&str], texts: &mut Vec<String>) {
+    match value {
+        OwnedValue::Str(text) if json_path.is_empty() => texts.push(text.to_string()),
+        OwnedValue::PreTokStr(pre_tokenized_string) if json_path.is_empty() => texts.push(pre_tokenized_string.text.to_string()),
+        OwnedValue::Array(values) => {
+            for value in values {
+                collect_texts(value, json_path, texts)
+            }
+        }
+        OwnedValue::Object(entries) => match json_path.split_first() {
+            Some((key, rest)) => {
+                for (_, value) in entries.iter().filter(|(entry_key, _)| entry_key.as_str() == *key) {
+                    collect_texts(value, rest, texts)
+                }
+            }
+            None => {
+                for (_, value) in entries {
+                    collect_texts(value, json_path, texts)
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns the start of the text not longer than `size` and cut at the word boundary if possible
+fn leading_fragment(text: &str, size: usize) -> &str {
+    if text.len() <= size {
+        return text;
+    }
+    let mut end = size;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    match text[..end].rfind(char::is_whitespace) {
+        Some(whitespace_position) if whitespace_position > 0 => text[..whitespace_position].trim_end(),
+        _ => &text[..end],
+    }
+}
+
+struct FragmentCandidate {
+    score: Score,
+    value_index: usize,
+    start_offset: usize,
+    stop_offset: usize,
+    highlighted: Vec<Range<usize>>,
+}
+
+impl FragmentCandidate {
+    fn new(value_index: usize, start_offset: usize) -> FragmentCandidate {
+        FragmentCandidate {
+            score: 0.0,
+            value_index,
+            start_offset,
+            stop_offset: start_offset,
+            highlighted: vec![],
+        }
+    }
+}
+
+/// Splits the text into fragments of `fragment_size` and returns ones having at least one of the terms
+fn search_fragments(
+    tokenizer: &mut TextAnalyzer,
+    text: &str,
+    value_index: usize,
+    terms: &BTreeMap<String, Score>,
+    fragment_size: usize,
+) -> Vec<FragmentCandidate> {
+    let mut token_stream = tokenizer.token_stream(text);
+    let mut fragment = FragmentCandidate::new(value_index, 0);
+    let mut fragments = vec![];
+    while let Some(token) = token_stream.next() {
+        if token.offset_to - fragment.start_offset > fragment_size {
+            if fragment.score > 0.0 {
+                fragments.push(fragment)
+            }
+            fragment = FragmentCandidate::new(value_index, token.offset_from);
+        }
+        fragment.stop_offset = token.offset_to;
+        // Terms of the query are produced by the same analyzer, so tokens are matched as is
+        if let Some(score) = terms.get(&token.text) {
+            fragment.score += score;
+            fragment.highlighted.push(token.offset_from..token.offset_to);
+        }
+    }
+    // Add trailing punctuation
+    if text.len() - fragment.start_offset < fragment_size {
+        fragment.stop_offset = text.len();
+    }
+    if fragment.score > 0.0 {
+        fragments.push(fragment)
+    }
+    fragments
+}
+
+/// Generates snippets for a field of documents
+///
+/// In contrast to `tantivy::snippet::SnippetGenerator` it can return several fragments, treats values of multi-valued fields
+/// separately and can extract values from sub-paths of JSON fields
+#[derive(Clone)]
+pub struct SnippetGenerator {
+    field: Field,
+    json_path: Vec<String>,
+    terms_text: BTreeMap<String, Score>,
+    tokenizer: TextAnalyzer,
+    config: proto::SnippetConfig,
+}
+
+impl SnippetGenerator {
+    pub fn new(field: Field, json_path: &str, terms_text: BTreeMap<String, Score>, tokenizer: TextAnalyzer, config: proto::SnippetConfig) -> Self {
+        SnippetGenerator {
+            field,
+            json_path: json_path.split('.').filter(|segment| !segment.is_empty()).map(str::to_string).collect(),
+            terms_text,
+            tokenizer,
+            config,
+        }
+    }
+
+    fn fragment_size(&self) -> usize {
+        self.config.fragment_size.map_or(DEFAULT_FRAGMENT_SIZE, |fragment_size| fragment_size as usize)
+    }
+
+    fn encode<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self.config.encoder() {
+            proto::SnippetEncoder::Html => Cow::Owned(escape_html(text)),
+            proto::SnippetEncoder::Raw => Cow::Borrowed(text),
+        }
+    }
+
+    fn to_html(&self, fragment: &str, highlighted: &[Range<usize>]) -> String {
+        let pre_tag = self.config.pre_tag.as_deref().unwrap_or(DEFAULT_PRE_TAG);
+        let post_tag = self.config.post_tag.as_deref().unwrap_or(DEFAULT_POST_TAG);
+        let mut html = String::new();
+        let mut start_from = 0;
+        for range in collapse_overlapped_ranges(highlighted) {
+            html.push_str(&self.encode(&fragment[start_from..range.start]));
+            html.push_str(pre_tag);
+            html.push_str(&self.encode(&fragment[range.clone()]));
+            html.push_str(post_tag);
+            start_from = range.end;
+        }
+        html.push_str(&self.encode(&fragment[start_from..]));
+        html
+    }
+
+    fn fragment(&self, fragment: &str, highlighted: Vec<Range<usize>>, value_index: usize) -> proto::SnippetFragment {
+        proto::SnippetFragment {
+            fragment: fragment.as_bytes().to_vec(),
+            html: self.to_html(fragment, &highlighted),
+            highlights: highlighted
+                .iter()
+                .map(|range| proto::Highlight {
+                    from: range.start as u32,
+                    to: range.end as u32,
+                })
+                .collect(),
+            value_index: value_index as u32,
+        }
+    }
+
+    /// Generates a snippet for the document
+    pub fn snippet_from_doc(&self, doc: &TantivyDocument) -> proto::Snippet {
+        let json_path: Vec<&str> = self.json_path.iter().map(String::as_str).collect();
+        let mut texts = vec![];
+        for value in doc.get_all(self.field) {
+            collect_texts(&OwnedValue::from(value.as_value()), &json_path, &mut texts);
+        }
+        self.snippet(&texts)
+    }
+
+    /// Generates a snippet for values of the field
+    pub fn snippet(&self, texts: &[String]) -> proto::Snippet {
+        let mut tokenizer = self.tokenizer.clone();
+        let mut candidates: Vec<_> = texts
+            .iter()
+            .enumerate()
+            .flat_map(|(value_index, text)| search_fragments(&mut tokenizer, text, value_index, &self.terms_text, self.fragment_size()))
+            .collect();
+        candidates.sort_by(|left, right| {
+            right
+                .score
+                .partial_cmp(&left.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| (left.value_index, left.start_offset).cmp(&(right.value_index, right.start_offset)))
+        });
+        candidates.truncate(self.config.number_of_fragments.unwrap_or(1) as usize);
+        let best_candidate = candidates.first().map(|candidate| (candidate.value_index, candidate.start_offset));
+        candidates.sort_by_key(|candidate| (candidate.value_index, candidate.start_offset));
+
+        let mut best_fragment = None;
+        let mut fragments = Vec::with_capacity(candidates.len());
+        for candidate in candidates {
+            let fragment = self.fragment(
+                &texts[candidate.value_index][candidate.start_offset..candidate.stop_offset],
+                candidate
+                    .highlighted
+                    .iter()
+                    .map(|range| range.start - candidate.start_offset..range.end - candidate.start_offset)
+                    .collect(),
+                candidate.value_index,
+            );
+            if best_candidate == Some((candidate.value_index, candidate.start_offset)) {
+                best_fragment = Some(fragment.clone());
+            }
+            fragments.push(fragment);
+        }
+        if fragments.is_empty() && self.config.no_match_size > 0 {
+            if let Some((value_index, text)) = texts.iter().enumerate().find(|(_, text)| !text.is_empty()) {
+                let fragment = self.fragment(leading_fragment(text, self.config.no_match_size as usize), vec![], value_index);
+                best_fragment = Some(fragment.clone());
+                fragments.push(fragment);
+            }
+        }
+
+        let best_fragment = best_fragment.unwrap_or_default();
+        proto::Snippet {
+            fragment: best_fragment.fragment,
+            highlights: best_fragment.highlights,
+            html: best_fragment.html,
+            fragments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use summa_proto::proto;
+    use tantivy::query::TermQuery;
+    use tantivy::schema::{IndexRecordOption, JsonObjectOptions, Schema, STORED, TEXT};
+    use tantivy::{doc, TantivyDocument, Term};
+
+    use super::SnippetGeneratorConfig;
+    use crate::components::test_utils::create_test_index;
+
+    #[test]
+    fn test_snippet_configs() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let metadata = schema_builder.add_json_field("metadata", JsonObjectOptions::from(TEXT) | STORED);
+        let index = create_test_index(
+            schema_builder.build(),
+            [[doc!(
+                title => "Rust & safety",
+                title => "Nothing here",
+                title => "Rust is fast",
+                metadata => serde_json::from_str::<serde_json::Value>(r#"{"abstract": "Why rust?", "publisher": "Rust Foundation"}"#).unwrap(),
+            )]],
+        );
+        let searcher = index.reader().unwrap().searcher();
+        let document: TantivyDocument = searcher.doc(tantivy::DocAddress::new(0, 0)).unwrap();
+
+        let query = TermQuery::new(Term::from_field_text(title, "rust"), IndexRecordOption::Basic);
+        let snippet_configs = HashMap::from_iter([(
+            "title".to_string(),
+            proto::SnippetConfig {
+                number_of_fragments: Some(3),
+                pre_tag: Some("<em>".to_string()),
+                post_tag: Some("</em>".to_string()),
+                ..Default::default()
+            },
+        )]);
+        let snippet_generators = SnippetGeneratorConfig::new(searcher.clone(), Box::new(query), snippet_configs)
+            .as_generators()
+            .unwrap();
+        let snippet = snippet_generators[0].1.snippet_from_doc(&document);
+        assert_eq!(
+            snippet
+                .fragments
+                .iter()
+                .map(|fragment| (fragment.html.as_str(), fragment.value_index))
+                .collect::<Vec<_>>(),
+            vec![("<em>Rust</em> &amp; safety", 0), ("<em>Rust</em> is fast", 2)]
+        );
+        assert_eq!(snippet.html, "<em>Rust</em> &amp; safety");
+
+        // Zero-sized fragments contain only matched tokens as legacy `snippet_configs` did
+        let query = TermQuery::new(Term::from_field_text(title, "rust"), IndexRecordOption::Basic);
+        let snippet_configs = HashMap::from_iter([(
+            "title".to_string(),
+            proto::SnippetConfig {
+                fragment_size: Some(0),
+                ..Default::default()
+            },
+        )]);
+        let snippet_generators = SnippetGeneratorConfig::new(searcher.clone(), Box::new(query), snippet_configs)
+            .as_generators()
+            .unwrap();
+        assert_eq!(snippet_generators[0].1.snippet_from_doc(&document).html, "<b>Rust</b>");
+
+        let query = TermQuery::new(Term::from_field_text(title, "missing"), IndexRecordOption::Basic);
+        let snippet_configs = HashMap::from_iter([(
+            "title".to_string(),
+            proto::SnippetConfig {
+                no_match_size: 10,
+                encoder: proto::SnippetEncoder::Raw.into(),
+                ..Default::default()
+            },
+        )]);
+        let snippet_generators = SnippetGeneratorConfig::new(searcher.clone(), Box::new(query), snippet_configs)
+            .as_generators()
+            .unwrap();
+        assert_eq!(snippet_generators[0].1.snippet_from_doc(&document).html, "Rust &");
+
+        let mut query_term = Term::from_field_json_path(metadata, "abstract", false);
+        query_term.append_type_and_str("rust");
+        let query = TermQuery::new(query_term, IndexRecordOption::Basic);
+        let snippet_configs = HashMap::from_iter([("metadata.abstract".to_string(), proto::SnippetConfig::default())]);
+        let snippet_generators = SnippetGeneratorConfig::new(searcher, Box::new(query), snippet_configs).as_generators().unwrap();
+        assert_eq!(snippet_generators[0].1.snippet_from_doc(&document).html, "Why <b>rust</b>?");
     }
 }
@@ -9,6 +9,7 @@ use tantivy::{DateTime, TantivyDocument};
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
+use crate::components::vectors::encode_vector;
 use crate::errors::{Error, SummaResult, ValidationError};
 use crate::page_rank::quantize_page_rank;
 use crate::utils::current_time;
@@ -204,6 +205,13 @@ impl<'a> SummaDocument<'a> {
                 let field_entry = schema.get_field_entry(field);
                 let field_type = field_entry.field_type();
                 match json_value {
+                    // Arrays of numbers are packed into vectors stored by bytes fields
+                    JsonValue::Array(json_items)
+                        if matches!(field_type, FieldType::Bytes(_)) && !json_items.is_empty() && json_items.iter().all(JsonValue::is_number) =>
+                    {
+                        let vector: Vec<f32> = json_items.iter().filter_map(JsonValue::as_f64).map(|value| value as f32).collect();
+                        doc.add_field_value(field, &OwnedValue::Bytes(encode_vector(&vector)))
+                    }
                     JsonValue::Array(json_items) => {
                         for json_item in json_items {
                             match value_from_json(field_type, json_item) {
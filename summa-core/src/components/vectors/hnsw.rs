@@ -0,0 +1,323 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+use std::convert::Infallible;
+use std::io;
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use summa_proto::proto;
+use tantivy::DocId;
+
+use super::similarity;
+
+const FORMAT_VERSION: u8 = 1;
+const NO_ENTRY_POINT: u32 = u32::MAX;
+
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    distance: f32,
+    node: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance).then_with(|| self.node.cmp(&other.node))
+    }
+}
+
+/// Hierarchical Navigable Small World graph over vectors of a segment
+///
+/// Nodes are numbered in the order of insertion and refer to documents of the segment. The graph does not keep vectors,
+/// so searching requires a callback computing similarities to the query from the fast field.
+#[derive(Debug)]
+pub struct HnswGraph {
+    metric: proto::VectorMetric,
+    max_connections: usize,
+    entry_point: Option<u32>,
+    docs: Vec<DocId>,
+    // Neighbours of every node on every layer the node belongs to
+    layers: Vec<Vec<Vec<u32>>>,
+}
+
+impl HnswGraph {
+    /// Builds graph over `vectors` of documents, every node has up to `max_connections` neighbours on upper layers
+    /// and twice more on the bottom one
+    pub fn build(vectors: Vec<(DocId, Vec<f32>)>, metric: proto::VectorMetric, max_connections: usize, ef_construction: usize) -> HnswGraph {
+        let max_connections = max_connections.max(2);
+        let level_multiplier = 1.0 / (max_connections as f64).ln();
+        // Fixed seed makes graphs of the same segment reproducible
+        let mut rng = SmallRng::seed_from_u64(42);
+        let (docs, vectors): (Vec<_>, Vec<_>) = vectors.into_iter().unzip();
+        let mut graph = HnswGraph {
+            metric,
+            max_connections,
+            entry_point: None,
+            docs,
+            layers: Vec::with_capacity(vectors.len()),
+        };
+        for node in 0..vectors.len() as u32 {
+            let level = (-rng.gen::<f64>().max(f64::MIN_POSITIVE).ln() * level_multiplier) as usize;
+            graph.insert(node, level, &vectors, ef_construction.max(1));
+        }
+        graph
+    }
+
+    pub fn metric(&self) -> proto::VectorMetric {
+        self.metric
+    }
+
+    fn max_neighbours(&self, layer: usize) -> usize {
+        if layer == 0 {
+            2 * self.max_connections
+        } else {
+            self.max_connections
+        }
+    }
+
+    fn insert(&mut self, node: u32, level: usize, vectors: &[Vec<f32>], ef_construction: usize) {
+        self.layers.push(vec![vec![]; level + 1]);
+        let Some(entry_point) = self.entry_point else {
+            self.entry_point = Some(node);
+            return;
+        };
+        let metric = self.metric;
+        let query = &vectors[node as usize];
+        let mut distance_to = |other: u32| Ok::<_, Infallible>(distance(metric, query, &vectors[other as usize]));
+
+        let entry_level = self.layers[entry_point as usize].len() - 1;
+        let mut nearest = vec![Candidate {
+            distance: distance(metric, query, &vectors[entry_point as usize]),
+            node: entry_point,
+        }];
+        for layer in (level + 1..=entry_level).rev() {
+            nearest = self.search_layer(&nearest, 1, layer, &mut distance_to).unwrap_or_else(|never| match never {});
+        }
+        for layer in (0..=level.min(entry_level)).rev() {
+            nearest = self
+                .search_layer(&nearest, ef_construction, layer, &mut distance_to)
+                .unwrap_or_else(|never| match never {});
+            let max_neighbours = self.max_neighbours(layer);
+            let neighbours: Vec<u32> = nearest.iter().take(max_neighbours).map(|candidate| candidate.node).collect();
+            for &neighbour in &neighbours {
+                let neighbour_vector = &vectors[neighbour as usize];
+                let links = &mut self.layers[neighbour as usize][layer];
+                links.push(node);
+                if links.len() > max_neighbours {
+                    links.sort_by(|left, right| {
+                        distance(metric, neighbour_vector, &vectors[*left as usize]).total_cmp(&distance(metric, neighbour_vector, &vectors[*right as usize]))
+                    });
+                    links.truncate(max_neighbours);
+                }
+            }
+            self.layers[node as usize][layer] = neighbours;
+        }
+        if level > entry_level {
+            self.entry_point = Some(node);
+        }
+    }
+
+    /// Returns up to `ef` nodes of the layer closest to the query in the order of increasing distance
+    fn search_layer<E>(
+        &self,
+        entry_points: &[Candidate],
+        ef: usize,
+        layer: usize,
+        distance_to: &mut impl FnMut(u32) -> Result<f32, E>,
+    ) -> Result<Vec<Candidate>, E> {
+        let mut visited: HashSet<u32> = entry_points.iter().map(|candidate| candidate.node).collect();
+        let mut candidates: BinaryHeap<Reverse<Candidate>> = entry_points.iter().copied().map(Reverse).collect();
+        let mut nearest: BinaryHeap<Candidate> = entry_points.iter().copied().collect();
+        while let Some(Reverse(candidate)) = candidates.pop() {
+            if matches!(nearest.peek(), Some(farthest) if nearest.len() >= ef && candidate.distance > farthest.distance) {
+                break;
+            }
+            for &neighbour in &self.layers[candidate.node as usize][layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let neighbour = Candidate {
+                    distance: distance_to(neighbour)?,
+                    node: neighbour,
+                };
+                if nearest.len() < ef || matches!(nearest.peek(), Some(farthest) if neighbour.distance < farthest.distance) {
+                    candidates.push(Reverse(neighbour));
+                    nearest.push(neighbour);
+                    if nearest.len() > ef {
+                        nearest.pop();
+                    }
+                }
+            }
+        }
+        Ok(nearest.into_sorted_vec())
+    }
+
+    /// Returns up to `k` documents accepted by `filter` that are the most similar to the query
+    ///
+    /// `similarity_to` computes similarity of the document vector to the query. Documents are visited through `ef_search` candidates,
+    /// so heavy filtering may leave less than `k` documents.
+    pub fn search<E>(
+        &self,
+        k: usize,
+        ef_search: usize,
+        mut filter: impl FnMut(DocId) -> bool,
+        mut similarity_to: impl FnMut(DocId) -> Result<f32, E>,
+    ) -> Result<Vec<(DocId, f32)>, E> {
+        let Some(entry_point) = self.entry_point else {
+            return Ok(vec![]);
+        };
+        let mut distance_to = |node: u32| similarity_to(self.docs[node as usize]).map(|similarity| -similarity);
+        let mut nearest = vec![Candidate {
+            distance: distance_to(entry_point)?,
+            node: entry_point,
+        }];
+        for layer in (1..self.layers[entry_point as usize].len()).rev() {
+            nearest = self.search_layer(&nearest, 1, layer, &mut distance_to)?;
+        }
+        let nearest = self.search_layer(&nearest, ef_search.max(k), 0, &mut distance_to)?;
+        Ok(nearest
+            .into_iter()
+            .map(|candidate| (self.docs[candidate.node as usize], -candidate.distance))
+            .filter(|(doc, _)| filter(*doc))
+            .take(k)
+            .collect())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![FORMAT_VERSION, self.metric as u8];
+        bytes.extend((self.max_connections as u32).to_le_bytes());
+        bytes.extend(self.entry_point.unwrap_or(NO_ENTRY_POINT).to_le_bytes());
+        bytes.extend((self.docs.len() as u32).to_le_bytes());
+        for (doc, node_layers) in self.docs.iter().zip(&self.layers) {
+            bytes.extend(doc.to_le_bytes());
+            bytes.push(node_layers.len() as u8);
+            for links in node_layers {
+                bytes.extend((links.len() as u32).to_le_bytes());
+                for link in links {
+                    bytes.extend(link.to_le_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<HnswGraph> {
+        let mut reader = GraphReader { bytes };
+        let version = reader.read_u8()?;
+        if version != FORMAT_VERSION {
+            return Err(invalid_data(format!("unsupported hnsw graph version {version}")));
+        }
+        let metric = proto::VectorMetric::try_from(reader.read_u8()? as i32).map_err(|e| invalid_data(e.to_string()))?;
+        let max_connections = reader.read_u32()? as usize;
+        let entry_point = match reader.read_u32()? {
+            NO_ENTRY_POINT => None,
+            entry_point => Some(entry_point),
+        };
+        let num_nodes = reader.read_u32()? as usize;
+        let mut docs = Vec::with_capacity(num_nodes);
+        let mut layers = Vec::with_capacity(num_nodes);
+        for _ in 0..num_nodes {
+            docs.push(reader.read_u32()?);
+            let num_layers = reader.read_u8()? as usize;
+            let mut node_layers = Vec::with_capacity(num_layers);
+            for _ in 0..num_layers {
+                let num_links = reader.read_u32()? as usize;
+                node_layers.push((0..num_links).map(|_| reader.read_u32()).collect::<io::Result<Vec<_>>>()?);
+            }
+            layers.push(node_layers);
+        }
+        Ok(HnswGraph {
+            metric,
+            max_connections,
+            entry_point,
+            docs,
+            layers,
+        })
+    }
+}
+
+#[inline]
+fn distance(metric: proto::VectorMetric, left: &[f32], right: &[f32]) -> f32 {
+    -similarity(metric, left, right)
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+struct GraphReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl GraphReader<'_> {
+    fn read_u8(&mut self) -> io::Result<u8> {
+        let (value, rest) = self.bytes.split_first().ok_or_else(|| invalid_data("truncated hnsw graph".to_string()))?;
+        self.bytes = rest;
+        Ok(*value)
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        if self.bytes.len() < 4 {
+            return Err(invalid_data("truncated hnsw graph".to_string()));
+        }
+        let (value, rest) = self.bytes.split_at(4);
+        self.bytes = rest;
+        Ok(u32::from_le_bytes([value[0], value[1], value[2], value[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+    use summa_proto::proto;
+
+    use super::HnswGraph;
+    use crate::components::vectors::similarity;
+
+    #[test]
+    fn test_hnsw() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let vectors: Vec<Vec<f32>> = (0..500).map(|_| (0..8).map(|_| rng.gen_range(-1.0..1.0)).collect()).collect();
+        let graph = HnswGraph::build(
+            vectors.iter().cloned().enumerate().map(|(doc, vector)| (doc as u32, vector)).collect(),
+            proto::VectorMetric::L2,
+            8,
+            64,
+        );
+        let graph = HnswGraph::from_bytes(&graph.to_bytes()).unwrap();
+        assert_eq!(graph.metric(), proto::VectorMetric::L2);
+
+        let query = &vectors[17];
+        let similarity_to = |doc: u32| Ok::<_, Infallible>(similarity(proto::VectorMetric::L2, query, &vectors[doc as usize]));
+        let nearest = graph.search(5, 64, |_| true, similarity_to).unwrap();
+        assert_eq!(nearest.len(), 5);
+        assert_eq!(nearest[0].0, 17);
+
+        let mut exact: Vec<_> = (0..vectors.len() as u32).map(|doc| (doc, similarity_to(doc).unwrap())).collect();
+        exact.sort_by(|left, right| right.1.total_cmp(&left.1));
+        let recall = nearest
+            .iter()
+            .filter(|(doc, _)| exact[..5].iter().any(|(exact_doc, _)| exact_doc == doc))
+            .count();
+        assert!(recall >= 4);
+
+        let odd = graph.search(3, 64, |doc| doc % 2 == 1, similarity_to).unwrap();
+        assert!(odd.iter().all(|(doc, _)| doc % 2 == 1));
+    }
+}
@@ -0,0 +1,98 @@
+//! Dense vectors stored in bytes fast fields as little-endian f32 values
+mod hnsw;
+
+use std::path::PathBuf;
+
+pub use hnsw::HnswGraph;
+use summa_proto::proto;
+use tantivy::columnar::BytesColumn;
+use tantivy::index::SegmentId;
+use tantivy::DocId;
+
+pub const DEFAULT_MAX_CONNECTIONS: usize = 16;
+pub const DEFAULT_EF_CONSTRUCTION: usize = 100;
+pub const DEFAULT_EF_SEARCH: usize = 64;
+
+/// Packs vector into bytes stored by the fast field
+pub fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// Unpacks vector from bytes, `None` if the length of bytes is not a multiple of 4
+pub fn decode_vector(bytes: &[u8]) -> Option<Vec<f32>> {
+    if bytes.len() % 4 != 0 {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect(),
+    )
+}
+
+/// Reads the first vector of the document
+pub fn read_vector(column: &BytesColumn, doc: DocId, buffer: &mut Vec<u8>) -> std::io::Result<Option<Vec<f32>>> {
+    let Some(ord) = column.ords().first(doc) else {
+        return Ok(None);
+    };
+    buffer.clear();
+    column.ord_to_bytes(ord, buffer)?;
+    Ok(decode_vector(buffer))
+}
+
+/// Similarity of vectors, the higher the closer
+///
+/// L2 distance is mapped to `1 / (1 + distance^2)` so similarities stay positive and can be summed with other scores
+pub fn similarity(metric: proto::VectorMetric, left: &[f32], right: &[f32]) -> f32 {
+    match metric {
+        proto::VectorMetric::Cosine => {
+            let dot = dot(left, right);
+            let norms = dot_self(left).sqrt() * dot_self(right).sqrt();
+            if norms > 0.0 {
+                dot / norms
+            } else {
+                0.0
+            }
+        }
+        proto::VectorMetric::Dot => dot(left, right),
+        proto::VectorMetric::L2 => {
+            let squared_distance: f32 = left.iter().zip(right).map(|(l, r)| (l - r) * (l - r)).sum();
+            1.0 / (1.0 + squared_distance)
+        }
+    }
+}
+
+#[inline]
+fn dot(left: &[f32], right: &[f32]) -> f32 {
+    left.iter().zip(right).map(|(l, r)| l * r).sum()
+}
+
+#[inline]
+fn dot_self(vector: &[f32]) -> f32 {
+    dot(vector, vector)
+}
+
+/// File keeping HNSW graph of the vector field for the segment
+pub fn hnsw_graph_path(segment_id: SegmentId, field_name: &str) -> PathBuf {
+    PathBuf::from(format!("{}.{}.hnsw", segment_id.uuid_string(), field_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use summa_proto::proto;
+
+    use super::{decode_vector, encode_vector, similarity};
+
+    #[test]
+    fn test_vectors() {
+        let vector = vec![1.0, -2.5, 0.125];
+        assert_eq!(decode_vector(&encode_vector(&vector)), Some(vector));
+        assert_eq!(decode_vector(&[0, 1, 2]), None);
+
+        assert!((similarity(proto::VectorMetric::Cosine, &[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(similarity(proto::VectorMetric::Cosine, &[1.0, 0.0], &[0.0, 0.0]).abs() < 1e-6);
+        assert!((similarity(proto::VectorMetric::Dot, &[1.0, 2.0], &[3.0, 4.0]) - 11.0).abs() < 1e-6);
+        assert!((similarity(proto::VectorMetric::L2, &[0.0, 0.0], &[1.0, 1.0]) - 1.0 / 3.0).abs() < 1e-6);
+    }
+}
@@ -64,6 +64,9 @@ pub struct Config {
     #[builder(default = "100")]
     #[serde(default = "return_100")]
     pub doc_store_cache_num_blocks: usize,
+    #[builder(default = "None")]
+    #[serde(default)]
+    pub expiry_interval_ms: Option<u64>,
     #[serde(default = "HashMap::new")]
     pub indices: HashMap<String, IndexEngineConfig>,
     #[builder(default = "1024 * 1024 * 1024")]
@@ -79,6 +82,7 @@ impl Default for Config {
             autocommit_interval_ms: None,
             collector_cache: CollectorCacheConfig::default(),
             doc_store_compress_threads: 1,
+            expiry_interval_ms: None,
             indices: HashMap::new(),
             writer_heap_size_bytes: 1024 * 1024 * 1024,
             writer_threads: Some(WriterThreads::N(1)),
@@ -162,3 +166,53 @@ impl QueryParserConfig {
         }
     }
 }
+
+/// Limits of resources that a single search may use
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits(pub proto::SearchLimits);
+impl SearchLimits {
+    /// Combines limits taking the strictest value of every limit
+    pub fn stricter(&self, other: &SearchLimits) -> SearchLimits {
+        fn min<T: Ord + Copy>(left: Option<T>, right: Option<T>) -> Option<T> {
+            match (left, right) {
+                (Some(left), Some(right)) => Some(left.min(right)),
+                (left, right) => left.or(right),
+            }
+        }
+        SearchLimits(proto::SearchLimits {
+            max_regex_size_bytes: min(self.0.max_regex_size_bytes, other.0.max_regex_size_bytes),
+            max_phrase_slop: min(self.0.max_phrase_slop, other.0.max_phrase_slop),
+            max_query_terms: min(self.0.max_query_terms, other.0.max_query_terms),
+            max_more_like_this_terms: min(self.0.max_more_like_this_terms, other.0.max_more_like_this_terms),
+            max_collectors: min(self.0.max_collectors, other.0.max_collectors),
+            max_limit: min(self.0.max_limit, other.0.max_limit),
+            max_offset: min(self.0.max_offset, other.0.max_offset),
+            max_aggregation_buckets: min(self.0.max_aggregation_buckets, other.0.max_aggregation_buckets),
+            max_aggregation_memory_bytes: min(self.0.max_aggregation_memory_bytes, other.0.max_aggregation_memory_bytes),
+        })
+    }
+
+    /// Fails if `value` exceeds `limit`
+    pub fn check<T: Into<u64>>(name: &str, value: T, limit: Option<T>) -> SummaResult<()> {
+        match limit.map(Into::into) {
+            Some(limit) if value.into() > limit => Err(ValidationError::LimitExceeded(name.to_string(), limit).into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the number of collectors and their limits and offsets
+    pub fn check_collectors(&self, collectors: &[proto::Collector]) -> SummaResult<()> {
+        SearchLimits::check("collectors", collectors.len() as u64, self.0.max_collectors.map(u64::from))?;
+        for collector in collectors {
+            let (limit, offset) = match &collector.collector {
+                Some(proto::collector::Collector::TopDocs(top_docs)) => (top_docs.limit, top_docs.offset),
+                Some(proto::collector::Collector::Fusion(fusion)) => (fusion.limit, fusion.offset),
+                Some(proto::collector::Collector::ReservoirSampling(reservoir_sampling)) => (reservoir_sampling.limit, 0),
+                _ => continue,
+            };
+            SearchLimits::check("limit", limit, self.0.max_limit)?;
+            SearchLimits::check("offset", offset, self.0.max_offset)?;
+        }
+        Ok(())
+    }
+}
@@ -8,6 +8,10 @@ use tantivy::schema::FieldType;
 pub enum ValidationError {
     #[error("builder_error: {0}")]
     Builder(#[from] BuilderError),
+    #[error("invalid_cardinality_precision: {0}")]
+    InvalidCardinalityPrecision(u32),
+    #[error("invalid_facet_error: {0}")]
+    InvalidFacet(String),
     #[error("invalid_fast_field_type_error: ({field:?}, {field_type:?}, {tantivy_error:?})")]
     InvalidFastFieldType {
         field: String,
@@ -16,16 +20,24 @@ pub enum ValidationError {
     },
     #[error("invalid_http_header: <{0}: {1}>")]
     InvalidHttpHeader(String, String),
+    #[error("invalid_ingest_processor_error: {0}")]
+    InvalidIngestProcessor(String),
+    #[error("invalid_rescore_error: {0}")]
+    InvalidRescore(String),
     #[error("invalid_segments_number: {0}")]
     InvalidSegmentsNumber(u32),
     #[error("invalid_schema_error: {0}")]
     InvalidSchema(String),
     #[error("invalid_unique_field_type_error: {0:?}")]
     InvalidUniqueFieldType(FieldType),
+    #[error("invalid_vector_error: {0}")]
+    InvalidVector(String),
     #[error("empty_argument_error: {0}")]
     EmptyArgument(String),
     #[error("existing_path_error: {0}")]
     ExistingPath(PathBuf),
+    #[error("limit_exceeded_error: {0} is limited to {1}")]
+    LimitExceeded(String, u64),
     #[error("missing_index_error: {0}")]
     MissingIndex(String),
     #[error("missing_field_error: {0}")]
@@ -40,6 +52,8 @@ pub enum ValidationError {
     MissingUniqueField(String),
     #[error("required_fast_field: {0}")]
     RequiredFastField(String),
+    #[error("stale_version_error: version {version} is not greater than stored version {stored_version}")]
+    StaleVersion { version: u64, stored_version: u64 },
     #[error("utf8_error: {0}")]
     Utf8(#[from] std::str::Utf8Error),
     #[error("template_error: {0}")]
@@ -70,8 +84,8 @@ pub enum Error {
     Infallible,
     #[error("internal_error")]
     Internal,
-    #[error("invalid_aggregation")]
-    InvalidAggregation,
+    #[error("invalid_aggregation: {0}")]
+    InvalidAggregation(String),
     #[error("{0:?}: {1:?}")]
     InvalidFieldType(String, FieldType),
     #[error("{0:?} for {1:?}")]
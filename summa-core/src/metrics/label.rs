@@ -20,6 +20,7 @@ impl ToLabel for proto::query::Query {
             proto::query::Query::MoreLikeThis(_) => "more_like_this",
             proto::query::Query::DisjunctionMax(_) => "disjunction_max",
             proto::query::Query::Exists(_) => "exists",
+            proto::query::Query::Vector(_) => "vector",
         }
         .to_owned()
     }
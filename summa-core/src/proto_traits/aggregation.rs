@@ -0,0 +1,398 @@
+use std::collections::{BTreeMap, HashMap};
+
+use summa_proto::proto;
+use tantivy::aggregation::agg_req::{Aggregation, AggregationVariants, Aggregations};
+use tantivy::aggregation::agg_result::{AggregationResult, AggregationResults, BucketEntries, BucketEntry, BucketResult, MetricResult, RangeBucketEntry};
+use tantivy::aggregation::bucket::{
+    CustomOrder, DateHistogramAggregationReq, HistogramAggregation, HistogramBounds, OrderTarget, RangeAggregation, RangeAggregationRange, TermsAggregation,
+};
+use tantivy::aggregation::metric::{PercentileValues, PercentilesAggregationReq, Stats, StatsAggregation};
+use tantivy::aggregation::Key;
+
+use crate::errors::{Error, SummaResult};
+use crate::proto_traits::Wrapper;
+
+impl From<proto::HistogramBounds> for Wrapper<HistogramBounds> {
+    fn from(bounds: proto::HistogramBounds) -> Self {
+        Wrapper::from(HistogramBounds {
+            min: bounds.min,
+            max: bounds.max,
+        })
+    }
+}
+
+impl From<proto::BucketOrder> for Wrapper<CustomOrder> {
+    fn from(bucket_order: proto::BucketOrder) -> Self {
+        Wrapper::from(CustomOrder {
+            order: Wrapper::from(bucket_order.order()).into(),
+            target: OrderTarget::from(bucket_order.target.as_str()),
+        })
+    }
+}
+
+impl TryFrom<proto::TypedAggregation> for Wrapper<Aggregation> {
+    type Error = Error;
+
+    fn try_from(typed_aggregation: proto::TypedAggregation) -> SummaResult<Self> {
+        let histogram_bounds = |bounds: Option<proto::HistogramBounds>| bounds.map(|bounds| Wrapper::<HistogramBounds>::from(bounds).into_inner());
+        let (agg, is_metric) = match typed_aggregation
+            .aggregation
+            .ok_or_else(|| Error::InvalidAggregation("aggregation type is not set".to_string()))?
+        {
+            proto::typed_aggregation::Aggregation::Terms(terms) => (
+                AggregationVariants::Terms(TermsAggregation {
+                    field: terms.field,
+                    size: terms.size,
+                    segment_size: terms.segment_size,
+                    min_doc_count: terms.min_doc_count,
+                    order: terms.order.map(|order| Wrapper::<CustomOrder>::from(order).into_inner()),
+                    missing: terms.missing.map(Key::Str),
+                    ..Default::default()
+                }),
+                false,
+            ),
+            proto::typed_aggregation::Aggregation::Histogram(histogram) => (
+                AggregationVariants::Histogram(HistogramAggregation {
+                    field: histogram.field,
+                    interval: histogram.interval,
+                    offset: histogram.offset,
+                    min_doc_count: histogram.min_doc_count,
+                    hard_bounds: histogram_bounds(histogram.hard_bounds),
+                    extended_bounds: histogram_bounds(histogram.extended_bounds),
+                    ..Default::default()
+                }),
+                false,
+            ),
+            proto::typed_aggregation::Aggregation::DateHistogram(date_histogram) => {
+                // `DateHistogramAggregationReq` has private deprecated fields, so it cannot be built with a struct expression
+                let mut date_histogram_req = DateHistogramAggregationReq::default();
+                date_histogram_req.field = date_histogram.field;
+                date_histogram_req.fixed_interval = Some(date_histogram.fixed_interval);
+                date_histogram_req.offset = date_histogram.offset;
+                date_histogram_req.min_doc_count = date_histogram.min_doc_count;
+                date_histogram_req.hard_bounds = histogram_bounds(date_histogram.hard_bounds);
+                date_histogram_req.extended_bounds = histogram_bounds(date_histogram.extended_bounds);
+                (AggregationVariants::DateHistogram(date_histogram_req), false)
+            }
+            proto::typed_aggregation::Aggregation::Range(range) => (
+                AggregationVariants::Range(RangeAggregation {
+                    field: range.field,
+                    ranges: range
+                        .ranges
+                        .into_iter()
+                        .map(|range| RangeAggregationRange {
+                            key: range.key,
+                            from: range.from,
+                            to: range.to,
+                        })
+                        .collect(),
+                    keyed: false,
+                }),
+                false,
+            ),
+            proto::typed_aggregation::Aggregation::Stats(stats) => (
+                AggregationVariants::Stats(StatsAggregation {
+                    field: stats.field,
+                    missing: stats.missing,
+                }),
+                true,
+            ),
+            proto::typed_aggregation::Aggregation::Percentiles(percentiles) => (
+                AggregationVariants::Percentiles(PercentilesAggregationReq {
+                    field: percentiles.field,
+                    percents: (!percentiles.percents.is_empty()).then_some(percentiles.percents),
+                    keyed: false,
+                    missing: percentiles.missing,
+                }),
+                true,
+            ),
+        };
+        if is_metric && !typed_aggregation.sub_aggregations.is_empty() {
+            return Err(Error::InvalidAggregation("metric aggregations cannot have sub-aggregations".to_string()));
+        }
+        Ok(Wrapper::from(Aggregation {
+            agg,
+            sub_aggregation: Aggregations::try_from(Wrapper::from(typed_aggregation.sub_aggregations))?,
+        }))
+    }
+}
+
+impl TryFrom<Wrapper<BTreeMap<String, proto::TypedAggregation>>> for Aggregations {
+    type Error = Error;
+
+    fn try_from(typed_aggregations: Wrapper<BTreeMap<String, proto::TypedAggregation>>) -> SummaResult<Self> {
+        typed_aggregations
+            .into_inner()
+            .into_iter()
+            .map(|(name, typed_aggregation)| Ok((name, Wrapper::<Aggregation>::try_from(typed_aggregation)?.into_inner())))
+            .collect()
+    }
+}
+
+impl From<Key> for Wrapper<proto::BucketKey> {
+    fn from(key: Key) -> Self {
+        Wrapper::from(proto::BucketKey {
+            key: Some(match key {
+                Key::Str(value) => proto::bucket_key::Key::Str(value),
+                Key::I64(value) => proto::bucket_key::Key::I64(value),
+                Key::U64(value) => proto::bucket_key::Key::U64(value),
+                Key::F64(value) => proto::bucket_key::Key::F64(value),
+            }),
+        })
+    }
+}
+
+impl From<BucketEntry> for Wrapper<proto::AggregationBucket> {
+    fn from(bucket_entry: BucketEntry) -> Self {
+        Wrapper::from(proto::AggregationBucket {
+            key: Some(Wrapper::from(bucket_entry.key).into_inner()),
+            key_as_string: bucket_entry.key_as_string,
+            doc_count: bucket_entry.doc_count,
+            from: None,
+            to: None,
+            sub_aggregations: Wrapper::from(bucket_entry.sub_aggregation).into_inner(),
+        })
+    }
+}
+
+impl From<RangeBucketEntry> for Wrapper<proto::AggregationBucket> {
+    fn from(range_bucket_entry: RangeBucketEntry) -> Self {
+        Wrapper::from(proto::AggregationBucket {
+            key: Some(Wrapper::from(range_bucket_entry.key).into_inner()),
+            key_as_string: None,
+            doc_count: range_bucket_entry.doc_count,
+            from: range_bucket_entry.from,
+            to: range_bucket_entry.to,
+            sub_aggregations: Wrapper::from(range_bucket_entry.sub_aggregation).into_inner(),
+        })
+    }
+}
+
+fn buckets<T>(bucket_entries: BucketEntries<T>) -> Vec<proto::AggregationBucket>
+where
+    Wrapper<proto::AggregationBucket>: From<T>,
+{
+    let bucket_entries: Vec<_> = match bucket_entries {
+        BucketEntries::Vec(bucket_entries) => bucket_entries,
+        BucketEntries::HashMap(bucket_entries) => bucket_entries.into_values().collect(),
+    };
+    bucket_entries
+        .into_iter()
+        .map(|bucket_entry| Wrapper::<proto::AggregationBucket>::from(bucket_entry).into_inner())
+        .collect()
+}
+
+impl From<Stats> for Wrapper<proto::StatsAggregationResult> {
+    fn from(stats: Stats) -> Self {
+        Wrapper::from(proto::StatsAggregationResult {
+            count: stats.count,
+            sum: stats.sum,
+            min: stats.min,
+            max: stats.max,
+            avg: stats.avg,
+        })
+    }
+}
+
+impl From<PercentileValues> for Wrapper<proto::PercentilesAggregationResult> {
+    fn from(percentile_values: PercentileValues) -> Self {
+        let values = match percentile_values {
+            // Fields of `PercentileValuesVecEntry` are private, so entries are read from their serialized form
+            PercentileValues::Vec(entries) => entries
+                .into_iter()
+                .filter_map(|entry| {
+                    let entry = serde_json::to_value(entry).ok()?;
+                    Some(proto::PercentileValue {
+                        percent: entry.get("key")?.as_f64()?,
+                        value: entry.get("value")?.as_f64().unwrap_or(f64::NAN),
+                    })
+                })
+                .collect(),
+            PercentileValues::HashMap(entries) => entries
+                .into_iter()
+                .filter_map(|(percent, value)| {
+                    Some(proto::PercentileValue {
+                        percent: percent.parse().ok()?,
+                        value,
+                    })
+                })
+                .collect(),
+        };
+        Wrapper::from(proto::PercentilesAggregationResult { values })
+    }
+}
+
+impl From<AggregationResult> for Wrapper<proto::TypedAggregationResult> {
+    fn from(aggregation_result: AggregationResult) -> Self {
+        let result = match aggregation_result {
+            AggregationResult::BucketResult(BucketResult::Terms {
+                buckets: bucket_entries,
+                sum_other_doc_count,
+                doc_count_error_upper_bound,
+                ..
+            }) => Some(proto::typed_aggregation_result::Result::Buckets(proto::BucketAggregationResult {
+                buckets: bucket_entries
+                    .into_iter()
+                    .map(|bucket_entry| Wrapper::<proto::AggregationBucket>::from(bucket_entry).into_inner())
+                    .collect(),
+                sum_other_doc_count: Some(sum_other_doc_count),
+                doc_count_error_upper_bound,
+            })),
+            AggregationResult::BucketResult(BucketResult::Histogram { buckets: bucket_entries, .. }) => {
+                Some(proto::typed_aggregation_result::Result::Buckets(proto::BucketAggregationResult {
+                    buckets: buckets(bucket_entries),
+                    sum_other_doc_count: None,
+                    doc_count_error_upper_bound: None,
+                }))
+            }
+            AggregationResult::BucketResult(BucketResult::Range { buckets: bucket_entries, .. }) => {
+                Some(proto::typed_aggregation_result::Result::Buckets(proto::BucketAggregationResult {
+                    buckets: buckets(bucket_entries),
+                    sum_other_doc_count: None,
+                    doc_count_error_upper_bound: None,
+                }))
+            }
+            AggregationResult::MetricResult(MetricResult::Stats(stats)) => {
+                Some(proto::typed_aggregation_result::Result::Stats(Wrapper::from(stats).into_inner()))
+            }
+            AggregationResult::MetricResult(MetricResult::Percentiles(percentiles)) => Some(proto::typed_aggregation_result::Result::Percentiles(
+                Wrapper::from(percentiles.values).into_inner(),
+            )),
+            // Typed aggregations never request other metrics
+            AggregationResult::MetricResult(_) => None,
+        };
+        Wrapper::from(proto::TypedAggregationResult { result })
+    }
+}
+
+impl From<AggregationResults> for Wrapper<HashMap<String, proto::TypedAggregationResult>> {
+    fn from(aggregation_results: AggregationResults) -> Self {
+        Wrapper::from(
+            aggregation_results
+                .0
+                .into_iter()
+                .map(|(name, aggregation_result)| (name, Wrapper::from(aggregation_result).into_inner()))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use summa_proto::proto;
+    use tantivy::aggregation::agg_req::Aggregations;
+    use tantivy::aggregation::AggregationCollector;
+    use tantivy::doc;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{Schema, FAST, STRING};
+
+    use crate::components::test_utils::create_test_index;
+    use crate::errors::Error;
+    use crate::proto_traits::Wrapper;
+
+    #[test]
+    fn test_typed_aggregations() {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = create_test_index(
+            schema_builder.build(),
+            [[
+                doc!(category => "a", price => 1.0),
+                doc!(category => "a", price => 3.0),
+                doc!(category => "b", price => 10.0),
+            ]],
+        );
+
+        let stats = |field: &str| proto::TypedAggregation {
+            aggregation: Some(proto::typed_aggregation::Aggregation::Stats(proto::StatsAggregation {
+                field: field.to_string(),
+                missing: None,
+            })),
+            sub_aggregations: BTreeMap::new(),
+        };
+        let typed_aggregations = BTreeMap::from_iter([
+            (
+                "categories".to_string(),
+                proto::TypedAggregation {
+                    aggregation: Some(proto::typed_aggregation::Aggregation::Terms(proto::TermsAggregation {
+                        field: "category".to_string(),
+                        ..Default::default()
+                    })),
+                    sub_aggregations: BTreeMap::from_iter([("price_stats".to_string(), stats("price"))]),
+                },
+            ),
+            (
+                "prices".to_string(),
+                proto::TypedAggregation {
+                    aggregation: Some(proto::typed_aggregation::Aggregation::Range(proto::RangeAggregation {
+                        field: "price".to_string(),
+                        ranges: vec![
+                            proto::RangeAggregationRange {
+                                key: Some("cheap".to_string()),
+                                from: None,
+                                to: Some(5.0),
+                            },
+                            proto::RangeAggregationRange {
+                                key: Some("expensive".to_string()),
+                                from: Some(5.0),
+                                to: None,
+                            },
+                        ],
+                    })),
+                    sub_aggregations: BTreeMap::new(),
+                },
+            ),
+        ]);
+        let aggregations = Aggregations::try_from(Wrapper::from(typed_aggregations)).unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let aggregation_results = searcher
+            .search(&AllQuery, &AggregationCollector::from_aggs(aggregations, Default::default()))
+            .unwrap();
+        let aggregation_results: HashMap<String, proto::TypedAggregationResult> = Wrapper::from(aggregation_results).into_inner();
+
+        let Some(proto::typed_aggregation_result::Result::Buckets(categories)) = &aggregation_results["categories"].result else {
+            panic!("terms aggregation should return buckets")
+        };
+        assert_eq!(
+            categories
+                .buckets
+                .iter()
+                .map(|bucket| {
+                    let Some(proto::typed_aggregation_result::Result::Stats(price_stats)) = &bucket.sub_aggregations["price_stats"].result else {
+                        panic!("stats aggregation should return stats")
+                    };
+                    (bucket.key.clone().and_then(|key| key.key), bucket.doc_count, price_stats.avg)
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(proto::bucket_key::Key::Str("a".to_string())), 2, Some(2.0)),
+                (Some(proto::bucket_key::Key::Str("b".to_string())), 1, Some(10.0)),
+            ]
+        );
+
+        let Some(proto::typed_aggregation_result::Result::Buckets(prices)) = &aggregation_results["prices"].result else {
+            panic!("range aggregation should return buckets")
+        };
+        assert_eq!(
+            prices
+                .buckets
+                .iter()
+                .map(|bucket| (bucket.key.clone().and_then(|key| key.key), bucket.doc_count))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(proto::bucket_key::Key::Str("cheap".to_string())), 2),
+                (Some(proto::bucket_key::Key::Str("expensive".to_string())), 1),
+            ]
+        );
+
+        let mut metric_with_sub_aggregations = stats("price");
+        metric_with_sub_aggregations.sub_aggregations.insert("inner".to_string(), stats("price"));
+        assert!(matches!(
+            Aggregations::try_from(Wrapper::from(BTreeMap::from_iter([("price_stats".to_string(), metric_with_sub_aggregations)]))),
+            Err(Error::InvalidAggregation(_))
+        ));
+    }
+}
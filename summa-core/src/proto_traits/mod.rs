@@ -1,3 +1,4 @@
+pub mod aggregation;
 pub mod compression;
 pub mod merge_policy;
 pub mod order;
@@ -4,17 +4,29 @@ use crate::proto_traits::Wrapper;
 
 impl From<tantivy::snippet::Snippet> for Wrapper<proto::Snippet> {
     fn from(snippet: tantivy::snippet::Snippet) -> Self {
+        let highlights: Vec<_> = snippet
+            .highlighted()
+            .iter()
+            .map(|r| proto::Highlight {
+                from: r.start as u32,
+                to: r.end as u32,
+            })
+            .collect();
+        let fragments = if snippet.fragment().is_empty() {
+            vec![]
+        } else {
+            vec![proto::SnippetFragment {
+                fragment: snippet.fragment().as_bytes().to_vec(),
+                highlights: highlights.clone(),
+                html: snippet.to_html(),
+                value_index: 0,
+            }]
+        };
         Wrapper::from(proto::Snippet {
             fragment: snippet.fragment().as_bytes().to_vec(),
-            highlights: snippet
-                .highlighted()
-                .iter()
-                .map(|r| proto::Highlight {
-                    from: r.start as u32,
-                    to: r.end as u32,
-                })
-                .collect(),
+            highlights,
             html: snippet.to_html(),
+            fragments,
         })
     }
 }
@@ -0,0 +1,182 @@
+use fasteval2::Evaler;
+use tantivy::schema::{Field, OwnedValue, Schema, Type};
+use tantivy::{DateTime, TantivyDocument};
+
+use super::eval_scorer::rewrite_aggregations;
+use super::fast_field_iterator::Aggregation;
+use super::functions::{build_namespace, FUNCTION_NAMES};
+use super::safe_into_f64::SafeIntoF64;
+use crate::errors::{SummaResult, ValidationError};
+
+/// Field of the document bound to the variable of `eval_expr`
+struct DocumentVariable {
+    var_name: String,
+    field: Field,
+    aggregation: Option<Aggregation>,
+}
+
+impl DocumentVariable {
+    fn value(&self, document: &TantivyDocument) -> f64 {
+        let mut values = document.get_all(self.field).filter_map(|value| as_f64(&OwnedValue::from(value)));
+        match self.aggregation {
+            None => values.next().unwrap_or(0.0),
+            Some(aggregation) => aggregation.aggregate(values),
+        }
+    }
+}
+
+/// Fast field filled at indexing with the value of `eval_expr` computed over other fields of the document
+///
+/// Expressions use the dialect of `EvalScorer`: variables are numeric, `bool` and date fields of the document, `now` is the time of indexing
+/// and multi-valued fields are referred through aggregations like `max(field)`.
+pub(crate) struct ComputedField {
+    field: Field,
+    field_type: Type,
+    eval_expr: String,
+    parser: fasteval2::Parser,
+    variables: Vec<DocumentVariable>,
+}
+
+impl ComputedField {
+    pub fn new(schema: &Schema, field_name: &str, eval_expr: &str) -> SummaResult<ComputedField> {
+        let field = schema.get_field(field_name).map_err(|_| ValidationError::MissingField(field_name.to_owned()))?;
+        let field_entry = schema.get_field_entry(field);
+        if !field_entry.is_fast() {
+            return Err(ValidationError::RequiredFastField(field_name.to_owned()).into());
+        }
+        let field_type = field_entry.field_type().value_type();
+        if !is_numeric(field_type) {
+            return Err(ValidationError::InvalidSchema(format!("computed field `{field_name}` must be numeric, `bool` or date")).into());
+        }
+
+        let parser = fasteval2::Parser::new();
+        let (eval_expr, aggregated_variables) = rewrite_aggregations(eval_expr, schema);
+        let mut slab = fasteval2::Slab::new();
+        let parsed = parser.parse(&eval_expr, &mut slab.ps)?.from(&slab.ps);
+        let mut variables = vec![];
+        for var_name in parsed
+            .var_names(&slab)
+            .iter()
+            .filter(|var_name| (*var_name).as_str() != "now" && !FUNCTION_NAMES.contains(&(*var_name).as_str()))
+        {
+            let (variable_field_name, aggregation) = match aggregated_variables.iter().find(|variable| &variable.var_name == var_name) {
+                Some(aggregated_variable) => (aggregated_variable.field_name.as_str(), aggregated_variable.aggregation),
+                None => (var_name.as_str(), None),
+            };
+            let variable_field = schema.get_field(variable_field_name)?;
+            if !is_numeric(schema.get_field_entry(variable_field).field_type().value_type()) {
+                return Err(ValidationError::InvalidSchema(format!(
+                    "`{variable_field_name}` used by computed field `{field_name}` must be numeric, `bool` or date"
+                ))
+                .into());
+            }
+            variables.push(DocumentVariable {
+                var_name: var_name.to_owned(),
+                field: variable_field,
+                aggregation,
+            });
+        }
+
+        let computed_field = ComputedField {
+            field,
+            field_type,
+            eval_expr,
+            parser,
+            variables,
+        };
+        // Evaluating the expression once reveals unknown functions and wrong numbers of arguments
+        computed_field.compute(&TantivyDocument::default())?;
+        Ok(computed_field)
+    }
+
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Evaluates `eval_expr` over values of `document`, missing values are `0` and non-finite results are skipped
+    pub fn compute(&self, document: &TantivyDocument) -> SummaResult<Option<OwnedValue>> {
+        let values = self
+            .variables
+            .iter()
+            .map(|variable| (variable.var_name.as_str(), variable.value(document)))
+            .collect::<Vec<_>>();
+        let now = instant::now() / 1000.0;
+        let mut namespace = build_namespace(now);
+        let mut slab = fasteval2::Slab::new();
+        let value = self
+            .parser
+            .parse(&self.eval_expr, &mut slab.ps)?
+            .from(&slab.ps)
+            .eval(&slab, &mut |name: &str, args: Vec<f64>| {
+                if args.is_empty() {
+                    if name == "now" {
+                        return Some(now);
+                    }
+                    if let Some((_, value)) = values.iter().find(|(var_name, _)| *var_name == name) {
+                        return Some(*value);
+                    }
+                }
+                namespace(name, args)
+            })?;
+        if !value.is_finite() {
+            return Ok(None);
+        }
+        Ok(Some(match self.field_type {
+            Type::U64 => OwnedValue::U64(value.round().max(0.0) as u64),
+            Type::I64 => OwnedValue::I64(value.round() as i64),
+            Type::Bool => OwnedValue::Bool(value != 0.0),
+            Type::Date => OwnedValue::Date(DateTime::from_timestamp_secs(value.round() as i64)),
+            _ => OwnedValue::F64(value),
+        }))
+    }
+}
+
+fn is_numeric(value_type: Type) -> bool {
+    matches!(value_type, Type::U64 | Type::I64 | Type::F64 | Type::Bool | Type::Date)
+}
+
+fn as_f64(value: &OwnedValue) -> Option<f64> {
+    match value {
+        OwnedValue::U64(value) => Some(value.safe_into_f64()),
+        OwnedValue::I64(value) => Some(value.safe_into_f64()),
+        OwnedValue::F64(value) => Some(value.safe_into_f64()),
+        OwnedValue::Bool(value) => Some(value.safe_into_f64()),
+        OwnedValue::Date(value) => Some(value.safe_into_f64()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::{OwnedValue, SchemaBuilder, FAST, INDEXED, STORED, TEXT};
+    use tantivy::{doc, TantivyDocument};
+
+    use super::ComputedField;
+
+    #[test]
+    fn test_computed_field() {
+        let mut schema_builder = SchemaBuilder::new();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let citations = schema_builder.add_u64_field("citations", INDEXED | STORED);
+        let page_rank = schema_builder.add_f64_field("page_rank", STORED);
+        schema_builder.add_f64_field("ranking", FAST);
+        schema_builder.add_u64_field("quantized_page_rank", FAST);
+        let schema = schema_builder.build();
+
+        let ranking = ComputedField::new(&schema, "ranking", "log1p(max(citations)) * 0.5 + count(citations)").unwrap();
+        let quantized_page_rank = ComputedField::new(&schema, "quantized_page_rank", "qpr(page_rank)").unwrap();
+
+        let mut document = TantivyDocument::default();
+        document.add_text(title, "title");
+        document.add_u64(citations, 3);
+        document.add_u64(citations, 7);
+        document.add_f64(page_rank, 0.3);
+        assert_eq!(ranking.compute(&document).unwrap(), Some(OwnedValue::F64(7f64.ln_1p() * 0.5 + 2.0)));
+        assert_eq!(quantized_page_rank.compute(&document).unwrap(), Some(OwnedValue::U64(4)));
+        assert_eq!(ranking.compute(&doc!(title => "title")).unwrap(), Some(OwnedValue::F64(0.0)));
+
+        assert!(ComputedField::new(&schema, "title", "citations").is_err());
+        assert!(ComputedField::new(&schema, "ranking", "title + 1").is_err());
+        assert!(ComputedField::new(&schema, "ranking", "unknown_function(citations)").is_err());
+    }
+}
@@ -1,54 +1,139 @@
 use fasteval2::Evaler;
+use regex::{Captures, Regex};
 use tantivy::schema::Schema;
 use tantivy::SegmentReader;
 
+use super::fast_field_iterator::Aggregation;
+use super::functions::{build_namespace, FUNCTION_NAMES};
 use crate::errors::{SummaResult, ValidationError};
 use crate::scorers::SegmentEvalScorer;
 
+/// Fast field bound to the variable of `eval_expr`
+#[derive(Clone)]
+pub(crate) struct FastFieldVariable {
+    pub var_name: String,
+    pub field_name: String,
+    pub aggregation: Option<Aggregation>,
+}
+
 /// Instantiates `SegmentEvalScorer` for each segment
 pub(crate) struct EvalScorer {
     eval_expr: String,
     schema: Schema,
     parser: fasteval2::Parser,
-    var_names: Vec<String>,
+    variables: Vec<FastFieldVariable>,
+}
+
+/// Replaces aggregations of fast fields like `max(citations)` with variables holding aggregated values of the document
+///
+/// Aggregations of anything except a single fast field are left to functions of `fasteval`
+pub(crate) fn rewrite_aggregations(eval_expr: &str, schema: &Schema) -> (String, Vec<FastFieldVariable>) {
+    thread_local! {
+        static AGGREGATION: Regex = Regex::new(r"\b(avg|count|max|min|sum)\s*\(\s*([A-Za-z_][A-Za-z0-9_]*)\s*\)").expect("cannot compile regex");
+    }
+    AGGREGATION.with(|aggregation_regex| {
+        let mut variables: Vec<FastFieldVariable> = vec![];
+        let rewritten_eval_expr = aggregation_regex.replace_all(eval_expr, |captures: &Captures| {
+            let field_name = &captures[2];
+            if schema.get_field(field_name).is_err() {
+                return captures[0].to_string();
+            }
+            let aggregation = Aggregation::from_name(&captures[1]).expect("unknown aggregation");
+            let var_name = format!("{}__{}", aggregation.name(), field_name);
+            if !variables.iter().any(|variable| variable.var_name == var_name) {
+                variables.push(FastFieldVariable {
+                    var_name: var_name.clone(),
+                    field_name: field_name.to_string(),
+                    aggregation: Some(aggregation),
+                });
+            }
+            var_name
+        });
+        (rewritten_eval_expr.into_owned(), variables)
+    })
 }
 
 impl EvalScorer {
     pub fn new(eval_expr: &str, schema: &Schema) -> SummaResult<EvalScorer> {
-        static RESERVED_WORDS: [&str; 4] = ["now", "original_score", "fastsigm", "iqpr"];
+        static RESERVED_WORDS: [&str; 2] = ["now", "original_score"];
         let parser = fasteval2::Parser::new();
+        let (eval_expr, aggregated_variables) = rewrite_aggregations(eval_expr, schema);
 
         // Create `Slab` for extracting variable names
         let mut slab = fasteval2::Slab::new();
-        let parsed = parser.parse(eval_expr, &mut slab.ps)?.from(&slab.ps);
-        let mut var_names = vec![];
+        let parsed = parser.parse(&eval_expr, &mut slab.ps)?.from(&slab.ps);
+        let mut variables = vec![];
         for var_name in parsed
             .var_names(&slab)
             .iter()
-            .filter(|var_name| !RESERVED_WORDS.contains(&(*var_name).as_str()))
+            .filter(|var_name| !RESERVED_WORDS.contains(&(*var_name).as_str()) && !FUNCTION_NAMES.contains(&(*var_name).as_str()))
         {
-            let field = schema.get_field(var_name)?;
+            let variable = match aggregated_variables.iter().find(|variable| &variable.var_name == var_name) {
+                Some(aggregated_variable) => aggregated_variable.clone(),
+                None => FastFieldVariable {
+                    var_name: var_name.to_owned(),
+                    field_name: var_name.to_owned(),
+                    aggregation: None,
+                },
+            };
+            let field = schema.get_field(&variable.field_name)?;
             if !schema.get_field_entry(field).is_fast() {
-                return Err(ValidationError::RequiredFastField(var_name.to_owned()).into());
+                return Err(ValidationError::RequiredFastField(variable.field_name).into());
             }
-            var_names.push(var_name.to_owned());
+            variables.push(variable);
         }
 
+        // Evaluating the expression once reveals unknown functions and wrong numbers of arguments
+        let mut namespace = build_namespace(0.0);
+        parsed.eval(&slab, &mut |name: &str, args: Vec<f64>| {
+            let is_variable = args.is_empty();
+            namespace(name, args).or_else(|| is_variable.then_some(0.0))
+        })?;
+
         Ok(EvalScorer {
-            eval_expr: eval_expr.to_owned(),
+            eval_expr,
             schema: schema.clone(),
             parser,
-            var_names,
+            variables,
         })
     }
 
     /// Instantiates `SegmentEvalScorer` for passed segment
     pub fn get_for_segment_reader(&self, segment_reader: &SegmentReader) -> SummaResult<SegmentEvalScorer> {
-        SegmentEvalScorer::for_segment(segment_reader, &self.schema, &self.parser, &self.eval_expr, &self.var_names)
+        SegmentEvalScorer::for_segment(segment_reader, &self.schema, &self.parser, &self.eval_expr, &self.variables)
     }
 
     /// Instantiates `SegmentEvalScorer` for passed segment in async way
     pub async fn get_for_segment_reader_async(&self, segment_reader: &SegmentReader) -> SummaResult<SegmentEvalScorer> {
-        SegmentEvalScorer::for_segment_async(segment_reader, &self.schema, &self.parser, &self.eval_expr, &self.var_names).await
+        SegmentEvalScorer::for_segment_async(segment_reader, &self.schema, &self.parser, &self.eval_expr, &self.variables).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::{Schema, FAST};
+
+    use super::rewrite_aggregations;
+    use crate::scorers::fast_field_iterator::Aggregation;
+
+    #[test]
+    fn test_rewrite_aggregations() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("citations", FAST);
+        let schema = schema_builder.build();
+
+        let (eval_expr, variables) = rewrite_aggregations("max(citations) + sum( citations ) / count(citations) + max(original_score, 1)", &schema);
+        assert_eq!(eval_expr, "max__citations + sum__citations / count__citations + max(original_score, 1)");
+        assert_eq!(
+            variables
+                .iter()
+                .map(|variable| (variable.var_name.as_str(), variable.aggregation))
+                .collect::<Vec<_>>(),
+            vec![
+                ("max__citations", Some(Aggregation::Max)),
+                ("sum__citations", Some(Aggregation::Sum)),
+                ("count__citations", Some(Aggregation::Count))
+            ]
+        );
     }
 }
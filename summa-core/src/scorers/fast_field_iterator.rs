@@ -8,6 +8,70 @@ pub(crate) trait FastFieldIterator: Send + Sync {
     fn value(&self) -> &f64;
 }
 
+/// Aggregation of all values of the document in multi-valued fast field
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Aggregation {
+    Avg,
+    Count,
+    Max,
+    Min,
+    Sum,
+}
+
+impl Aggregation {
+    pub fn from_name(name: &str) -> Option<Aggregation> {
+        match name {
+            "avg" => Some(Aggregation::Avg),
+            "count" => Some(Aggregation::Count),
+            "max" => Some(Aggregation::Max),
+            "min" => Some(Aggregation::Min),
+            "sum" => Some(Aggregation::Sum),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aggregation::Avg => "avg",
+            Aggregation::Count => "count",
+            Aggregation::Max => "max",
+            Aggregation::Min => "min",
+            Aggregation::Sum => "sum",
+        }
+    }
+
+    /// Aggregates all values of the document
+    pub fn aggregate(&self, values: impl Iterator<Item = f64>) -> f64 {
+        let mut count = 0usize;
+        let mut sum = 0f64;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        for value in values {
+            count += 1;
+            sum += value;
+            min = min.min(value);
+            max = max.max(value);
+        }
+        // Documents without values are aggregated to `0` as missing values of single-valued fields
+        match (self, count) {
+            (Aggregation::Count, _) => count as f64,
+            (Aggregation::Sum, _) => sum,
+            (_, 0) => 0.0,
+            (Aggregation::Avg, _) => sum / count as f64,
+            (Aggregation::Max, _) => max,
+            (Aggregation::Min, _) => min,
+        }
+    }
+}
+
+/// Creates iterator over the first values of documents or over aggregated values if `aggregation` is set
+pub(crate) fn fast_field_iterator<T: FastValue + SafeIntoF64>(ff: Column<T>, aggregation: Option<Aggregation>) -> Box<dyn FastFieldIterator> {
+    match aggregation {
+        None => FastFieldIteratorImpl::from_fast_field_reader(ff),
+        Some(aggregation) => Box::new(AggregatedFastFieldIteratorImpl { value: 0f64, ff, aggregation }),
+    }
+}
+
 pub(crate) struct FastFieldIteratorImpl<T: FastValue + SafeIntoF64> {
     value: f64,
     ff: Column<T>,
@@ -27,3 +91,18 @@ impl<T: FastValue + SafeIntoF64> FastFieldIterator for FastFieldIteratorImpl<T>
         &self.value
     }
 }
+
+pub(crate) struct AggregatedFastFieldIteratorImpl<T: FastValue + SafeIntoF64> {
+    value: f64,
+    ff: Column<T>,
+    aggregation: Aggregation,
+}
+
+impl<T: FastValue + SafeIntoF64> FastFieldIterator for AggregatedFastFieldIteratorImpl<T> {
+    fn advance(&mut self, doc_id: DocId) {
+        self.value = self.aggregation.aggregate(self.ff.values_for_doc(doc_id).map(SafeIntoF64::safe_into_f64));
+    }
+    fn value(&self) -> &f64 {
+        &self.value
+    }
+}
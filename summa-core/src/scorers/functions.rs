@@ -0,0 +1,114 @@
+//! Functions of `eval_expr` complementing built-in functions of `fasteval`
+
+use crate::page_rank::{inverse_quantized_page_rank, quantize_page_rank};
+
+/// Names that must not be treated as fast fields while parsing `eval_expr`
+pub(crate) const FUNCTION_NAMES: [&str; 13] = [
+    "fastsigm",
+    "iqpr",
+    "qpr",
+    "gauss",
+    "exp_decay",
+    "linear_decay",
+    "log1p",
+    "sat",
+    "clamp",
+    "if",
+    "days_since",
+    "sum",
+    "avg",
+];
+
+const SECONDS_IN_DAY: f64 = 86400.0;
+
+pub(crate) type Namespace = Box<dyn FnMut(&str, Vec<f64>) -> Option<f64> + Send + Sync>;
+
+/// Namespace resolving functions of `eval_expr`, `now` is the timestamp in seconds used by `days_since`
+pub(crate) fn build_namespace(now: f64) -> Namespace {
+    Box::new(move |name: &str, args: Vec<f64>| call(name, &args, now))
+}
+
+/// Distance from `origin` exceeding `offset`
+#[inline]
+fn decay_distance(value: f64, origin: f64, offset: f64) -> f64 {
+    ((value - origin).abs() - offset).max(0.0)
+}
+
+/// Splits optional `offset` and `decay` arguments of decay functions
+#[inline]
+fn decay_args(rest: &[f64]) -> Option<(f64, f64)> {
+    match rest {
+        [] => Some((0.0, 0.5)),
+        [offset] => Some((*offset, 0.5)),
+        [offset, decay] => Some((*offset, *decay)),
+        _ => None,
+    }
+}
+
+pub(crate) fn call(name: &str, args: &[f64], now: f64) -> Option<f64> {
+    match (name, args) {
+        ("fastsigm", [x, rest @ ..]) if rest.len() <= 1 => {
+            let x = x.abs();
+            let a = rest.first().unwrap_or(&1f64);
+            Some(x / (a + x))
+        }
+        ("iqpr", [x]) => Some(inverse_quantized_page_rank(x.abs() as u64)),
+        ("qpr", [x]) => Some(quantize_page_rank(*x) as f64),
+        // Decay functions equal to `1` at `origin` and to `decay` at `scale` from `origin` plus `offset`
+        ("gauss", [value, origin, scale, rest @ ..]) => {
+            let (offset, decay) = decay_args(rest)?;
+            let sigma_squared = -scale * scale / (2.0 * decay.ln());
+            Some((-decay_distance(*value, *origin, offset).powi(2) / (2.0 * sigma_squared)).exp())
+        }
+        ("exp_decay", [value, origin, scale, rest @ ..]) => {
+            let (offset, decay) = decay_args(rest)?;
+            Some((decay.ln() / scale * decay_distance(*value, *origin, offset)).exp())
+        }
+        ("linear_decay", [value, origin, scale, rest @ ..]) => {
+            let (offset, decay) = decay_args(rest)?;
+            let zero_distance = scale / (1.0 - decay);
+            Some(((zero_distance - decay_distance(*value, *origin, offset)) / zero_distance).max(0.0))
+        }
+        ("log1p", [x]) => Some(x.ln_1p()),
+        // Saturation reaching `0.5` at `k`
+        ("sat", [x, k]) => Some(x / (x + k)),
+        ("clamp", [x, min, max]) => Some(x.max(*min).min(*max)),
+        ("if", [condition, then, otherwise]) => Some(if *condition != 0.0 { *then } else { *otherwise }),
+        ("days_since", [timestamp]) => Some((now - timestamp) / SECONDS_IN_DAY),
+        ("sum", [_, ..]) => Some(args.iter().sum()),
+        ("avg", [_, ..]) => Some(args.iter().sum::<f64>() / args.len() as f64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::call;
+
+    fn assert_close(left: Option<f64>, right: f64) {
+        assert!((left.unwrap() - right).abs() < 1e-9, "{left:?} != {right}");
+    }
+
+    #[test]
+    fn test_functions() {
+        for decay_function in ["gauss", "exp_decay", "linear_decay"] {
+            assert_close(call(decay_function, &[10.0, 10.0, 5.0], 0.0), 1.0);
+            assert_close(call(decay_function, &[15.0, 10.0, 5.0], 0.0), 0.5);
+            assert_close(call(decay_function, &[17.0, 10.0, 5.0, 2.0, 0.3], 0.0), 0.3);
+            assert_close(call(decay_function, &[11.0, 10.0, 5.0, 2.0], 0.0), 1.0);
+        }
+        assert_close(call("linear_decay", &[30.0, 10.0, 5.0], 0.0), 0.0);
+        assert_eq!(call("gauss", &[1.0, 2.0], 0.0), None);
+
+        assert_close(call("log1p", &[0.0], 0.0), 0.0);
+        assert_close(call("sat", &[3.0, 3.0], 0.0), 0.5);
+        assert_close(call("clamp", &[7.0, 0.0, 5.0], 0.0), 5.0);
+        assert_close(call("if", &[0.0, 1.0, 2.0], 0.0), 2.0);
+        assert_close(call("if", &[1.0, 1.0, 2.0], 0.0), 1.0);
+        assert_close(call("days_since", &[0.0], 2.0 * 86400.0), 2.0);
+        assert_close(call("fastsigm", &[1.0], 0.0), 0.5);
+        assert_close(call("sum", &[1.0, 2.0, 3.0], 0.0), 6.0);
+        assert_close(call("avg", &[1.0, 2.0, 3.0], 0.0), 2.0);
+        assert_eq!(call("avg", &[], 0.0), None);
+    }
+}
@@ -1,8 +1,12 @@
+mod computed_field;
 mod eval_scorer;
 pub(crate) mod eval_scorer_tweaker;
 mod fast_field_iterator;
+mod functions;
 mod safe_into_f64;
+pub(crate) mod score_tweakers;
 mod segment_eval_scorer;
 
+pub(crate) use computed_field::ComputedField;
 pub(crate) use eval_scorer::EvalScorer;
 pub(crate) use segment_eval_scorer::SegmentEvalScorer;
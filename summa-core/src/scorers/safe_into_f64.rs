@@ -22,6 +22,17 @@ impl SafeIntoF64 for f64 {
     }
 }
 
+impl SafeIntoF64 for bool {
+    fn safe_into_f64(self) -> f64 {
+        if self {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Dates are represented by timestamps in seconds
 impl SafeIntoF64 for DateTime {
     fn safe_into_f64(self) -> f64 {
         self.into_timestamp_secs() as f64
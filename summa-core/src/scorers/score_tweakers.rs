@@ -0,0 +1,218 @@
+use tantivy::collector::{ScoreSegmentTweaker, ScoreTweaker};
+use tantivy::query::{EnableScoring, Query, Scorer};
+use tantivy::schema::{FieldType, Schema};
+use tantivy::{DocId, DocSet, Score, Searcher, SegmentReader, TantivyError};
+
+use crate::errors::{Error, SummaResult, ValidationError};
+use crate::scorers::eval_scorer_tweaker::{EvalScorerSegmentScoreTweaker, EvalScorerTweaker};
+use crate::scorers::fast_field_iterator::FastFieldIterator;
+use crate::scorers::segment_eval_scorer::{fast_field_to_iter, fast_field_to_iter_async};
+
+/// Passes relevance score computed by the query as is
+pub(crate) struct RelevanceScoreTweaker;
+
+#[async_trait]
+impl ScoreTweaker<Score> for RelevanceScoreTweaker {
+    type Child = fn(DocId, Score) -> Score;
+
+    fn segment_tweaker(&self, _: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(|_, score| score)
+    }
+
+    async fn segment_tweaker_async(&self, _: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(|_, score| score)
+    }
+}
+
+pub(crate) struct FastFieldSegmentScoreTweaker {
+    fast_field_iterator: Box<dyn FastFieldIterator>,
+}
+
+impl ScoreSegmentTweaker<f64> for FastFieldSegmentScoreTweaker {
+    fn score(&mut self, doc: DocId, _: Score) -> f64 {
+        self.fast_field_iterator.advance(doc);
+        *self.fast_field_iterator.value()
+    }
+}
+
+/// Replaces score with the value of numeric fast field, the same way as `order_by` scorer does
+pub(crate) struct FastFieldScoreTweaker {
+    field_name: String,
+    schema: Schema,
+}
+
+impl FastFieldScoreTweaker {
+    pub fn new(field_name: &str, schema: &Schema) -> SummaResult<Self> {
+        let field = schema
+            .get_field(field_name)
+            .map_err(|_| ValidationError::MissingField(field_name.to_string()))?;
+        let field_entry = schema.get_field_entry(field);
+        if !field_entry.is_fast() {
+            return Err(ValidationError::RequiredFastField(field_name.to_string()).into());
+        }
+        if !matches!(
+            field_entry.field_type(),
+            FieldType::U64(_) | FieldType::I64(_) | FieldType::F64(_) | FieldType::Date(_) | FieldType::Bool(_)
+        ) {
+            return Err(Error::InvalidFieldType(field_name.to_string(), field_entry.field_type().clone()));
+        }
+        Ok(FastFieldScoreTweaker {
+            field_name: field_name.to_string(),
+            schema: schema.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl ScoreTweaker<f64> for FastFieldScoreTweaker {
+    type Child = FastFieldSegmentScoreTweaker;
+
+    fn segment_tweaker(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(FastFieldSegmentScoreTweaker {
+            fast_field_iterator: fast_field_to_iter(&self.schema, segment_reader, &self.field_name, None)
+                .map_err(|e| TantivyError::InvalidArgument(e.to_string()))?,
+        })
+    }
+
+    async fn segment_tweaker_async(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(FastFieldSegmentScoreTweaker {
+            fast_field_iterator: fast_field_to_iter_async(&self.schema, segment_reader, &self.field_name, None)
+                .await
+                .map_err(|e| TantivyError::InvalidArgument(e.to_string()))?,
+        })
+    }
+}
+
+/// Scores documents that have to be passed in ascending order of their ids
+pub(crate) struct QuerySegmentScoreTweaker {
+    scorer: Box<dyn Scorer>,
+}
+
+impl ScoreSegmentTweaker<f64> for QuerySegmentScoreTweaker {
+    fn score(&mut self, doc: DocId, _: Score) -> f64 {
+        if self.scorer.doc() < doc {
+            self.scorer.seek(doc);
+        }
+        if self.scorer.doc() == doc {
+            self.scorer.score() as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Replaces score with the relevance score of another query, documents not matching the query are scored with `0`
+pub(crate) struct QueryScoreTweaker {
+    query: Box<dyn Query>,
+    searcher: Searcher,
+}
+
+impl QueryScoreTweaker {
+    pub fn new(query: Box<dyn Query>, searcher: Searcher) -> Self {
+        QueryScoreTweaker { query, searcher }
+    }
+}
+
+#[async_trait]
+impl ScoreTweaker<f64> for QueryScoreTweaker {
+    type Child = QuerySegmentScoreTweaker;
+
+    fn segment_tweaker(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        let weight = self.query.weight(EnableScoring::enabled_from_searcher(&self.searcher))?;
+        Ok(QuerySegmentScoreTweaker {
+            scorer: weight.scorer(segment_reader, 1.0)?,
+        })
+    }
+
+    async fn segment_tweaker_async(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        let weight = self.query.weight_async(EnableScoring::enabled_from_searcher(&self.searcher)).await?;
+        Ok(QuerySegmentScoreTweaker {
+            scorer: weight.scorer_async(segment_reader, 1.0).await?,
+        })
+    }
+}
+
+pub(crate) enum RescoreSegmentTweaker {
+    EvalExpr(Box<EvalScorerSegmentScoreTweaker>),
+    Query(QuerySegmentScoreTweaker),
+}
+
+impl ScoreSegmentTweaker<f64> for RescoreSegmentTweaker {
+    fn score(&mut self, doc: DocId, score: Score) -> f64 {
+        match self {
+            RescoreSegmentTweaker::EvalExpr(segment_score_tweaker) => segment_score_tweaker.score(doc, score),
+            RescoreSegmentTweaker::Query(segment_score_tweaker) => segment_score_tweaker.score(doc, score),
+        }
+    }
+}
+
+/// Second phase scorer of `RescoreConfig`
+pub(crate) enum RescoreTweaker {
+    EvalExpr(EvalScorerTweaker),
+    Query(QueryScoreTweaker),
+}
+
+#[async_trait]
+impl ScoreTweaker<f64> for RescoreTweaker {
+    type Child = RescoreSegmentTweaker;
+
+    fn segment_tweaker(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(match self {
+            RescoreTweaker::EvalExpr(score_tweaker) => RescoreSegmentTweaker::EvalExpr(Box::new(score_tweaker.segment_tweaker(segment_reader)?)),
+            RescoreTweaker::Query(score_tweaker) => RescoreSegmentTweaker::Query(score_tweaker.segment_tweaker(segment_reader)?),
+        })
+    }
+
+    async fn segment_tweaker_async(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(match self {
+            RescoreTweaker::EvalExpr(score_tweaker) => RescoreSegmentTweaker::EvalExpr(Box::new(score_tweaker.segment_tweaker_async(segment_reader).await?)),
+            RescoreTweaker::Query(score_tweaker) => RescoreSegmentTweaker::Query(score_tweaker.segment_tweaker_async(segment_reader).await?),
+        })
+    }
+}
+
+pub(crate) enum FusionSegmentScoreTweaker {
+    Relevance,
+    EvalExpr(Box<EvalScorerSegmentScoreTweaker>),
+    OrderBy(FastFieldSegmentScoreTweaker),
+}
+
+impl ScoreSegmentTweaker<f64> for FusionSegmentScoreTweaker {
+    fn score(&mut self, doc: DocId, score: Score) -> f64 {
+        match self {
+            FusionSegmentScoreTweaker::Relevance => score as f64,
+            FusionSegmentScoreTweaker::EvalExpr(segment_score_tweaker) => segment_score_tweaker.score(doc, score),
+            FusionSegmentScoreTweaker::OrderBy(segment_score_tweaker) => segment_score_tweaker.score(doc, score),
+        }
+    }
+}
+
+/// Scorer of a source of `FusionCollector` or of collapsing `TopDocsCollector` mapping scores of all kinds to `f64`
+pub(crate) enum FusionScoreTweaker {
+    Relevance,
+    EvalExpr(EvalScorerTweaker),
+    OrderBy(FastFieldScoreTweaker),
+}
+
+#[async_trait]
+impl ScoreTweaker<f64> for FusionScoreTweaker {
+    type Child = FusionSegmentScoreTweaker;
+
+    fn segment_tweaker(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(match self {
+            FusionScoreTweaker::Relevance => FusionSegmentScoreTweaker::Relevance,
+            FusionScoreTweaker::EvalExpr(score_tweaker) => FusionSegmentScoreTweaker::EvalExpr(Box::new(score_tweaker.segment_tweaker(segment_reader)?)),
+            FusionScoreTweaker::OrderBy(score_tweaker) => FusionSegmentScoreTweaker::OrderBy(score_tweaker.segment_tweaker(segment_reader)?),
+        })
+    }
+
+    async fn segment_tweaker_async(&self, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(match self {
+            FusionScoreTweaker::Relevance => FusionSegmentScoreTweaker::Relevance,
+            FusionScoreTweaker::EvalExpr(score_tweaker) => {
+                FusionSegmentScoreTweaker::EvalExpr(Box::new(score_tweaker.segment_tweaker_async(segment_reader).await?))
+            }
+            FusionScoreTweaker::OrderBy(score_tweaker) => FusionSegmentScoreTweaker::OrderBy(score_tweaker.segment_tweaker_async(segment_reader).await?),
+        })
+    }
+}
@@ -1,10 +1,12 @@
 use fasteval2::{Compiler, Evaler, Instruction};
+use tantivy::columnar::{Column, ColumnType};
 use tantivy::schema::{FieldType, Schema};
-use tantivy::{DocId, SegmentReader};
+use tantivy::{DocId, SegmentReader, TantivyError};
 
-use super::fast_field_iterator::{FastFieldIterator, FastFieldIteratorImpl};
+use super::eval_scorer::FastFieldVariable;
+use super::fast_field_iterator::{fast_field_iterator, Aggregation, FastFieldIterator};
+use super::functions::{build_namespace, Namespace};
 use crate::errors::{Error, SummaResult, ValidationError};
-use crate::page_rank::inverse_quantized_page_rank;
 
 /// Responsible for evaluation `fasteval` formula against documents and fastfields to receive document score
 pub(crate) struct SegmentEvalScorer {
@@ -13,54 +15,55 @@ pub(crate) struct SegmentEvalScorer {
     boxed_original_score: Box<f64>,
     _boxed_now: Box<f64>,
     fast_fields_iterators: Vec<Box<dyn FastFieldIterator>>,
-    namespace: fn(&str, Vec<f64>) -> Option<f64>,
+    namespace: Namespace,
 }
 
-fn fast_field_to_iter(schema: &Schema, segment_reader: &SegmentReader, field_name: &str) -> SummaResult<Box<dyn FastFieldIterator>> {
+pub(crate) fn fast_field_to_iter(
+    schema: &Schema,
+    segment_reader: &SegmentReader,
+    field_name: &str,
+    aggregation: Option<Aggregation>,
+) -> SummaResult<Box<dyn FastFieldIterator>> {
     let field = schema.get_field(field_name)?;
     let field_type = schema.get_field_entry(field).field_type();
+    let invalid_fast_field_type = |tantivy_error| ValidationError::InvalidFastFieldType {
+        field: field_name.to_owned(),
+        field_type: field_type.to_owned(),
+        tantivy_error,
+    };
+    let fast_fields = segment_reader.fast_fields();
     let fast_field = match field_type {
-        FieldType::U64(_) => FastFieldIteratorImpl::from_fast_field_reader(segment_reader.fast_fields().u64(field_name).map_err(|tantivy_error| {
-            ValidationError::InvalidFastFieldType {
-                field: field_name.to_owned(),
-                field_type: field_type.to_owned(),
-                tantivy_error,
-            }
-        })?),
-        FieldType::I64(_) => FastFieldIteratorImpl::from_fast_field_reader(segment_reader.fast_fields().i64(field_name).map_err(|tantivy_error| {
-            ValidationError::InvalidFastFieldType {
-                field: field_name.to_owned(),
-                field_type: field_type.to_owned(),
-                tantivy_error,
-            }
-        })?),
-        FieldType::F64(_) => FastFieldIteratorImpl::from_fast_field_reader(segment_reader.fast_fields().f64(field_name).map_err(|tantivy_error| {
-            ValidationError::InvalidFastFieldType {
-                field: field_name.to_owned(),
-                field_type: field_type.to_owned(),
-                tantivy_error,
-            }
-        })?),
-        FieldType::Date(_) => FastFieldIteratorImpl::from_fast_field_reader(segment_reader.fast_fields().date(field_name).map_err(|tantivy_error| {
-            ValidationError::InvalidFastFieldType {
-                field: field_name.to_owned(),
-                field_type: field_type.to_owned(),
-                tantivy_error,
-            }
-        })?),
+        FieldType::U64(_) => fast_field_iterator(fast_fields.u64(field_name).map_err(invalid_fast_field_type)?, aggregation),
+        FieldType::I64(_) => fast_field_iterator(fast_fields.i64(field_name).map_err(invalid_fast_field_type)?, aggregation),
+        FieldType::F64(_) => fast_field_iterator(fast_fields.f64(field_name).map_err(invalid_fast_field_type)?, aggregation),
+        FieldType::Date(_) => fast_field_iterator(fast_fields.date(field_name).map_err(invalid_fast_field_type)?, aggregation),
+        FieldType::Bool(_) => fast_field_iterator(fast_fields.bool(field_name).map_err(invalid_fast_field_type)?, aggregation),
         field_type => return Err(Error::InvalidFieldType(field_name.to_owned(), field_type.to_owned())),
     };
     Ok(fast_field)
 }
 
-async fn fast_field_to_iter_async(schema: &Schema, segment_reader: &SegmentReader, field_name: &str) -> SummaResult<Box<dyn FastFieldIterator>> {
+pub(crate) async fn fast_field_to_iter_async(
+    schema: &Schema,
+    segment_reader: &SegmentReader,
+    field_name: &str,
+    aggregation: Option<Aggregation>,
+) -> SummaResult<Box<dyn FastFieldIterator>> {
     let field = schema.get_field(field_name)?;
     let field_type = schema.get_field_entry(field).field_type();
     let fast_field = match field_type {
-        FieldType::U64(_) => FastFieldIteratorImpl::from_fast_field_reader(segment_reader.fast_fields().u64_async(field_name).await?),
-        FieldType::I64(_) => FastFieldIteratorImpl::from_fast_field_reader(segment_reader.fast_fields().i64_async(field_name).await?),
-        FieldType::F64(_) => FastFieldIteratorImpl::from_fast_field_reader(segment_reader.fast_fields().f64_async(field_name).await?),
-        FieldType::Date(_) => FastFieldIteratorImpl::from_fast_field_reader(segment_reader.fast_fields().date_async(field_name).await?),
+        FieldType::U64(_) => fast_field_iterator(segment_reader.fast_fields().u64_async(field_name).await?, aggregation),
+        FieldType::I64(_) => fast_field_iterator(segment_reader.fast_fields().i64_async(field_name).await?, aggregation),
+        FieldType::F64(_) => fast_field_iterator(segment_reader.fast_fields().f64_async(field_name).await?, aggregation),
+        FieldType::Date(_) => fast_field_iterator(segment_reader.fast_fields().date_async(field_name).await?, aggregation),
+        FieldType::Bool(_) => {
+            let column: Option<Column<bool>> = match segment_reader.fast_fields().dynamic_column_handle_async(field_name, ColumnType::Bool).await? {
+                Some(dynamic_column_handle) => dynamic_column_handle.open_async().await?.into(),
+                None => None,
+            };
+            let column = column.ok_or_else(|| TantivyError::SchemaError(format!("Field `{field_name}` is missing or is not configured as a fast field.")))?;
+            fast_field_iterator(column, aggregation)
+        }
         field_type => return Err(Error::InvalidFieldType(field_name.to_owned(), field_type.to_owned())),
     };
     Ok(fast_field)
@@ -74,27 +77,13 @@ impl SegmentEvalScorer {
         schema: &Schema,
         parser: &fasteval2::Parser,
         eval_expr: &str,
-        var_names: &Vec<String>,
+        variables: &[FastFieldVariable],
     ) -> SummaResult<SegmentEvalScorer> {
         let mut slab = fasteval2::Slab::new();
 
-        let mut namespace = |name: &str, args: Vec<f64>| -> Option<f64> {
-            match name {
-                "fastsigm" => {
-                    let x = args[0].abs();
-                    let a = args.get(1).unwrap_or(&1f64);
-                    Some(x / (*a + x))
-                }
-                "iqpr" => {
-                    let x = args[0].abs() as u64;
-                    Some(inverse_quantized_page_rank(x))
-                }
-                _ => None,
-            }
-        };
-
         let boxed_original_score = Box::new(0f64);
         let boxed_now = Box::new(instant::now() / 1000.0);
+        let mut namespace = build_namespace(*boxed_now);
 
         // Set default variables
         unsafe {
@@ -105,10 +94,10 @@ impl SegmentEvalScorer {
         let mut fast_fields_iterators = vec![];
 
         // Set fast fields
-        for var_name in var_names {
-            let fast_field_iterator = fast_field_to_iter(schema, segment_reader, var_name)?;
+        for variable in variables {
+            let fast_field_iterator = fast_field_to_iter(schema, segment_reader, &variable.field_name, variable.aggregation)?;
             unsafe {
-                slab.ps.add_unsafe_var(var_name.to_owned(), fast_field_iterator.value());
+                slab.ps.add_unsafe_var(variable.var_name.to_owned(), fast_field_iterator.value());
             }
             fast_fields_iterators.push(fast_field_iterator);
         }
@@ -147,27 +136,13 @@ impl SegmentEvalScorer {
         schema: &Schema,
         parser: &fasteval2::Parser,
         eval_expr: &str,
-        var_names: &Vec<String>,
+        variables: &[FastFieldVariable],
     ) -> SummaResult<SegmentEvalScorer> {
         let mut slab = fasteval2::Slab::new();
 
-        let mut namespace = |name: &str, args: Vec<f64>| -> Option<f64> {
-            match name {
-                "fastsigm" => {
-                    let x = args[0].abs();
-                    let a = args.get(1).unwrap_or(&1f64);
-                    Some(x / (*a + x))
-                }
-                "iqpr" => {
-                    let x = args[0].abs() as u64;
-                    Some(inverse_quantized_page_rank(x))
-                }
-                _ => None,
-            }
-        };
-
         let boxed_original_score = Box::new(0f64);
         let boxed_now = Box::new(instant::now() / 1000.0);
+        let mut namespace = build_namespace(*boxed_now);
 
         // Set default variables
         unsafe {
@@ -178,10 +153,10 @@ impl SegmentEvalScorer {
         let mut fast_fields_iterators = vec![];
 
         // Set fast fields
-        for var_name in var_names {
-            let fast_field_iterator = fast_field_to_iter_async(schema, segment_reader, var_name).await?;
+        for variable in variables {
+            let fast_field_iterator = fast_field_to_iter_async(schema, segment_reader, &variable.field_name, variable.aggregation).await?;
             unsafe {
-                slab.ps.add_unsafe_var(var_name.to_owned(), fast_field_iterator.value());
+                slab.ps.add_unsafe_var(variable.var_name.to_owned(), fast_field_iterator.value());
             }
             fast_fields_iterators.push(fast_field_iterator);
         }
@@ -14,25 +14,43 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let serde_default_structs = &[
         "dag_pb.PBNode",
         "dag_pb.PBLink",
+        "summa.proto.CollapseConfig",
+        "summa.proto.DropProcessor",
+        "summa.proto.FacetCollector",
+        "summa.proto.FusionCollector",
+        "summa.proto.FusionSource",
+        "summa.proto.HnswConfig",
         "summa.proto.IndexAttributes",
         "summa.proto.MoreLikeThisQuery",
         "summa.proto.NerMatchConfig",
         "summa.proto.PhraseQuery",
         "summa.proto.QueryParserConfig",
+        "summa.proto.RegexExtractProcessor",
+        "summa.proto.RescoreConfig",
         "summa.proto.ReservoirSamplingCollector",
+        "summa.proto.SearchLimits",
+        "summa.proto.SnippetConfig",
         "summa.proto.TopDocsCollector",
+        "summa.proto.TypedAggregation",
+        "summa.proto.VectorField",
+        "summa.proto.VectorQuery",
         "unixfs.Data",
     ];
+    // Request maps that are a part of `CollectorCache` keys must have a stable order
+    let btree_maps = &[
+        ".summa.proto.TypedAggregation.sub_aggregations",
+        ".summa.proto.TypedAggregationCollector.aggregations",
+    ];
     #[cfg(feature = "grpc")]
-    build_tonic(files, serde_default_structs)?;
+    build_tonic(files, serde_default_structs, btree_maps)?;
     #[cfg(not(feature = "grpc"))]
-    build_prost(files, serde_default_structs)?;
+    build_prost(files, serde_default_structs, btree_maps)?;
     Ok(())
 }
 
 #[cfg(feature = "grpc")]
-fn build_tonic(files: &[&str], serde_default_structs: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
-    let builder = tonic_build::configure();
+fn build_tonic(files: &[&str], serde_default_structs: &[&str], btree_maps: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let builder = tonic_build::configure().btree_map(btree_maps);
     let mut builder_ref = builder
         .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
         .type_attribute(".", "#[serde(rename_all = \"snake_case\")]");
@@ -45,9 +63,10 @@ fn build_tonic(files: &[&str], serde_default_structs: &[&str]) -> Result<(), Box
 }
 
 #[cfg(not(feature = "grpc"))]
-fn build_prost(files: &[&str], serde_default_structs: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+fn build_prost(files: &[&str], serde_default_structs: &[&str], btree_maps: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
     let mut builder = prost_build::Config::new();
     let mut builder_ref = builder
+        .btree_map(btree_maps)
         .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
         .type_attribute(".", "#[serde(rename_all = \"snake_case\")]");
     for serde_default_struct in serde_default_structs {
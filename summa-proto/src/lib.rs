@@ -1,6 +1,6 @@
 pub mod proto_traits;
 
-#[allow(clippy::derive_partial_eq_without_eq)]
+#[allow(clippy::derive_partial_eq_without_eq, clippy::large_enum_variant)]
 /// Protobuf messages for communicating with Summa
 ///
 /// ```rust,no_run
@@ -42,7 +42,8 @@ pub mod proto_traits;
 ///             ],
 ///             is_fieldnorms_scoring_enabled: None,
 ///             load_cache: None,
-///             store_cache: None
+///             store_cache: None,
+///             timeout_ms: None,
 ///         })
 ///         .await
 ///         .expect("cannot search");
@@ -43,6 +43,11 @@ pub mod shortcuts {
                 explain: false,
                 fields: Vec::new(),
                 excluded_fields: Vec::new(),
+                collapse: None,
+                snippet_field_configs: HashMap::new(),
+                min_score: None,
+                score_normalization: proto::ScoreNormalization::None.into(),
+                rescore: None,
             })),
         }
     }
@@ -59,6 +64,11 @@ pub mod shortcuts {
                 explain: false,
                 fields: Vec::new(),
                 excluded_fields: Vec::new(),
+                collapse: None,
+                snippet_field_configs: HashMap::new(),
+                min_score: None,
+                score_normalization: proto::ScoreNormalization::None.into(),
+                rescore: None,
             })),
         }
     }
@@ -72,6 +82,10 @@ pub mod shortcuts {
             }),
             position,
             snippets: HashMap::new(),
+            inner_hits: Vec::new(),
+            group_size: None,
+            normalized_score: None,
+            source_ranks: Vec::new(),
         }
     }
 
@@ -80,6 +94,7 @@ pub mod shortcuts {
             collector_output: Some(proto::collector_output::CollectorOutput::Documents(proto::DocumentsCollectorOutput {
                 scored_documents,
                 has_next,
+                groups_count: None,
             })),
         }
     }
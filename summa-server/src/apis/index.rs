@@ -5,17 +5,18 @@
 use std::collections::HashSet;
 use std::error::Error;
 use std::io::ErrorKind;
+use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use summa_core::components::{IndexHolder, NamedFieldDocument};
+use summa_core::components::{IndexHolder, IndexingOutcome, NamedFieldDocument};
 use summa_core::configs::ConfigProxy;
 use summa_core::utils::sync::Handler;
 use summa_core::validators;
 use summa_proto::proto;
 use summa_proto::proto::DocumentsResponse;
 use tokio_stream::wrappers::ReceiverStream;
-use tokio_stream::StreamExt;
+use tokio_stream::{Stream, StreamExt};
 use tonic::{Request, Response, Status, Streaming};
 use tracing::{info, info_span};
 
@@ -82,12 +83,22 @@ impl proto::index_api_server::IndexApi for IndexApiImpl {
         Ok(Response::new(response))
     }
 
+    async fn begin_transaction(&self, proto_request: Request<proto::BeginTransactionRequest>) -> Result<Response<proto::BeginTransactionResponse>, Status> {
+        let proto_request = proto_request.into_inner();
+        let index_holder = self.index_service.get_index_holder(&proto_request.index_name).await?;
+        let transaction_id = self
+            .index_service
+            .begin_transaction(&index_holder, proto_request.timeout_ms.map(Duration::from_millis))
+            .await?;
+        Ok(Response::new(proto::BeginTransactionResponse { transaction_id }))
+    }
+
     async fn commit_index(&self, proto_request: Request<proto::CommitIndexRequest>) -> Result<Response<proto::CommitIndexResponse>, Status> {
         let now = Instant::now();
         let proto_request = proto_request.into_inner();
         let index_holder = self.index_service.get_index_holder(&proto_request.index_name).await?;
         self.index_service
-            .commit_and_restart_consumption(&index_holder, proto_request.with_hotcache)
+            .commit_and_restart_consumption(&index_holder, proto_request.transaction_id.as_deref(), proto_request.with_hotcache)
             .await?;
         Ok(Response::new(proto::CommitIndexResponse {
             elapsed_secs: now.elapsed().as_secs_f64(),
@@ -189,7 +200,7 @@ impl proto::index_api_server::IndexApi for IndexApiImpl {
         &self,
         request: Request<Streaming<proto::IndexDocumentStreamRequest>>,
     ) -> Result<Response<proto::IndexDocumentStreamResponse>, Status> {
-        let (mut success_docs, mut failed_docs) = (0u64, 0u64);
+        let (mut success_docs, mut failed_docs, mut skipped_docs) = (0u64, 0u64, 0u64);
         let mut elapsed_secs = 0f64;
         let mut in_stream = request.into_inner();
         let mut last_status_report = Instant::now();
@@ -199,7 +210,10 @@ impl proto::index_api_server::IndexApi for IndexApiImpl {
                     info!(action = "received_chunk", index_name = chunk.index_name, documents = ?chunk.documents.len());
                     let now = Instant::now();
                     let index_holder = self.index_service.get_index_holder(&chunk.index_name).await?;
-                    let (success_bulk_docs, failed_bulk_docs) = index_holder
+                    if let Some(transaction_id) = &chunk.transaction_id {
+                        self.index_service.touch_transaction(index_holder.index_name(), transaction_id).await?;
+                    }
+                    let (success_bulk_docs, failed_bulk_docs, skipped_bulk_docs) = index_holder
                         .index_bulk(
                             &chunk.documents,
                             chunk.conflict_strategy.and_then(proto::ConflictStrategy::from_i32),
@@ -210,8 +224,14 @@ impl proto::index_api_server::IndexApi for IndexApiImpl {
                     elapsed_secs += now.elapsed().as_secs_f64();
                     success_docs += success_bulk_docs;
                     failed_docs += failed_bulk_docs;
+                    skipped_docs += skipped_bulk_docs;
                     if last_status_report.elapsed().as_secs_f64() > 60f64 {
-                        info!(action = "indexed", success_docs = success_docs, failed_docs = failed_docs);
+                        info!(
+                            action = "indexed",
+                            success_docs = success_docs,
+                            failed_docs = failed_docs,
+                            skipped_docs = skipped_docs
+                        );
                         last_status_report = Instant::now();
                     }
                     if self.index_service.should_terminate() {
@@ -230,6 +250,7 @@ impl proto::index_api_server::IndexApi for IndexApiImpl {
         let response = proto::IndexDocumentStreamResponse {
             success_docs,
             failed_docs,
+            skipped_docs,
             elapsed_secs,
         };
         Ok(Response::new(response))
@@ -237,13 +258,16 @@ impl proto::index_api_server::IndexApi for IndexApiImpl {
 
     async fn index_document(&self, request: Request<proto::IndexDocumentRequest>) -> Result<Response<proto::IndexDocumentResponse>, Status> {
         let proto_request = request.into_inner();
-        self.index_service
+        let indexing_outcome = self
+            .index_service
             .get_index_holder(&proto_request.index_name)
             .await?
             .index_document(&proto_request.document, proto_request.skip_updated_at_modification)
             .await
             .map_err(crate::errors::Error::from)?;
-        let response = proto::IndexDocumentResponse {};
+        let response = proto::IndexDocumentResponse {
+            is_skipped: indexing_outcome == IndexingOutcome::Skipped,
+        };
         Ok(Response::new(response))
     }
 
@@ -255,6 +279,26 @@ impl proto::index_api_server::IndexApi for IndexApiImpl {
         Ok(Response::new(response))
     }
 
+    type reindexStream = Pin<Box<dyn Stream<Item = Result<proto::ReindexResponse, Status>> + Send>>;
+
+    async fn reindex(&self, proto_request: Request<proto::ReindexRequest>) -> Result<Response<Self::reindexStream>, Status> {
+        let progress_receiver = self.index_service.reindex(proto_request.into_inner()).await?;
+        let progress_stream = ReceiverStream::new(progress_receiver).map(|progress| progress.map_err(Status::from));
+        Ok(Response::new(Box::pin(progress_stream) as Self::reindexStream))
+    }
+
+    async fn rollback_index(&self, proto_request: Request<proto::RollbackIndexRequest>) -> Result<Response<proto::RollbackIndexResponse>, Status> {
+        let now = Instant::now();
+        let proto_request = proto_request.into_inner();
+        let index_holder = self.index_service.get_index_holder(&proto_request.index_name).await?;
+        self.index_service
+            .rollback_and_restart_consumption(&index_holder, proto_request.transaction_id.as_deref())
+            .await?;
+        Ok(Response::new(proto::RollbackIndexResponse {
+            elapsed_secs: now.elapsed().as_secs_f64(),
+        }))
+    }
+
     async fn set_index_alias(&self, proto_request: Request<proto::SetIndexAliasRequest>) -> Result<Response<proto::SetIndexAliasResponse>, Status> {
         let proto_request = proto_request.into_inner();
         let mut server_config = self.server_config.write().await;
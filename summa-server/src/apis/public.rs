@@ -4,6 +4,7 @@
 
 use std::time::Instant;
 
+use summa_core::components::SearchDeadline;
 use summa_proto::proto;
 use tonic::{Request, Response, Status};
 use tracing::{info_span, Instrument};
@@ -28,9 +29,10 @@ impl proto::public_api_server::PublicApi for PublicApiImpl {
     async fn search(&self, proto_request: Request<proto::SearchRequest>) -> Result<Response<proto::SearchResponse>, Status> {
         let proto_request = proto_request.into_inner();
         let now = Instant::now();
+        let search_deadline = SearchDeadline::from_timeout_ms(proto_request.timeout_ms);
         let collector_outputs = self
             .index_service
-            .constrained_search(proto_request)
+            .constrained_search(proto_request, &search_deadline)
             .instrument(info_span!("search"))
             .await
             .map_err(crate::errors::Error::from)?;
@@ -38,6 +40,7 @@ impl proto::public_api_server::PublicApi for PublicApiImpl {
         Ok(Response::new(proto::SearchResponse {
             collector_outputs,
             elapsed_secs,
+            timed_out: search_deadline.is_exceeded(),
         }))
     }
 }
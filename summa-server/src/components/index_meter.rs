@@ -12,6 +12,9 @@ pub struct IndexMeter {
     deleted_memory_usage: Histogram<u64>,
     store_memory_usage: Histogram<u64>,
     fields_memory_usage: Histogram<u64>,
+    collector_cache_hits: Histogram<u64>,
+    collector_cache_misses: Histogram<u64>,
+    expired_documents_count: Histogram<u64>,
 }
 impl IndexMeter {
     pub fn new(meter: Meter) -> IndexMeter {
@@ -36,6 +39,18 @@ impl IndexMeter {
                 .with_description("Memory usage per fields in bytes")
                 .with_unit(Unit::new("bytes"))
                 .init(),
+            collector_cache_hits: meter
+                .u64_histogram("collector_cache_hits")
+                .with_description("Collector cache hits count")
+                .init(),
+            collector_cache_misses: meter
+                .u64_histogram("collector_cache_misses")
+                .with_description("Collector cache misses count")
+                .init(),
+            expired_documents_count: meter
+                .u64_histogram("expired_documents_count")
+                .with_description("Expired documents deleted since opening the index")
+                .init(),
         }
     }
 
@@ -74,6 +89,11 @@ impl IndexMeter {
             ];
             self.fields_memory_usage.record(*memory_usage, field_keys);
         }
+        let (collector_cache_hits, collector_cache_misses) = index_holder.collector_cache_hits_and_misses();
+        let index_keys = &[KeyValue::new("index_name", index_holder.index_name().to_string())];
+        self.collector_cache_hits.record(collector_cache_hits, index_keys);
+        self.collector_cache_misses.record(collector_cache_misses, index_keys);
+        self.expired_documents_count.record(index_holder.expired_documents_count(), index_keys);
         Ok(())
     }
 }
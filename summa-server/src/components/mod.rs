@@ -5,6 +5,6 @@ mod consumers;
 #[cfg(feature = "metrics")]
 mod index_meter;
 
-pub(crate) use consumer_manager::{ConsumerManager, PreparedConsumption};
+pub(crate) use consumer_manager::{ConsumerManager, PreparedConsumption, StoppedConsumption};
 #[cfg(feature = "metrics")]
 pub(crate) use index_meter::IndexMeter;
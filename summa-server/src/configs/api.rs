@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use summa_core::errors::BuilderError;
+use summa_proto::proto::SearchLimits;
 
 #[derive(Builder, Clone, Debug, Serialize, Deserialize)]
 #[builder(default, build_fn(error = "BuilderError"))]
@@ -14,6 +15,12 @@ pub struct Config {
     pub keep_alive_timeout_seconds: u64,
     pub max_connection_age_seconds: u64,
     pub max_connection_age_grace_seconds: u64,
+    /// Limits of searches made through the search API, stricter limits of the index are applied too
+    #[serde(default)]
+    pub search_limits: Option<SearchLimits>,
+    /// Limits of searches made through the public API
+    #[serde(default)]
+    pub public_search_limits: Option<SearchLimits>,
 }
 
 impl Default for Config {
@@ -25,6 +32,8 @@ impl Default for Config {
             keep_alive_timeout_seconds: 5,
             max_connection_age_seconds: 60,
             max_connection_age_grace_seconds: 300,
+            search_limits: None,
+            public_search_limits: None,
         }
     }
 }
@@ -11,6 +11,8 @@ pub enum ValidationError {
     ExistingConsumer(String),
     #[error("existing_index_error: {0}")]
     ExistingIndex(String),
+    #[error("existing_transaction_error: {0}")]
+    ExistingTransaction(String),
     #[error("invalid_argument: {0}")]
     InvalidArgument(String),
     #[error("invalid_header_name: {0}")]
@@ -27,6 +29,8 @@ pub enum ValidationError {
     MissingField(String),
     #[error("missing_query_error")]
     MissingQuery,
+    #[error("missing_transaction_error: {0}")]
+    MissingTransaction(String),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -51,6 +55,8 @@ pub enum Error {
     Lock(#[from] tokio::sync::TryLockError),
     #[error("not_allowed_error")]
     NotAllowed,
+    #[error("reindex_error: {0}")]
+    Reindex(String),
     #[error("tantivy_error: {0}")]
     Tantivy(#[from] tantivy::TantivyError),
     #[error("timeout_error: {0}")]
@@ -95,9 +101,12 @@ impl From<Error> for tonic::Status {
                     _ => tonic::Code::Internal,
                 },
                 Error::Validation(ValidationError::MissingIndex(_)) => tonic::Code::NotFound,
+                Error::Validation(ValidationError::ExistingTransaction(_)) => tonic::Code::FailedPrecondition,
+                Error::Validation(ValidationError::MissingTransaction(_)) => tonic::Code::NotFound,
                 Error::Validation(_) => tonic::Code::InvalidArgument,
                 Error::NotAllowed => tonic::Code::PermissionDenied,
                 Error::Lock(_) => tonic::Code::FailedPrecondition,
+                Error::Reindex(_) => tonic::Code::Aborted,
                 _ => tonic::Code::Internal,
             },
             format!("{error}"),
@@ -79,10 +79,12 @@ pub fn file(log_path: &Path) -> io::Result<Vec<WorkerGuard>> {
     std::fs::create_dir_all(log_path)?;
     let file_writer_request = create_writer(log_path, "request", &mut guards)?;
     let file_writer_query = create_writer(log_path, "query", &mut guards)?;
+    let file_writer_slow_query = create_writer(log_path, "slow_query", &mut guards)?;
     let file_writer_summa = create_writer(log_path, "summa", &mut guards)?;
 
     let filter_layer_request = EnvFilter::new(REQUEST_ENV_FILTER);
     let filter_layer_query = EnvFilter::new("query");
+    let filter_layer_slow_query = EnvFilter::new("slow_query");
 
     let request_layer = fmt::layer()
         .with_thread_names(false)
@@ -94,13 +96,24 @@ pub fn file(log_path: &Path) -> io::Result<Vec<WorkerGuard>> {
         .with_target(false)
         .with_writer(file_writer_query)
         .with_filter(filter_layer_query);
+    let slow_query_layer = fmt::layer()
+        .json()
+        .with_thread_names(false)
+        .with_target(false)
+        .with_writer(file_writer_slow_query)
+        .with_filter(filter_layer_slow_query);
     let default_layer = fmt::layer()
         .with_thread_names(false)
         .with_target(true)
         .with_level(true)
         .with_writer(file_writer_summa)
         .with_filter(EnvFilter::new(ENV_FILTER));
-    tracing_subscriber::registry().with(request_layer).with(query_layer).with(default_layer).init();
+    tracing_subscriber::registry()
+        .with(request_layer)
+        .with(query_layer)
+        .with(slow_query_layer)
+        .with(default_layer)
+        .init();
 
     Ok(guards)
 }
@@ -210,6 +210,7 @@ mod tests {
             .commit_index(proto::CommitIndexRequest {
                 index_name: "test_index".to_string(),
                 with_hotcache: false,
+                transaction_id: None,
             })
             .await?;
         index_api_client
@@ -223,6 +224,7 @@ mod tests {
             .commit_index(proto::CommitIndexRequest {
                 index_name: "test_index".to_string(),
                 with_hotcache: false,
+                transaction_id: None,
             })
             .await?;
         Ok(r)
@@ -278,6 +280,11 @@ mod tests {
                             explain: false,
                             fields: vec![],
                             excluded_fields: vec![],
+                            collapse: None,
+                            snippet_field_configs: Default::default(),
+                            min_score: None,
+                            score_normalization: proto::ScoreNormalization::None.into(),
+                            rescore: None,
                         })),
                     },
                     proto::Collector {
@@ -308,6 +315,7 @@ mod tests {
                 is_fieldnorms_scoring_enabled: None,
                 load_cache: None,
                 store_cache: None,
+                timeout_ms: None,
             }))
             .await
             .unwrap()
@@ -325,6 +333,7 @@ mod tests {
                         ..Default::default()
                     }],
                     has_next: false,
+                    groups_count: None,
                 })),
             }
         );
@@ -353,6 +362,7 @@ mod tests {
                 is_fieldnorms_scoring_enabled: None,
                 load_cache: None,
                 store_cache: None,
+                timeout_ms: None,
             }))
             .await
             .unwrap()
@@ -370,6 +380,7 @@ mod tests {
                         ..Default::default()
                     }],
                     has_next: false,
+                    groups_count: None,
                 })),
             }
         );
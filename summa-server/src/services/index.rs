@@ -1,16 +1,19 @@
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::components::{ConsumerManager, PreparedConsumption};
+use crate::components::{ConsumerManager, PreparedConsumption, StoppedConsumption};
 use crate::errors::SummaServerResult;
 use crate::errors::ValidationError;
 use crate::utils::thread_handler::{ControlMessage, ThreadHandler};
 use async_broadcast::Receiver;
-use summa_core::components::{cleanup_index, IndexHolder, IndexRegistry};
+use summa_core::components::{
+    cleanup_index, IndexHolder, IndexRegistry, IndexWriterHolder, IngestPipeline, NamedFieldDocument, QueryProfile, SearchDeadline, SummaDocument,
+};
+use summa_core::configs::core::SearchLimits;
 use summa_core::configs::ConfigProxy;
 use summa_core::configs::PartialProxy;
 use summa_core::directories::DefaultExternalRequestGenerator;
@@ -19,11 +22,25 @@ use summa_core::utils::sync::{Handler, OwningHandler};
 use summa_core::validators;
 use summa_proto::proto;
 use tantivy::index::SegmentId;
+use tantivy::schema::{Field, Schema};
 use tantivy::store::ZstdCompressor;
-use tantivy::IndexBuilder;
-use tokio::sync::RwLock;
+use tantivy::{IndexBuilder, TantivyDocument};
+use tokio::sync::{Mutex, OwnedRwLockWriteGuard, RwLock};
 use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
 
+const DEFAULT_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(60);
+/// Number of documents written to the source index during reindexing that may wait for replaying, reindexing fails on overflow
+const REINDEX_MIRROR_CAPACITY: usize = 100_000;
+
+/// Open transaction of an index, see `BeginTransactionRequest`
+struct Transaction {
+    transaction_id: String,
+    timeout: Duration,
+    deadline: tokio::time::Instant,
+    /// Set while the transaction is being committed or rolled back, such transactions cannot be used anymore
+    is_finishing: bool,
+}
+
 /// `services::Index` is responsible for indices lifecycle. Here lives indices creation and deletion as well as committing and indexing new documents.
 #[derive(Clone)]
 pub struct Index {
@@ -32,7 +49,10 @@ pub struct Index {
     consumer_manager: Arc<RwLock<ConsumerManager>>,
     should_terminate: Arc<AtomicBool>,
     autocommit_thread: Arc<RwLock<Option<ThreadHandler<SummaServerResult<()>>>>>,
+    expiry_thread: Arc<RwLock<Option<ThreadHandler<SummaServerResult<()>>>>>,
     service_thread: Arc<RwLock<Option<ThreadHandler<SummaServerResult<()>>>>>,
+    /// Open transactions by index names
+    transactions: Arc<Mutex<HashMap<String, Transaction>>>,
 }
 
 #[derive(Default)]
@@ -94,7 +114,9 @@ impl Index {
             consumer_manager: Arc::default(),
             should_terminate: Arc::default(),
             autocommit_thread: Arc::default(),
+            expiry_thread: Arc::default(),
             service_thread: Arc::default(),
+            transactions: Arc::default(),
         })
     }
 
@@ -125,6 +147,7 @@ impl Index {
             let index_engine_config_holder = self.derive_configs(&index_name).await;
             let merge_policy = core_config.indices[&index_name].merge_policy.clone();
             let query_parser_config = core_config.indices[&index_name].query_parser_config.as_ref().cloned().unwrap_or_default();
+            let search_limits = core_config.indices[&index_name].search_limits.clone();
             let default_fields = query_parser_config.default_fields.clone();
             let index_name_clone = index_name.clone();
             let index_holder = tokio::task::spawn_blocking(move || {
@@ -135,6 +158,7 @@ impl Index {
                     index_engine_config_holder,
                     merge_policy,
                     query_parser_config,
+                    search_limits,
                 )
             })
             .await??;
@@ -199,12 +223,21 @@ impl Index {
         let index_engine_config_holder = self.derive_configs(index_name).await;
         let merge_policy = index_engine_config.merge_policy.clone();
         let query_parser_config = index_engine_config.query_parser_config.as_ref().cloned().unwrap_or_default();
+        let search_limits = index_engine_config.search_limits.clone();
         let index_name = index_name.to_string();
         Ok(self
             .index_registry
             .add(
                 tokio::task::spawn_blocking(move || {
-                    IndexHolder::create_holder(&core_config, index, &index_name, index_engine_config_holder, merge_policy, query_parser_config)
+                    IndexHolder::create_holder(
+                        &core_config,
+                        index,
+                        &index_name,
+                        index_engine_config_holder,
+                        merge_policy,
+                        query_parser_config,
+                        search_limits,
+                    )
                 })
                 .await??,
             )
@@ -216,6 +249,7 @@ impl Index {
     pub async fn attach_index(&self, attach_index_request: proto::AttachIndexRequest) -> SummaServerResult<Handler<IndexHolder>> {
         let index_path = self.server_config.read().await.get().get_path_for_index_data(&attach_index_request.index_name);
         let query_parser_config = attach_index_request.query_parser_config;
+        let search_limits = attach_index_request.search_limits;
         let (index, index_engine_config) = match attach_index_request.index_engine {
             None | Some(proto::attach_index_request::IndexEngine::File(proto::AttachFileEngineRequest {})) => {
                 if !index_path.exists() {
@@ -229,6 +263,8 @@ impl Index {
                     config: Some(proto::index_engine_config::Config::File(file_engine_config)),
                     merge_policy: attach_index_request.merge_policy,
                     query_parser_config: query_parser_config.clone(),
+                    search_limits: search_limits.clone(),
+                    slow_query_threshold_ms: attach_index_request.slow_query_threshold_ms,
                 };
                 (index, index_engine_config)
             }
@@ -244,6 +280,8 @@ impl Index {
                     config: Some(proto::index_engine_config::Config::Remote(remote_engine_config)),
                     merge_policy: attach_index_request.merge_policy,
                     query_parser_config: query_parser_config.clone(),
+                    search_limits: search_limits.clone(),
+                    slow_query_threshold_ms: attach_index_request.slow_query_threshold_ms,
                 };
                 (index, index_engine_config)
             }
@@ -284,6 +322,176 @@ impl Index {
         Ok(documents)
     }
 
+    /// Creates an index from `reindex_request.target_index` and copies documents of the source index into it
+    ///
+    /// Documents written to the source index during copying are mirrored and replayed to the target index after the copy.
+    /// Writes to the source index are blocked during the final catch-up, and then all aliases of the source index are moved
+    /// to the target index at once. Documents of the final catch-up become searchable in the target index shortly after
+    /// swapping aliases. Reindexing continues in background if the returned receiver is dropped.
+    ///
+    /// Deletions made during reindexing are not replayed. Documents written while mirroring is being enabled may be copied
+    /// twice if the target index has no unique fields. Reindexing fails if more than `REINDEX_MIRROR_CAPACITY` documents
+    /// are waiting for replaying, and the target index is deleted on any failure.
+    #[instrument(skip_all, fields(source_index_name = ?reindex_request.source_index_name))]
+    pub async fn reindex(
+        &self,
+        reindex_request: proto::ReindexRequest,
+    ) -> SummaServerResult<tokio::sync::mpsc::Receiver<SummaServerResult<proto::ReindexResponse>>> {
+        let source_index_holder = self.get_index_holder(&reindex_request.source_index_name).await?;
+        let target_index_request = reindex_request
+            .target_index
+            .ok_or_else(|| ValidationError::InvalidArgument("missing target_index".to_owned()))?;
+        // Empty processors make the legacy pipeline in `IngestPipeline::new`, it is applied by the target index itself
+        let transform = if reindex_request.transform.is_empty() {
+            IngestPipeline::default()
+        } else {
+            IngestPipeline::new(&validators::parse_schema(&target_index_request.schema)?, &reindex_request.transform)?
+        };
+        let target_index_holder = self.create_index(target_index_request).await?;
+        let conflict_strategy = reindex_request
+            .conflict_strategy
+            .and_then(proto::ConflictStrategy::from_i32)
+            .unwrap_or(target_index_holder.conflict_strategy());
+        let convert_document = {
+            let source_schema = source_index_holder.schema().clone();
+            let multi_fields = source_index_holder.multi_fields().clone();
+            let target_schema = target_index_holder.schema().clone();
+            let renamed_fields = Arc::new(reindex_request.renamed_fields);
+            let transform = Arc::new(transform);
+            move |document: &TantivyDocument| {
+                let mut document = reindex_document(&source_schema, &multi_fields, &target_schema, &renamed_fields, document)?;
+                transform.process(&mut document)?;
+                Ok(document)
+            }
+        };
+
+        let (mirror_sender, mirror_receiver) = tokio::sync::mpsc::channel(REINDEX_MIRROR_CAPACITY);
+        source_index_holder.index_writer_holder()?.write().await.set_mirror(Some(mirror_sender));
+        if let Err(error) = self.commit_and_restart_consumption(&source_index_holder, None, false).await {
+            self.abort_reindex(&source_index_holder, target_index_holder).await;
+            return Err(error);
+        }
+
+        let (progress_sender, progress_receiver) = tokio::sync::mpsc::channel(16);
+        let index_service = self.clone();
+        let span = tracing::Span::current();
+        tokio::spawn(
+            async move {
+                let result = index_service
+                    .reindex_documents(
+                        &source_index_holder,
+                        &target_index_holder,
+                        convert_document,
+                        conflict_strategy,
+                        mirror_receiver,
+                        &progress_sender,
+                    )
+                    .await;
+                if let Err(error) = &result {
+                    error!(action = "reindex_failed", error = ?error);
+                    index_service.abort_reindex(&source_index_holder, target_index_holder).await;
+                }
+                let _ = progress_sender.send(result).await;
+            }
+            .instrument(span),
+        );
+        Ok(progress_receiver)
+    }
+
+    /// Stops mirroring of the source index and deletes the partially filled target index
+    async fn abort_reindex(&self, source_index_holder: &Handler<IndexHolder>, target_index_holder: Handler<IndexHolder>) {
+        if let Ok(index_writer_holder) = source_index_holder.index_writer_holder() {
+            index_writer_holder.write().await.set_mirror(None);
+        }
+        let index_name = target_index_holder.index_name().to_owned();
+        drop(target_index_holder);
+        if let Err(error) = self.delete_index(proto::DeleteIndexRequest { index_name }).await {
+            warn!(action = "skip_deleting_reindex_target", error = ?error);
+        }
+    }
+
+    /// Copies the last committed snapshot of the source index and then replays mirrored documents
+    async fn reindex_documents(
+        &self,
+        source_index_holder: &Handler<IndexHolder>,
+        target_index_holder: &Handler<IndexHolder>,
+        convert_document: impl Fn(&TantivyDocument) -> SummaServerResult<TantivyDocument> + Clone + Send + Sync + 'static,
+        conflict_strategy: proto::ConflictStrategy,
+        mut mirror_receiver: tokio::sync::mpsc::Receiver<TantivyDocument>,
+        progress_sender: &tokio::sync::mpsc::Sender<SummaServerResult<proto::ReindexResponse>>,
+    ) -> SummaServerResult<proto::ReindexResponse> {
+        let searcher = source_index_holder.index_reader().searcher();
+        let mut progress = proto::ReindexResponse {
+            total_documents: searcher.num_docs(),
+            ..Default::default()
+        };
+        let _ = progress_sender.send(Ok(progress.clone())).await;
+        let mut source_documents_receiver = source_index_holder
+            .documents(&searcher, &None, {
+                let convert_document = convert_document.clone();
+                move |document| Some(convert_document(&document))
+            })
+            .await?;
+        let mut target_index_writer = target_index_holder.index_writer_holder()?.clone().read_owned().await;
+        while let Some(document) = source_documents_receiver.recv().await {
+            target_index_writer.index_document(document?, conflict_strategy)?;
+            progress.copied_documents += 1;
+            if progress.copied_documents % 100_000 == 0 {
+                info!(
+                    action = "copied",
+                    documents = progress.copied_documents,
+                    total_documents = progress.total_documents
+                );
+                let _ = progress_sender.send(Ok(progress.clone())).await;
+                drop(target_index_writer);
+                target_index_writer = target_index_holder.index_writer_holder()?.clone().read_owned().await;
+            }
+        }
+
+        // Mirrored documents are newer than copied ones, so they are replayed only after the whole snapshot is copied
+        while let Ok(document) = mirror_receiver.try_recv() {
+            target_index_writer.index_document(convert_document(&document)?, conflict_strategy)?;
+            progress.mirrored_documents += 1;
+        }
+        info!(action = "replayed", mirrored_documents = progress.mirrored_documents);
+        let _ = progress_sender.send(Ok(progress.clone())).await;
+        drop(target_index_writer);
+        // The bulk of documents is committed while the source index is still writable
+        self.commit_and_restart_consumption(target_index_holder, None, false).await?;
+
+        debug!(action = "acquiring_source_index_writer_for_write");
+        let mut source_index_writer = source_index_holder.index_writer_holder()?.write().await;
+        if !source_index_writer.is_mirrored() {
+            return Err(crate::errors::Error::Reindex(format!(
+                "more than {REINDEX_MIRROR_CAPACITY} documents have been written to the source index during reindexing"
+            )));
+        }
+        source_index_writer.set_mirror(None);
+        let target_index_writer = target_index_holder.index_writer_holder()?.read().await;
+        while let Some(document) = mirror_receiver.recv().await {
+            target_index_writer.index_document(convert_document(&document)?, conflict_strategy)?;
+            progress.mirrored_documents += 1;
+        }
+        drop(target_index_writer);
+
+        let mut server_config = self.server_config.write().await;
+        let aliases = server_config.get().core.get_index_aliases_for_index(source_index_holder.index_name());
+        for alias in &aliases {
+            server_config.get_mut().core.set_index_alias(alias, target_index_holder.index_name())?;
+        }
+        server_config.commit().await?;
+        drop(server_config);
+        // The source index writer must be released before committing, otherwise concurrent commits of the source index
+        // that are waiting for its writer would block committing of the target index
+        drop(source_index_writer);
+        self.commit_and_restart_consumption(target_index_holder, None, false).await?;
+        info!(action = "swapped_aliases", aliases = ?aliases, mirrored_documents = progress.mirrored_documents);
+
+        progress.swapped_aliases = aliases;
+        progress.is_finished = true;
+        Ok(progress)
+    }
+
     /// Create consumer and insert it into the consumer registry. Add it to the `IndexHolder` afterwards.
     #[instrument(skip_all, fields(index_name = ?create_index_request.index_name))]
     pub async fn create_index(&self, create_index_request: proto::CreateIndexRequest) -> SummaServerResult<Handler<IndexHolder>> {
@@ -319,6 +527,8 @@ impl Index {
                     })),
                     merge_policy: create_index_request.merge_policy,
                     query_parser_config,
+                    search_limits: create_index_request.search_limits.clone(),
+                    slow_query_threshold_ms: create_index_request.slow_query_threshold_ms,
                 };
                 (index, index_engine_config)
             }
@@ -330,6 +540,8 @@ impl Index {
                     })),
                     merge_policy: create_index_request.merge_policy,
                     query_parser_config,
+                    search_limits: create_index_request.search_limits.clone(),
+                    slow_query_threshold_ms: create_index_request.slow_query_threshold_ms,
                 };
                 (index, index_engine_config)
             }
@@ -409,15 +621,20 @@ impl Index {
     /// Delete consumer from the consumer registry and from `IndexHolder` afterwards.
     #[instrument(skip_all, fields(consumer_name = ?delete_consumer_request.consumer_name))]
     pub async fn delete_consumer(&self, delete_consumer_request: proto::DeleteConsumerRequest) -> SummaServerResult<proto::DeleteConsumerResponse> {
+        let index_holder = self.consumer_manager.read().await.find_index_holder_for(&delete_consumer_request.consumer_name);
+        if let Some(index_holder) = &index_holder {
+            if self.has_transaction(index_holder.index_name()).await {
+                return Err(ValidationError::ExistingTransaction(index_holder.index_name().to_string()).into());
+            }
+        }
         let mut server_config = self.server_config.write().await;
         if server_config.get_mut().consumers.remove(&delete_consumer_request.consumer_name).is_none() {
             return Err(ValidationError::MissingConsumer(delete_consumer_request.consumer_name.to_string()).into());
         }
         server_config.commit().await?;
         drop(server_config);
-        let index_holder = self.consumer_manager.read().await.find_index_holder_for(&delete_consumer_request.consumer_name);
         if let Some(index_holder) = index_holder {
-            let prepared_consumption = self.commit(&index_holder, false).await?;
+            let prepared_consumption = self.commit(&index_holder, None, false).await?;
             if let Some(prepared_consumption) = prepared_consumption {
                 prepared_consumption.on_delete().await?;
             }
@@ -435,7 +652,12 @@ impl Index {
             debug!(action = "acquiring_consumer_manager_for_write");
             self.consumer_manager.write().await.stop().await?;
             for index_holder in self.index_registry.index_holders_cloned().await.values() {
-                self.commit(index_holder, false).await?;
+                // Open transactions are left uncommitted
+                if self.has_transaction(index_holder.index_name()).await {
+                    warn!(action = "skipped_commit", index_name = ?index_holder.index_name(), reason = "open_transaction");
+                    continue;
+                }
+                self.commit(index_holder, None, false).await?;
             }
         }
         Ok(())
@@ -447,8 +669,23 @@ impl Index {
     }
 
     /// Commits all and restarts consuming threads
-    pub async fn commit_and_restart_consumption(&self, index_holder: &Handler<IndexHolder>, with_hotcache: bool) -> SummaServerResult<()> {
-        let prepared_consumption = self.commit(index_holder, with_hotcache).await?;
+    pub async fn commit_and_restart_consumption(
+        &self,
+        index_holder: &Handler<IndexHolder>,
+        transaction_id: Option<&str>,
+        with_hotcache: bool,
+    ) -> SummaServerResult<()> {
+        let prepared_consumption = self.commit(index_holder, transaction_id, with_hotcache).await?;
+        if let Some(prepared_consumption) = prepared_consumption {
+            debug!(action = "acquiring_consumer_manager_for_write");
+            self.consumer_manager.write().await.start_consuming(index_holder, prepared_consumption).await?;
+        }
+        Ok(())
+    }
+
+    /// Rollbacks everything and restarts consuming threads from the last committed offsets
+    pub async fn rollback_and_restart_consumption(&self, index_holder: &Handler<IndexHolder>, transaction_id: Option<&str>) -> SummaServerResult<()> {
+        let prepared_consumption = self.rollback(index_holder, transaction_id).await?;
         if let Some(prepared_consumption) = prepared_consumption {
             debug!(action = "acquiring_consumer_manager_for_write");
             self.consumer_manager.write().await.start_consuming(index_holder, prepared_consumption).await?;
@@ -456,6 +693,154 @@ impl Index {
         Ok(())
     }
 
+    /// Opens transaction for the index that is rolled back if it is not used for `timeout`
+    #[instrument(skip(self, index_holder), fields(index_name = ?index_holder.index_name()))]
+    pub async fn begin_transaction(&self, index_holder: &Handler<IndexHolder>, timeout: Option<Duration>) -> SummaServerResult<String> {
+        let index_name = index_holder.index_name().to_string();
+        let timeout = timeout.unwrap_or(DEFAULT_TRANSACTION_TIMEOUT);
+        let transaction_id = format!("{:032x}", rand::random::<u128>());
+        debug!(action = "acquiring_transactions");
+        match self.transactions.lock().await.entry(index_name.clone()) {
+            Entry::Occupied(_) => return Err(ValidationError::ExistingTransaction(index_name).into()),
+            Entry::Vacant(entry) => {
+                entry.insert(Transaction {
+                    transaction_id: transaction_id.clone(),
+                    timeout,
+                    deadline: tokio::time::Instant::now() + timeout,
+                    is_finishing: false,
+                });
+            }
+        }
+        info!(action = "began_transaction", transaction_id = ?transaction_id, timeout = ?timeout);
+        tokio::spawn(
+            self.clone()
+                .watch_transaction(index_name, transaction_id.clone())
+                .instrument(info_span!(parent: None, "transaction_watchdog")),
+        );
+        Ok(transaction_id)
+    }
+
+    /// Checks that `transaction_id` is open for the index and prolongs its timeout
+    pub async fn touch_transaction(&self, index_name: &str, transaction_id: &str) -> SummaServerResult<()> {
+        debug!(action = "acquiring_transactions");
+        match self.transactions.lock().await.get_mut(index_name) {
+            Some(transaction) if transaction.transaction_id == transaction_id && !transaction.is_finishing => {
+                transaction.deadline = tokio::time::Instant::now() + transaction.timeout;
+                Ok(())
+            }
+            _ => Err(ValidationError::MissingTransaction(transaction_id.to_string()).into()),
+        }
+    }
+
+    /// Checks that `transaction_id` is the open transaction of the index
+    ///
+    /// Committing and rolling back without a transaction are forbidden while some transaction is open
+    fn check_transaction(transactions: &HashMap<String, Transaction>, index_name: &str, transaction_id: Option<&str>) -> SummaServerResult<()> {
+        match (transactions.get(index_name), transaction_id) {
+            (None, None) => Ok(()),
+            (Some(transaction), Some(transaction_id)) if transaction.transaction_id == transaction_id && !transaction.is_finishing => Ok(()),
+            (Some(_), None) => Err(ValidationError::ExistingTransaction(index_name.to_string()).into()),
+            (_, Some(transaction_id)) => Err(ValidationError::MissingTransaction(transaction_id.to_string()).into()),
+        }
+    }
+
+    /// Checks `transaction_id` and marks the transaction as finishing, so it cannot be used concurrently with committing or rolling back
+    async fn begin_finishing_transaction(&self, index_name: &str, transaction_id: Option<&str>) -> SummaServerResult<()> {
+        debug!(action = "acquiring_transactions");
+        let mut transactions = self.transactions.lock().await;
+        Self::check_transaction(&transactions, index_name, transaction_id)?;
+        if let Some(transaction) = transactions.get_mut(index_name) {
+            transaction.is_finishing = true;
+        }
+        Ok(())
+    }
+
+    /// Closes `transaction_id` once the index has been committed or rolled back, or makes it usable again if it has failed
+    async fn end_finishing_transaction(&self, index_name: &str, transaction_id: Option<&str>, is_finished: bool) {
+        let Some(transaction_id) = transaction_id else { return };
+        debug!(action = "acquiring_transactions");
+        let mut transactions = self.transactions.lock().await;
+        if is_finished {
+            transactions.remove(index_name);
+            info!(action = "finished_transaction", transaction_id = transaction_id);
+        } else if let Some(transaction) = transactions.get_mut(index_name) {
+            transaction.is_finishing = false;
+        }
+    }
+
+    /// Stops consuming and locks the index writer for committing or rolling back `transaction_id`
+    ///
+    /// Transactions are checked once more after the index writer is locked, so writes of a transaction that begins meanwhile
+    /// wait for the writer and are not committed or rolled back partially
+    async fn lock_for_finishing(
+        &self,
+        index_holder: &Handler<IndexHolder>,
+        transaction_id: Option<&str>,
+    ) -> SummaServerResult<(Option<StoppedConsumption>, OwnedRwLockWriteGuard<IndexWriterHolder>)> {
+        debug!(action = "acquiring_transactions");
+        Self::check_transaction(&*self.transactions.lock().await, index_holder.index_name(), transaction_id)?;
+        debug!(action = "acquiring_consumer_manager_for_write");
+        let stopped_consumption = self.consumer_manager.write().await.stop_consuming_for(index_holder).await?;
+        debug!(action = "acquiring_index_writer_for_write");
+        let index_writer = index_holder.index_writer_holder()?.clone().write_owned().await;
+        if let Err(error) = self.begin_finishing_transaction(index_holder.index_name(), transaction_id).await {
+            drop(index_writer);
+            if let Some(stopped_consumption) = stopped_consumption {
+                debug!(action = "acquiring_consumer_manager_for_write");
+                self.consumer_manager
+                    .write()
+                    .await
+                    .start_consuming(index_holder, stopped_consumption.ignore())
+                    .await?;
+            }
+            return Err(error);
+        }
+        Ok((stopped_consumption, index_writer))
+    }
+
+    /// Whether the index has an open transaction, such indices are not committed by service threads
+    pub async fn has_transaction(&self, index_name: &str) -> bool {
+        self.transactions.lock().await.contains_key(index_name)
+    }
+
+    /// Rolls the transaction back once its deadline passes, so clients that vanished do not block autocommits forever
+    async fn watch_transaction(self, index_name: String, transaction_id: String) {
+        loop {
+            let deadline = match self.transactions.lock().await.get(&index_name) {
+                Some(transaction) if transaction.transaction_id == transaction_id => transaction.deadline,
+                _ => return,
+            };
+            if tokio::time::Instant::now() < deadline {
+                tokio::time::sleep_until(deadline).await;
+                continue;
+            }
+            {
+                debug!(action = "acquiring_transactions");
+                let mut transactions = self.transactions.lock().await;
+                match transactions.get_mut(&index_name) {
+                    Some(transaction)
+                        if transaction.transaction_id == transaction_id && !transaction.is_finishing && transaction.deadline <= tokio::time::Instant::now() =>
+                    {
+                        transaction.is_finishing = true;
+                    }
+                    Some(transaction) if transaction.transaction_id == transaction_id && !transaction.is_finishing => continue,
+                    _ => return,
+                }
+            }
+            warn!(action = "transaction_timed_out", index_name = ?index_name, transaction_id = ?transaction_id);
+            match self.get_index_holder(&index_name).await {
+                Ok(index_holder) => {
+                    if let Err(error) = self.rollback_and_restart_consumption_unchecked(&index_holder).await {
+                        error!(action = "rollback_failed", error = ?error);
+                    }
+                }
+                Err(error) => warn!(action = "missing_index", error = ?error),
+            }
+            self.end_finishing_transaction(&index_name, Some(&transaction_id), true).await;
+            return;
+        }
+    }
+
     /// Commits everything and restarts consuming threads
     pub async fn try_commit_and_restart_consumption(&self, index_holder: &Handler<IndexHolder>) -> SummaServerResult<()> {
         let prepared_consumption = self.try_commit(index_holder).await?;
@@ -467,22 +852,30 @@ impl Index {
     }
 
     /// Commits all without restarting consuming threads
+    ///
+    /// `transaction_id` must be set if the index has an open transaction, the transaction is finished by the commit then
     #[instrument(skip(self, index_holder), fields(index_name = ?index_holder.index_name()))]
-    pub async fn commit(&self, index_holder: &Handler<IndexHolder>, with_hotcache: bool) -> SummaServerResult<Option<PreparedConsumption>> {
-        debug!(action = "acquiring_consumer_manager_for_write");
-        let stopped_consumption = self.consumer_manager.write().await.stop_consuming_for(index_holder).await?;
-        debug!(action = "acquiring_index_writer_for_write");
-        let mut index_writer = index_holder.index_writer_holder()?.clone().write_owned().await;
-        let index_holder = index_holder.clone();
+    pub async fn commit(
+        &self,
+        index_holder: &Handler<IndexHolder>,
+        transaction_id: Option<&str>,
+        with_hotcache: bool,
+    ) -> SummaServerResult<Option<PreparedConsumption>> {
+        let (stopped_consumption, mut index_writer) = self.lock_for_finishing(index_holder, transaction_id).await?;
+        let index_reader_holder = index_holder.clone();
         let span = tracing::Span::current();
-        tokio::task::spawn_blocking(move || {
+        let result = tokio::task::spawn_blocking(move || {
             span.in_scope(|| {
                 index_writer.commit_and_prepare(with_hotcache)?;
-                index_holder.index_reader().reload()?;
+                index_reader_holder.index_reader().reload()?;
                 Ok::<_, crate::errors::Error>(())
             })
         })
-        .await??;
+        .await
+        .map_err(crate::errors::Error::from)
+        .and_then(|result| result);
+        self.end_finishing_transaction(index_holder.index_name(), transaction_id, result.is_ok()).await;
+        result?;
         let prepared_consumption = match stopped_consumption {
             Some(stopped_consumption) => Some(stopped_consumption.commit_offsets().await?),
             None => None,
@@ -491,20 +884,48 @@ impl Index {
     }
 
     /// Rollbacks everything without restarting consuming threads
+    ///
+    /// `transaction_id` must be set if the index has an open transaction, the transaction is finished by the rollback then
     #[instrument(skip(self, index_holder), fields(index_name = ?index_holder.index_name()))]
-    pub async fn rollback(&self, index_holder: &Handler<IndexHolder>) -> SummaServerResult<Option<PreparedConsumption>> {
-        let mut index_writer = index_holder.index_writer_holder()?.clone().try_write_owned()?;
+    pub async fn rollback(&self, index_holder: &Handler<IndexHolder>, transaction_id: Option<&str>) -> SummaServerResult<Option<PreparedConsumption>> {
+        let (stopped_consumption, index_writer) = self.lock_for_finishing(index_holder, transaction_id).await?;
+        let result = Self::rollback_index_writer(index_writer).await;
+        self.end_finishing_transaction(index_holder.index_name(), transaction_id, result.is_ok()).await;
+        result?;
+        Ok(stopped_consumption.map(|c| c.ignore()))
+    }
+
+    /// Rollbacks and restarts consuming threads regardless of transactions, the caller must mark the transaction as finishing
+    async fn rollback_and_restart_consumption_unchecked(&self, index_holder: &Handler<IndexHolder>) -> SummaServerResult<()> {
         debug!(action = "acquiring_consumer_manager_for_write");
         let stopped_consumption = self.consumer_manager.write().await.stop_consuming_for(index_holder).await?;
+        debug!(action = "acquiring_index_writer_for_write");
+        let index_writer = index_holder.index_writer_holder()?.clone().write_owned().await;
+        Self::rollback_index_writer(index_writer).await?;
+        if let Some(stopped_consumption) = stopped_consumption {
+            debug!(action = "acquiring_consumer_manager_for_write");
+            self.consumer_manager
+                .write()
+                .await
+                .start_consuming(index_holder, stopped_consumption.ignore())
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn rollback_index_writer(mut index_writer: OwnedRwLockWriteGuard<IndexWriterHolder>) -> SummaServerResult<()> {
         let span = tracing::Span::current();
         tokio::task::spawn_blocking(move || span.in_scope(|| index_writer.rollback())).await??;
-        Ok(stopped_consumption.map(|c| c.ignore()))
+        Ok(())
     }
 
-    /// Commits immediately or returns all without restarting consuming threads
+    /// Commits immediately or returns all without restarting consuming threads, fails if the index has an open transaction
     #[instrument(skip(self, index_holder), fields(index_name = ?index_holder.index_name()))]
     pub async fn try_commit(&self, index_holder: &Handler<IndexHolder>) -> SummaServerResult<Option<PreparedConsumption>> {
         let mut index_writer = index_holder.index_writer_holder()?.clone().try_write_owned()?;
+        // Checked after locking the index writer, so writes of transactions begun later are not committed
+        debug!(action = "acquiring_transactions");
+        Self::check_transaction(&*self.transactions.lock().await, index_holder.index_name(), None)?;
         debug!(action = "acquiring_consumer_manager_for_write");
         let stopped_consumption = self.consumer_manager.write().await.stop_consuming_for(index_holder).await?;
         let span = tracing::Span::current();
@@ -538,6 +959,9 @@ impl Index {
                                 info!(action = "autocommit_thread_tick");
                                 let index_holders = index_service.index_registry.index_holders_cloned().await;
                                 for index_holder in index_holders.into_values() {
+                                    if index_service.has_transaction(index_holder.index_name()).await {
+                                        continue;
+                                    }
                                     let result = index_service.try_commit_and_restart_consumption(&index_holder).await;
                                     if let Err(error) = result {
                                         warn!(error = ?error);
@@ -558,6 +982,69 @@ impl Index {
         ));
     }
 
+    async fn setup_expiry_thread(&mut self) {
+        let interval_ms = match self.server_config.read().await.get().core.expiry_interval_ms {
+            Some(interval_ms) => interval_ms,
+            None => return,
+        };
+
+        let index_service = self.clone();
+        let (shutdown_trigger, mut shutdown_tripwire) = async_broadcast::broadcast(1);
+        let mut tick_task = tokio::time::interval(Duration::from_millis(interval_ms));
+
+        debug!(action = "acquiring_expiry_thread_for_write");
+        *self.expiry_thread.write().await = Some(ThreadHandler::new(
+            tokio::spawn(
+                async move {
+                    info!(action = "spawning_expiry_thread", interval_ms = interval_ms);
+                    // The first tick ticks immediately so we skip it
+                    tick_task.tick().await;
+                    loop {
+                        tokio::select! {
+                            _ = tick_task.tick() => {
+                                info!(action = "expiry_thread_tick");
+                                let index_holders = index_service.index_registry.index_holders_cloned().await;
+                                for index_holder in index_holders.into_values() {
+                                    if index_service.has_transaction(index_holder.index_name()).await {
+                                        continue;
+                                    }
+                                    let result = index_service.delete_expired_documents(&index_holder).await;
+                                    if let Err(error) = result {
+                                        warn!(error = ?error);
+                                    }
+                                }
+                            }
+                            _ = &mut shutdown_tripwire.recv() => {
+                                info!(action = "shutdown_expiry_thread");
+                                break;
+                            }
+                        }
+                    }
+                    Ok(())
+                }
+                .instrument(info_span!(parent: None, "expiry_thread")),
+            ),
+            shutdown_trigger,
+        ));
+    }
+
+    /// Deletes expired documents and commits the index if there were any
+    #[instrument(skip(self, index_holder), fields(index_name = ?index_holder.index_name()))]
+    async fn delete_expired_documents(&self, index_holder: &Handler<IndexHolder>) -> SummaServerResult<()> {
+        let expired_documents = index_holder.delete_expired_documents().await?;
+        if expired_documents > 0 {
+            info!(action = "deleted_expired_documents", expired_documents = expired_documents);
+            let prepared_consumption = self.try_commit(index_holder).await?;
+            index_holder.commit_expired_documents(expired_documents);
+            index_holder.index_reader().reload()?;
+            if let Some(prepared_consumption) = prepared_consumption {
+                debug!(action = "acquiring_consumer_manager_for_write");
+                self.consumer_manager.write().await.start_consuming(index_holder, prepared_consumption).await?;
+            }
+        }
+        Ok(())
+    }
+
     async fn setup_service_thread(&mut self) {
         let collector_cache_config = self.server_config.read().await.get().core.collector_cache.clone();
         let ttl_interval_ms = match collector_cache_config.ttl_interval_ms {
@@ -603,6 +1090,7 @@ impl Index {
     #[instrument(skip(self))]
     pub async fn start_threads(&mut self) {
         self.setup_autocommit_thread().await;
+        self.setup_expiry_thread().await;
         self.setup_service_thread().await;
     }
 
@@ -613,6 +1101,10 @@ impl Index {
         if let Some(autocommit_thread) = self.autocommit_thread.write().await.take() {
             autocommit_thread.stop().await??;
         }
+        debug!(action = "acquiring_expiry_thread_for_write");
+        if let Some(expiry_thread) = self.expiry_thread.write().await.take() {
+            expiry_thread.stop().await??;
+        }
         debug!(action = "acquiring_service_thread_for_write");
         if let Some(service_thread) = self.service_thread.write().await.take() {
             service_thread.stop().await??;
@@ -643,33 +1135,89 @@ impl Index {
         unimplemented!()
     }
 
-    /// Search documents
-    pub async fn search(&self, search_request: proto::SearchRequest) -> SummaServerResult<Vec<proto::CollectorOutput>> {
-        let index_holder = self.index_registry.get_index_holder(&search_request.index_alias).await?;
+    /// Runs the search in a separate task, so dropping the returned future stops the search at the next check of `search_deadline`
+    ///
+    /// Searches taking longer than `slow_query_threshold_ms` of the index are written to the slow query log
+    async fn spawn_search(
+        &self,
+        index_holder: Handler<IndexHolder>,
+        search_request: proto::SearchRequest,
+        load_cache: Option<bool>,
+        store_cache: Option<bool>,
+        search_limits: SearchLimits,
+        search_deadline: &SearchDeadline,
+    ) -> SummaServerResult<Vec<proto::CollectorOutput>> {
+        let _cancel_on_drop = search_deadline.cancel_on_drop();
+        let query_profile = QueryProfile::default();
+        let slow_query_threshold_ms = index_holder.index_engine_config().read().await.get().slow_query_threshold_ms;
+        let logged_search_request = slow_query_threshold_ms.is_some().then(|| search_request.clone());
         let query = search_request
             .query
             .and_then(|query| query.query)
             .unwrap_or_else(|| proto::query::Query::All(proto::AllQuery {}));
-        let collector_outputs = index_holder
-            .custom_search_async(
-                &search_request.index_alias,
-                query,
-                search_request.collectors,
-                search_request.is_fieldnorms_scoring_enabled,
-                search_request.load_cache,
-                search_request.store_cache,
-            )
-            .await?;
-        Ok(self.index_registry.finalize_extraction(collector_outputs).await?)
+        let intermediate_results = {
+            let index_holder = index_holder.clone();
+            let search_deadline = search_deadline.clone();
+            let query_profile = query_profile.clone();
+            tokio::spawn(async move {
+                index_holder
+                    .custom_search_async(
+                        &search_request.index_alias,
+                        query,
+                        search_request.collectors,
+                        search_request.is_fieldnorms_scoring_enabled,
+                        load_cache,
+                        store_cache,
+                        &search_limits,
+                        &search_deadline,
+                        &query_profile,
+                    )
+                    .await
+            })
+            .await??
+        };
+        let collector_outputs = self.index_registry.finalize_extraction(intermediate_results, &query_profile).await?;
+        if let (Some(slow_query_threshold_ms), Some(search_request)) = (slow_query_threshold_ms, logged_search_request) {
+            query_profile.log_if_slow(
+                index_holder.index_name(),
+                &search_request,
+                &collector_outputs,
+                search_deadline.is_exceeded(),
+                Duration::from_millis(u64::from(slow_query_threshold_ms)),
+            );
+        }
+        Ok(collector_outputs)
     }
 
     /// Search documents
-    pub async fn constrained_search(&self, mut search_request: proto::SearchRequest) -> SummaServerResult<Vec<proto::CollectorOutput>> {
+    ///
+    /// `search_deadline` is expired after `timeout_ms` of the request and reports if returned outputs are partial
+    pub async fn search(&self, search_request: proto::SearchRequest, search_deadline: &SearchDeadline) -> SummaServerResult<Vec<proto::CollectorOutput>> {
         let index_holder = self.index_registry.get_index_holder(&search_request.index_alias).await?;
-        let query = search_request
+        let (load_cache, store_cache) = (search_request.load_cache, search_request.store_cache);
+        let search_limits = SearchLimits(self.server_config.read().await.get().api.search_limits.clone().unwrap_or_default());
+        self.spawn_search(index_holder, search_request, load_cache, store_cache, search_limits, search_deadline)
+            .await
+    }
+
+    /// Parse query against the index and return the parsed query tree
+    pub async fn parse_query(&self, parse_query_request: proto::ParseQueryRequest) -> SummaServerResult<proto::ParseQueryResponse> {
+        let index_holder = self.index_registry.get_index_holder(&parse_query_request.index_alias).await?;
+        let query = parse_query_request
             .query
             .and_then(|query| query.query)
-            .unwrap_or_else(|| proto::query::Query::All(proto::AllQuery {}));
+            .unwrap_or(proto::query::Query::All(proto::AllQuery {}));
+        Ok(index_holder.parse_query(query).await?)
+    }
+
+    /// Search documents
+    pub async fn constrained_search(
+        &self,
+        mut search_request: proto::SearchRequest,
+        search_deadline: &SearchDeadline,
+    ) -> SummaServerResult<Vec<proto::CollectorOutput>> {
+        let index_holder = self.index_registry.get_index_holder(&search_request.index_alias).await?;
+        let search_limits = SearchLimits(self.server_config.read().await.get().api.public_search_limits.clone().unwrap_or_default());
 
         if search_request.collectors.len() > 2 {
             return Err(crate::errors::Error::NotAllowed);
@@ -694,17 +1242,8 @@ impl Index {
             }
         }
 
-        let collector_outputs = index_holder
-            .custom_search_async(
-                &search_request.index_alias,
-                query,
-                search_request.collectors,
-                search_request.is_fieldnorms_scoring_enabled,
-                Some(true),
-                Some(true),
-            )
-            .await?;
-        Ok(self.index_registry.finalize_extraction(collector_outputs).await?)
+        self.spawn_search(index_holder, search_request, Some(true), Some(true), search_limits, search_deadline)
+            .await
     }
 
     /// Merge several segments into a single one
@@ -754,6 +1293,28 @@ impl Index {
     }
 }
 
+/// Converts a document of the source index into a document of the target index, renaming fields listed in `renamed_fields`
+///
+/// Fields that are missing in the target schema are dropped.
+fn reindex_document(
+    source_schema: &Schema,
+    multi_fields: &HashSet<Field>,
+    target_schema: &Schema,
+    renamed_fields: &HashMap<String, String>,
+    document: &TantivyDocument,
+) -> SummaServerResult<TantivyDocument> {
+    let named_field_document = NamedFieldDocument::from_document(source_schema, &None, multi_fields, document);
+    let json_object = named_field_document
+        .0
+        .into_iter()
+        .map(|(field_name, value)| {
+            let field_name = renamed_fields.get(field_name).map(String::as_str).unwrap_or(field_name);
+            Ok((field_name.to_owned(), serde_json::to_value(value)?))
+        })
+        .collect::<SummaServerResult<serde_json::Map<_, _>>>()?;
+    Ok(SummaDocument::json_object_to_doc(target_schema, json_object)?)
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use std::default::Default;
@@ -792,6 +1353,8 @@ pub(crate) mod tests {
                 index_engine: Some(index_engine),
                 merge_policy: None,
                 query_parser_config: None,
+                search_limits: None,
+                slow_query_threshold_ms: None,
             })
             .await
     }
@@ -810,6 +1373,8 @@ pub(crate) mod tests {
                 index_engine: Some(proto::create_index_request::IndexEngine::Memory(proto::CreateMemoryEngineRequest {})),
                 merge_policy: None,
                 query_parser_config: None,
+                search_limits: None,
+                slow_query_threshold_ms: None,
             },)
             .await
             .is_ok());
@@ -823,6 +1388,8 @@ pub(crate) mod tests {
                 index_engine: Some(proto::create_index_request::IndexEngine::Memory(proto::CreateMemoryEngineRequest {})),
                 merge_policy: None,
                 query_parser_config: None,
+                search_limits: None,
+                slow_query_threshold_ms: None,
             },)
             .await
             .is_err());
@@ -848,6 +1415,8 @@ pub(crate) mod tests {
                 index_engine: Some(proto::create_index_request::IndexEngine::File(proto::CreateFileEngineRequest {})),
                 merge_policy: None,
                 query_parser_config: None,
+                search_limits: None,
+                slow_query_threshold_ms: None,
             })
             .await?;
         assert!(index_service
@@ -866,6 +1435,8 @@ pub(crate) mod tests {
                 index_engine: Some(proto::create_index_request::IndexEngine::Memory(proto::CreateMemoryEngineRequest {})),
                 merge_policy: None,
                 query_parser_config: None,
+                search_limits: None,
+                slow_query_threshold_ms: None,
             },)
             .await
             .is_ok());
@@ -894,7 +1465,7 @@ pub(crate) mod tests {
         index_holder
             .index_document(generate_unique_document(index_holder.schema(), "testtitle").as_bytes(), false)
             .await?;
-        index_service.commit(&index_holder, false).await?;
+        index_service.commit(&index_holder, None, false).await?;
 
         let search_response = index_holder
             .search_async(
@@ -914,6 +1485,191 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_reindex() -> SummaServerResult<()> {
+        logging::tests::initialize_default_once();
+        let schema = create_test_schema();
+
+        let root_path = tempdir::TempDir::new("summa_test").unwrap();
+        let data_path = root_path.path().join("data");
+
+        let index_service = create_test_index_service(&data_path).await;
+        let index_holder = create_test_index_holder(
+            &index_service,
+            &schema,
+            proto::create_index_request::IndexEngine::Memory(proto::CreateMemoryEngineRequest {}),
+        )
+        .await?;
+        for d in generate_documents(index_holder.schema(), 100) {
+            index_holder.index_document(d.as_bytes(), false).await?;
+        }
+        index_service.commit(&index_holder, None, false).await?;
+        let mut server_config = index_service.server_config().write().await;
+        server_config.get_mut().core.set_index_alias("test_alias", "test_index")?;
+        server_config.commit().await?;
+        drop(server_config);
+
+        let reindex_request = |index_name: &str, lowercased_field: &str| proto::ReindexRequest {
+            source_index_name: "test_index".to_owned(),
+            target_index: Some(proto::CreateIndexRequest {
+                index_name: index_name.to_owned(),
+                schema: serde_yaml::to_string(&schema).unwrap(),
+                index_engine: Some(proto::create_index_request::IndexEngine::Memory(proto::CreateMemoryEngineRequest {})),
+                ..Default::default()
+            }),
+            transform: vec![proto::IngestProcessor {
+                processor: Some(proto::ingest_processor::Processor::Lowercase(proto::LowercaseProcessor {
+                    field: lowercased_field.to_owned(),
+                })),
+            }],
+            ..Default::default()
+        };
+        assert!(index_service.reindex(reindex_request("test_index_invalid", "missing_field")).await.is_err());
+        assert!(index_service.get_index_holder("test_index_invalid").await.is_err());
+
+        let mut progress_receiver = index_service.reindex(reindex_request("test_index_reindexed", "title")).await?;
+        let mut last_progress = None;
+        while let Some(progress) = progress_receiver.recv().await {
+            last_progress = Some(progress?);
+        }
+        let last_progress = last_progress.unwrap();
+        assert!(last_progress.is_finished);
+        assert_eq!(last_progress.total_documents, 100);
+        assert_eq!(last_progress.copied_documents, 100);
+        assert_eq!(last_progress.swapped_aliases, vec!["test_alias".to_owned()]);
+
+        let target_index_holder = index_service.get_index_holder("test_alias").await?;
+        assert_eq!(target_index_holder.index_name(), "test_index_reindexed");
+        assert_eq!(target_index_holder.index_reader().searcher().num_docs(), 100);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reindex_with_concurrent_commits() -> SummaServerResult<()> {
+        logging::tests::initialize_default_once();
+        let schema = create_test_schema();
+
+        let root_path = tempdir::TempDir::new("summa_test").unwrap();
+        let data_path = root_path.path().join("data");
+
+        let index_service = create_test_index_service(&data_path).await;
+        let index_holder = create_test_index_holder(
+            &index_service,
+            &schema,
+            proto::create_index_request::IndexEngine::Memory(proto::CreateMemoryEngineRequest {}),
+        )
+        .await?;
+        for d in generate_documents(index_holder.schema(), 100) {
+            index_holder.index_document(d.as_bytes(), false).await?;
+        }
+        index_service.commit(&index_holder, None, false).await?;
+
+        let mut progress_receiver = index_service
+            .reindex(proto::ReindexRequest {
+                source_index_name: "test_index".to_owned(),
+                target_index: Some(proto::CreateIndexRequest {
+                    index_name: "test_index_reindexed".to_owned(),
+                    schema: serde_yaml::to_string(&schema).unwrap(),
+                    index_engine: Some(proto::create_index_request::IndexEngine::Memory(proto::CreateMemoryEngineRequest {})),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await?;
+        let is_reindexing = Arc::new(AtomicBool::new(true));
+        let committer = tokio::spawn({
+            let index_service = index_service.clone();
+            let index_holder = index_holder.clone();
+            let is_reindexing = is_reindexing.clone();
+            async move {
+                while is_reindexing.load(Ordering::Acquire) {
+                    for d in generate_documents(index_holder.schema(), 10) {
+                        index_holder.index_document(d.as_bytes(), false).await?;
+                    }
+                    index_service.commit_and_restart_consumption(&index_holder, None, false).await?;
+                }
+                Ok::<_, crate::errors::Error>(())
+            }
+        });
+        let last_progress = tokio::time::timeout(Duration::from_secs(60), async {
+            let mut last_progress = None;
+            while let Some(progress) = progress_receiver.recv().await {
+                last_progress = Some(progress?);
+            }
+            Ok::<_, crate::errors::Error>(last_progress)
+        })
+        .await
+        .expect("reindexing is deadlocked by concurrent commits")?
+        .unwrap();
+        is_reindexing.store(false, Ordering::Release);
+        tokio::time::timeout(Duration::from_secs(60), committer)
+            .await
+            .expect("commits are deadlocked by reindexing")
+            .unwrap()?;
+
+        assert!(last_progress.is_finished);
+        assert_eq!(last_progress.copied_documents, 100);
+        let target_index_holder = index_service.get_index_holder("test_index_reindexed").await?;
+        assert_eq!(
+            target_index_holder.index_reader().searcher().num_docs(),
+            last_progress.copied_documents + last_progress.mirrored_documents
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transactions() -> SummaServerResult<()> {
+        logging::tests::initialize_default_once();
+        let schema = create_test_schema();
+
+        let root_path = tempdir::TempDir::new("summa_test").unwrap();
+        let data_path = root_path.path().join("data");
+
+        let index_service = create_test_index_service(&data_path).await;
+        let index_holder = create_test_index_holder(
+            &index_service,
+            &schema,
+            proto::create_index_request::IndexEngine::Memory(proto::CreateMemoryEngineRequest {}),
+        )
+        .await?;
+        let transaction_id = index_service.begin_transaction(&index_holder, None).await?;
+        assert!(index_service.begin_transaction(&index_holder, None).await.is_err());
+        assert!(index_service.has_transaction("test_index").await);
+        for d in generate_documents(index_holder.schema(), 100) {
+            index_holder.index_document(d.as_bytes(), false).await?;
+        }
+        index_service.touch_transaction("test_index", &transaction_id).await?;
+        assert!(index_service.touch_transaction("test_index", "unknown").await.is_err());
+        assert!(index_service.commit(&index_holder, None, false).await.is_err());
+        assert!(index_service.try_commit(&index_holder).await.is_err());
+        assert!(index_service.rollback(&index_holder, None).await.is_err());
+        assert!(index_service.commit(&index_holder, Some("unknown"), false).await.is_err());
+        assert!(index_service.has_transaction("test_index").await);
+        index_service.rollback_and_restart_consumption(&index_holder, Some(&transaction_id)).await?;
+        assert!(!index_service.has_transaction("test_index").await);
+        assert!(index_service.rollback(&index_holder, Some(&transaction_id)).await.is_err());
+        index_service.commit(&index_holder, None, false).await?;
+        assert_eq!(index_holder.index_reader().searcher().num_docs(), 0);
+
+        let transaction_id = index_service.begin_transaction(&index_holder, None).await?;
+        for d in generate_documents(index_holder.schema(), 100) {
+            index_holder.index_document(d.as_bytes(), false).await?;
+        }
+        index_service.commit(&index_holder, Some(&transaction_id), false).await?;
+        assert!(!index_service.has_transaction("test_index").await);
+        assert_eq!(index_holder.index_reader().searcher().num_docs(), 100);
+
+        index_service.begin_transaction(&index_holder, Some(Duration::from_millis(50))).await?;
+        for d in generate_documents(index_holder.schema(), 100) {
+            index_holder.index_document(d.as_bytes(), false).await?;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!index_service.has_transaction("test_index").await);
+        index_service.commit(&index_holder, None, false).await?;
+        assert_eq!(index_holder.index_reader().searcher().num_docs(), 100);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_custom_ranking() -> SummaServerResult<()> {
         logging::tests::initialize_default_once();
@@ -935,7 +1691,7 @@ pub(crate) mod tests {
             for d in generate_documents_with_doc_id_gen_and_rng(AtomicI64::new(1), &mut rng, &schema, 300) {
                 index_holder.index_document(d.as_bytes(), false).await?;
             }
-            index_service.commit(&index_holder, false).await?;
+            index_service.commit(&index_holder, None, false).await?;
         }
         let index_holder_clone = index_holder.clone();
         let index_writer = index_holder.index_writer_holder().unwrap().clone().write_owned().await;
@@ -4,7 +4,8 @@ use js_sys::Uint8Array;
 use prost::Message;
 use serde::Serialize;
 use serde_wasm_bindgen::Serializer;
-use summa_core::components::{IndexHolder, IndexRegistry};
+use summa_core::components::{IndexHolder, IndexRegistry, QueryProfile, SearchDeadline};
+use summa_core::configs::core::SearchLimits;
 use summa_core::configs::{ConfigProxy, DirectProxy};
 use summa_core::directories::DefaultExternalRequestGenerator;
 use summa_core::errors::SummaResult;
@@ -61,6 +62,7 @@ impl WrappedIndexRegistry {
     async fn search_internal(&self, search_request: proto::SearchRequest) -> SummaResult<Vec<proto::CollectorOutput>> {
         info!(action = "search", search_request = ?search_request);
         let index_holder = self.index_registry.get_index_holder(&search_request.index_alias).await?;
+        let query_profile = QueryProfile::default();
         let collector_outputs = index_holder
             .custom_search_async(
                 &search_request.index_alias,
@@ -72,10 +74,13 @@ impl WrappedIndexRegistry {
                 search_request.is_fieldnorms_scoring_enabled,
                 search_request.load_cache,
                 search_request.store_cache,
+                &SearchLimits::default(),
+                &SearchDeadline::from_timeout_ms(search_request.timeout_ms),
+                &query_profile,
             )
             .await?;
         trace!(action = "searched");
-        self.index_registry.finalize_extraction(collector_outputs).await
+        self.index_registry.finalize_extraction(collector_outputs, &query_profile).await
     }
 
     /// Add new index to `WrappedIndexRegistry`
@@ -104,6 +109,7 @@ impl WrappedIndexRegistry {
             _ => unimplemented!(),
         };
         let query_parser_config = index_engine_config.query_parser_config.as_ref().cloned().unwrap_or_default();
+        let search_limits = index_engine_config.search_limits.clone();
         let index_holder = IndexHolder::create_holder(
             self.core_config.read().await.get(),
             index,
@@ -111,6 +117,7 @@ impl WrappedIndexRegistry {
             Arc::new(DirectProxy::new(index_engine_config)),
             None,
             query_parser_config,
+            search_limits,
         )?;
         let index_attributes = index_holder.index_attributes().cloned();
         self.index_registry.add(index_holder).await?;